@@ -0,0 +1,212 @@
+// Animated walls and floors, like the original's p_spec.c: runs of textures
+// or flats like NUKAGE1-3 that take turns showing wherever any of them is
+// used
+use crate::level::Level;
+use crate::wad_reader::WadData;
+use std::collections::HashMap;
+
+// Tics each frame shows for
+const ANIM_SPEED: u32 = 8;
+
+// The original's animdefs: whether it's a wall texture, and the last and
+// first names of each run
+const ANIM_DEFS: [(bool, &str, &str); 22] = [
+    (false, "NUKAGE3", "NUKAGE1"),
+    (false, "FWATER4", "FWATER1"),
+    (false, "SWATER4", "SWATER1"),
+    (false, "LAVA4", "LAVA1"),
+    (false, "BLOOD3", "BLOOD1"),
+    // Doom II
+    (false, "RROCK08", "RROCK05"),
+    (false, "SLIME04", "SLIME01"),
+    (false, "SLIME08", "SLIME05"),
+    (false, "SLIME12", "SLIME09"),
+    (true, "BLODGR4", "BLODGR1"),
+    (true, "SLADRIP3", "SLADRIP1"),
+    (true, "BLODRIP4", "BLODRIP1"),
+    (true, "FIREWALA", "FIREWALL"),
+    (true, "GSTFONT3", "GSTFONT1"),
+    (true, "FIRELAVA", "FIRELAV3"),
+    (true, "FIREMAG3", "FIREMAG1"),
+    (true, "FIREBLU2", "FIREBLU1"),
+    (true, "ROCKRED3", "ROCKRED1"),
+    (true, "BFALL4", "BFALL1"),
+    (true, "SFALL4", "SFALL1"),
+    (true, "WFALL4", "WFALL1"),
+    (true, "DBRAIN4", "DBRAIN1"),
+];
+
+// A run of textures or flats, numbered in the order the WAD has them
+struct Anim {
+    texture: bool,
+    base: usize,
+    count: usize,
+    speed: u32,
+}
+
+pub struct Animations {
+    anims: Vec<Anim>,
+    // Numbers for each texture and flat name, and their names by number
+    texture_numbers: HashMap<String, usize>,
+    flat_numbers: HashMap<String, usize>,
+    texture_names: Vec<String>,
+    flat_names: Vec<String>,
+    // What each texture and flat number shows this tic
+    texture_translation: Vec<usize>,
+    flat_translation: Vec<usize>,
+}
+
+impl Animations {
+    // Find the runs the WAD has, from its ANIMATED lump if there is one or
+    // the original's table otherwise. Runs whose first frame is missing,
+    // like Doom II's in Doom, are left out.
+    pub fn load(wad: &WadData) -> Animations {
+        let texture_names: Vec<String> = wad
+            .read_textures()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        let flat_names = wad.read_flat_names();
+        let defs = wad.read_animated().unwrap_or_else(|| {
+            ANIM_DEFS
+                .iter()
+                .map(|&(texture, last, first)| {
+                    (texture, last.to_string(), first.to_string(), ANIM_SPEED)
+                })
+                .collect()
+        });
+        Animations::new(texture_names, flat_names, defs)
+    }
+
+    // Runs are given as (is a wall texture, last name, first name, tics
+    // per frame)
+    fn new(
+        texture_names: Vec<String>,
+        flat_names: Vec<String>,
+        defs: Vec<(bool, String, String, u32)>,
+    ) -> Animations {
+        let numbers = |names: &[String]| {
+            names
+                .iter()
+                .enumerate()
+                .map(|(number, name)| (name.clone(), number))
+                .collect::<HashMap<_, _>>()
+        };
+        let texture_numbers = numbers(&texture_names);
+        let flat_numbers = numbers(&flat_names);
+
+        let mut anims = Vec::new();
+        for (texture, last, first, speed) in defs {
+            let numbers = if texture {
+                &texture_numbers
+            } else {
+                &flat_numbers
+            };
+            let (Some(&base), Some(&end)) = (numbers.get(&first), numbers.get(&last)) else {
+                continue;
+            };
+            // A run that ends before it starts has nothing to cycle through
+            if end < base + 1 {
+                continue;
+            }
+            anims.push(Anim {
+                texture,
+                base,
+                count: end - base + 1,
+                speed: speed.max(1),
+            });
+        }
+
+        Animations {
+            anims,
+            texture_translation: (0..texture_names.len()).collect(),
+            flat_translation: (0..flat_names.len()).collect(),
+            texture_numbers,
+            flat_numbers,
+            texture_names,
+            flat_names,
+        }
+    }
+
+    // Move every run on to the frame it shows `time` tics into the level
+    pub fn update(&mut self, time: u32) {
+        for anim in &self.anims {
+            let translation = if anim.texture {
+                &mut self.texture_translation
+            } else {
+                &mut self.flat_translation
+            };
+            for i in 0..anim.count {
+                let frame = (time / anim.speed) as usize + i;
+                translation[anim.base + i] = anim.base + frame % anim.count;
+            }
+        }
+    }
+
+    // The wall texture to draw for `name` this tic. Nothing draws walls
+    // yet, so this is only read later on.
+    #[allow(dead_code)]
+    pub fn texture<'a>(&'a self, name: &'a str) -> &'a str {
+        self.texture_numbers.get(name).map_or(name, |&number| {
+            &self.texture_names[self.texture_translation[number]]
+        })
+    }
+
+    // The flat to draw for `name` this tic
+    pub fn flat<'a>(&'a self, name: &'a str) -> &'a str {
+        self.flat_numbers.get(name).map_or(name, |&number| {
+            &self.flat_names[self.flat_translation[number]]
+        })
+    }
+}
+
+impl Level {
+    // Run the animated walls and floors and the switches waiting to pop
+    // back, once a tic
+    pub fn update_specials(&mut self) {
+        self.animations.update(self.stats.time);
+        self.tick_buttons();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn runs_cycle_every_few_tics() {
+        let defs = ANIM_DEFS
+            .iter()
+            .map(|&(texture, last, first)| {
+                (texture, last.to_string(), first.to_string(), ANIM_SPEED)
+            })
+            .collect();
+        let mut animations = Animations::new(
+            names(&["STARTAN3", "BFALL1", "BFALL2", "BFALL3", "BFALL4"]),
+            names(&["FLOOR4_8", "NUKAGE1", "NUKAGE2", "NUKAGE3"]),
+            defs,
+        );
+
+        animations.update(0);
+        assert_eq!(animations.flat("NUKAGE1"), "NUKAGE1");
+        assert_eq!(animations.texture("BFALL1"), "BFALL1");
+
+        animations.update(ANIM_SPEED);
+        assert_eq!(animations.flat("NUKAGE1"), "NUKAGE2");
+        assert_eq!(animations.flat("NUKAGE3"), "NUKAGE1");
+        assert_eq!(animations.texture("BFALL1"), "BFALL2");
+
+        animations.update(ANIM_SPEED * 3);
+        assert_eq!(animations.flat("NUKAGE1"), "NUKAGE1");
+        assert_eq!(animations.texture("BFALL1"), "BFALL4");
+
+        // Anything not in a run, or not in the WAD at all, stays put
+        assert_eq!(animations.flat("FLOOR4_8"), "FLOOR4_8");
+        assert_eq!(animations.texture("STARTAN3"), "STARTAN3");
+        assert_eq!(animations.texture("SLADRIP1"), "SLADRIP1");
+    }
+}
@@ -0,0 +1,184 @@
+// Moving ceilings, like the original's p_ceilng.c: crushers and ceilings
+// that lower or raise once
+use crate::floors::{MoveResult, Plane};
+use crate::level::Level;
+use crate::specials::SectorThinker;
+
+const CEILING_SPEED: f32 = 1.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CeilingKind {
+    LowerToFloor,
+    RaiseToHighest,
+    // Down to 8 above the floor, slowing down on whatever's under it
+    LowerAndCrush,
+    // Down and up until stopped
+    CrushAndRaise,
+    FastCrushAndRaise,
+    SilentCrushAndRaise,
+}
+
+pub struct Ceiling {
+    pub kind: CeilingKind,
+    pub sector: usize,
+    pub bottom_height: f32,
+    pub top_height: f32,
+    pub speed: f32,
+    pub crush: bool,
+    // 1 up, -1 down, 0 stopped
+    pub direction: i32,
+    // Where a stopped crusher was going
+    pub old_direction: i32,
+    pub tag: i16,
+}
+
+impl Level {
+    // Start ceilings moving in every sector tagged `tag`
+    pub fn do_ceiling(&mut self, tag: i16, kind: CeilingKind) -> bool {
+        // Stopped crushers start again
+        if matches!(
+            kind,
+            CeilingKind::CrushAndRaise
+                | CeilingKind::FastCrushAndRaise
+                | CeilingKind::SilentCrushAndRaise
+        ) {
+            self.activate_ceilings_in_stasis(tag);
+        }
+
+        let mut started = false;
+        let mut search = None;
+        while let Some(sector) = self.find_sector_from_tag(tag, search) {
+            search = Some(sector);
+            if self.sector_thinkers[sector].is_some() {
+                continue;
+            }
+            started = true;
+
+            let (floor, ceiling) = {
+                let sector = &self.sectors[sector];
+                (sector.floor_height, sector.ceiling_height)
+            };
+            let mut moving = Ceiling {
+                kind,
+                sector,
+                bottom_height: floor,
+                top_height: ceiling,
+                speed: CEILING_SPEED,
+                crush: false,
+                direction: -1,
+                old_direction: -1,
+                tag,
+            };
+            match kind {
+                CeilingKind::FastCrushAndRaise => {
+                    moving.crush = true;
+                    moving.bottom_height = floor + 8.0;
+                    moving.speed = CEILING_SPEED * 2.0;
+                }
+                CeilingKind::CrushAndRaise | CeilingKind::SilentCrushAndRaise => {
+                    moving.crush = true;
+                    moving.bottom_height = floor + 8.0;
+                }
+                // The original doesn't actually set it crushing
+                CeilingKind::LowerAndCrush => moving.bottom_height = floor + 8.0,
+                CeilingKind::LowerToFloor => {}
+                CeilingKind::RaiseToHighest => {
+                    moving.top_height = self.highest_ceiling_surrounding(sector);
+                    moving.direction = 1;
+                }
+            }
+            self.add_thinker(sector, SectorThinker::Ceiling(moving));
+        }
+        started
+    }
+
+    fn activate_ceilings_in_stasis(&mut self, tag: i16) {
+        for thinker in self.thinkers.iter_mut().flatten() {
+            if let SectorThinker::Ceiling(ceiling) = thinker {
+                if ceiling.tag == tag && ceiling.direction == 0 {
+                    ceiling.direction = ceiling.old_direction;
+                }
+            }
+        }
+    }
+
+    // Stop the crushers tagged `tag` where they are
+    pub fn ceiling_crush_stop(&mut self, tag: i16) -> bool {
+        let mut stopped = false;
+        for thinker in self.thinkers.iter_mut().flatten() {
+            if let SectorThinker::Ceiling(ceiling) = thinker {
+                if ceiling.tag == tag && ceiling.direction != 0 {
+                    ceiling.old_direction = ceiling.direction;
+                    ceiling.direction = 0;
+                    stopped = true;
+                }
+            }
+        }
+        stopped
+    }
+
+    // Returns false once the ceiling has arrived for good
+    pub fn tick_ceiling(&mut self, ceiling: &mut Ceiling) -> bool {
+        let crushers = matches!(
+            ceiling.kind,
+            CeilingKind::CrushAndRaise
+                | CeilingKind::FastCrushAndRaise
+                | CeilingKind::SilentCrushAndRaise
+        );
+        match ceiling.direction {
+            1 => {
+                let result = self.move_plane(
+                    ceiling.sector,
+                    ceiling.speed,
+                    ceiling.top_height,
+                    false,
+                    Plane::Ceiling,
+                    1,
+                );
+                if result == MoveResult::PastDest {
+                    if ceiling.kind == CeilingKind::RaiseToHighest {
+                        return false;
+                    }
+                    if crushers {
+                        ceiling.direction = -1;
+                    }
+                }
+            }
+            -1 => {
+                let result = self.move_plane(
+                    ceiling.sector,
+                    ceiling.speed,
+                    ceiling.bottom_height,
+                    ceiling.crush,
+                    Plane::Ceiling,
+                    -1,
+                );
+                match result {
+                    MoveResult::PastDest => match ceiling.kind {
+                        CeilingKind::CrushAndRaise | CeilingKind::SilentCrushAndRaise => {
+                            ceiling.speed = CEILING_SPEED;
+                            ceiling.direction = 1;
+                        }
+                        CeilingKind::FastCrushAndRaise => ceiling.direction = 1,
+                        CeilingKind::LowerAndCrush | CeilingKind::LowerToFloor => return false,
+                        CeilingKind::RaiseToHighest => {}
+                    },
+                    // Slow right down while squashing something
+                    MoveResult::Crushed => {
+                        if matches!(
+                            ceiling.kind,
+                            CeilingKind::CrushAndRaise
+                                | CeilingKind::SilentCrushAndRaise
+                                | CeilingKind::LowerAndCrush
+                        ) {
+                            ceiling.speed = CEILING_SPEED / 8.0;
+                        }
+                    }
+                    MoveResult::Ok => {}
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+}
@@ -0,0 +1,297 @@
+// Cheat codes, like the original's m_cheat.c: letters typed during a level
+// are watched for the id... sequences
+use crate::pickup::Power;
+use minifb::Key;
+
+// Enough for the longest code, idspispopd
+const MAX_TYPED: usize = 10;
+
+// The codes that complete straight away
+const CODES: [(&str, Cheat); 6] = [
+    ("iddqd", Cheat::God),
+    ("idkfa", Cheat::KeysFullAmmo),
+    ("idclip", Cheat::NoClip),
+    ("idspispopd", Cheat::NoClip),
+    ("iddt", Cheat::MapReveal),
+    ("idmypos", Cheat::MyPos),
+];
+// idbehold is followed by a letter for the powerup it toggles, idclev by
+// two digits
+const BEHOLD: &str = "idbehold";
+const BEHOLD_POWERS: [(char, Power); 6] = [
+    ('v', Power::Invulnerability),
+    ('s', Power::Strength),
+    ('i', Power::Invisibility),
+    ('r', Power::IronFeet),
+    ('a', Power::AllMap),
+    ('l', Power::Infrared),
+];
+const CLEV: &str = "idclev";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cheat {
+    God,
+    KeysFullAmmo,
+    NoClip,
+    // Cycles the automap through all lines, then things as well
+    MapReveal,
+    MyPos,
+    // idbehold on its own lists the powerups it can give
+    BeholdMenu,
+    Behold(Power),
+    // The two digits typed: episode and map, or the map number in Doom II
+    ChangeLevel(u8, u8),
+}
+
+pub struct CheatDetector {
+    typed: String,
+}
+
+impl CheatDetector {
+    pub fn new() -> CheatDetector {
+        CheatDetector {
+            typed: String::new(),
+        }
+    }
+
+    // Watch one key press. Returns the cheat it finishes, if any.
+    pub fn responder(&mut self, key: Key) -> Option<Cheat> {
+        self.typed.push(key_char(key)?);
+        if self.typed.len() > MAX_TYPED {
+            self.typed.remove(0);
+        }
+
+        let cheat = self.check();
+        // idbehold waits for its letter
+        if cheat.is_some() && cheat != Some(Cheat::BeholdMenu) {
+            self.typed.clear();
+        }
+        cheat
+    }
+
+    fn check(&self) -> Option<Cheat> {
+        let typed = self.typed.as_str();
+        if let Some(&(_, cheat)) = CODES.iter().find(|(code, _)| typed.ends_with(code)) {
+            return Some(cheat);
+        }
+        if typed.ends_with(BEHOLD) {
+            return Some(Cheat::BeholdMenu);
+        }
+
+        let (before, last) = typed.split_at(typed.len().saturating_sub(1));
+        if before.ends_with(BEHOLD) {
+            let &(_, power) = BEHOLD_POWERS.iter().find(|&&(c, _)| last.starts_with(c))?;
+            return Some(Cheat::Behold(power));
+        }
+        let (before, digits) = typed.split_at(typed.len().saturating_sub(2));
+        let digits = digits.as_bytes();
+        if before.ends_with(CLEV) && digits.iter().all(u8::is_ascii_digit) {
+            return Some(Cheat::ChangeLevel(digits[0] - b'0', digits[1] - b'0'));
+        }
+        None
+    }
+
+    // Part way through typing a code, so the keys aren't for anything else
+    pub fn typing(&self) -> bool {
+        let typed = self.typed.as_str();
+        // idclev with one digit so far
+        if typed[..typed.len().saturating_sub(1)].ends_with(CLEV) {
+            return true;
+        }
+        let codes = CODES.iter().map(|&(code, _)| code).chain([BEHOLD, CLEV]);
+        // A lone i could be anything, the hotkeys still get it
+        (2..=typed.len()).any(|len| {
+            let tail = &typed[typed.len() - len..];
+            codes.clone().any(|code| code.starts_with(tail))
+        })
+    }
+}
+
+// The letter or digit a key types
+pub fn key_char(key: Key) -> Option<char> {
+    let c = match key {
+        Key::A => 'a',
+        Key::B => 'b',
+        Key::C => 'c',
+        Key::D => 'd',
+        Key::E => 'e',
+        Key::F => 'f',
+        Key::G => 'g',
+        Key::H => 'h',
+        Key::I => 'i',
+        Key::J => 'j',
+        Key::K => 'k',
+        Key::L => 'l',
+        Key::M => 'm',
+        Key::N => 'n',
+        Key::O => 'o',
+        Key::P => 'p',
+        Key::Q => 'q',
+        Key::R => 'r',
+        Key::S => 's',
+        Key::T => 't',
+        Key::U => 'u',
+        Key::V => 'v',
+        Key::W => 'w',
+        Key::X => 'x',
+        Key::Y => 'y',
+        Key::Z => 'z',
+        Key::Key0 | Key::NumPad0 => '0',
+        Key::Key1 | Key::NumPad1 => '1',
+        Key::Key2 | Key::NumPad2 => '2',
+        Key::Key3 | Key::NumPad3 => '3',
+        Key::Key4 | Key::NumPad4 => '4',
+        Key::Key5 | Key::NumPad5 => '5',
+        Key::Key6 | Key::NumPad6 => '6',
+        Key::Key7 | Key::NumPad7 => '7',
+        Key::Key8 | Key::NumPad8 => '8',
+        Key::Key9 | Key::NumPad9 => '9',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> Key {
+        let keys = [
+            Key::A,
+            Key::B,
+            Key::C,
+            Key::D,
+            Key::E,
+            Key::F,
+            Key::G,
+            Key::H,
+            Key::I,
+            Key::J,
+            Key::K,
+            Key::L,
+            Key::M,
+            Key::N,
+            Key::O,
+            Key::P,
+            Key::Q,
+            Key::R,
+            Key::S,
+            Key::T,
+            Key::U,
+            Key::V,
+            Key::W,
+            Key::X,
+            Key::Y,
+            Key::Z,
+        ];
+        match c {
+            'a'..='z' => keys[(c as u8 - b'a') as usize],
+            '0'..='9' => [
+                Key::Key0,
+                Key::Key1,
+                Key::Key2,
+                Key::Key3,
+                Key::Key4,
+                Key::Key5,
+                Key::Key6,
+                Key::Key7,
+                Key::Key8,
+                Key::Key9,
+            ][(c as u8 - b'0') as usize],
+            _ => panic!("no key for {}", c),
+        }
+    }
+
+    // Type the text, returning the last cheat finished and whether the last
+    // key went into a code
+    fn type_text(cheats: &mut CheatDetector, text: &str) -> (Option<Cheat>, bool) {
+        let mut last = None;
+        let mut typing = false;
+        for c in text.chars() {
+            let cheat = cheats.responder(key(c));
+            typing = cheat.is_some() || cheats.typing();
+            last = cheat.or(last);
+        }
+        (last, typing)
+    }
+
+    #[test]
+    fn every_code_finishes_its_cheat() {
+        for (code, cheat) in CODES {
+            let mut cheats = CheatDetector::new();
+            assert_eq!(
+                type_text(&mut cheats, code),
+                (Some(cheat), true),
+                "{}",
+                code
+            );
+            // And starts over after
+            assert_eq!(cheats.responder(Key::D), None);
+        }
+        // Mistyping along the way doesn't matter
+        let mut cheats = CheatDetector::new();
+        assert_eq!(type_text(&mut cheats, "idkiddqd").0, Some(Cheat::God));
+    }
+
+    #[test]
+    fn behold_waits_for_its_letter() {
+        let mut cheats = CheatDetector::new();
+        assert_eq!(
+            type_text(&mut cheats, "idbehold"),
+            (Some(Cheat::BeholdMenu), true)
+        );
+        assert_eq!(
+            type_text(&mut cheats, "s"),
+            (Some(Cheat::Behold(Power::Strength)), true)
+        );
+
+        for (letter, power) in BEHOLD_POWERS {
+            let mut cheats = CheatDetector::new();
+            let typed = format!("idbehold{}", letter);
+            assert_eq!(type_text(&mut cheats, &typed).0, Some(Cheat::Behold(power)));
+        }
+
+        // Any other letter is just a letter
+        let mut cheats = CheatDetector::new();
+        type_text(&mut cheats, "idbehold");
+        assert_eq!(cheats.responder(Key::X), None);
+        assert!(!cheats.typing());
+    }
+
+    #[test]
+    fn clev_takes_two_digits() {
+        let mut cheats = CheatDetector::new();
+        assert_eq!(type_text(&mut cheats, "idclev"), (None, true));
+        // The first digit still belongs to the code
+        assert_eq!(type_text(&mut cheats, "1"), (None, true));
+        assert_eq!(
+            type_text(&mut cheats, "3"),
+            (Some(Cheat::ChangeLevel(1, 3)), true)
+        );
+
+        let mut cheats = CheatDetector::new();
+        assert_eq!(type_text(&mut cheats, "idclev2x").0, None);
+    }
+
+    #[test]
+    fn plain_keys_are_left_for_the_hotkeys() {
+        for c in ['i', 'm', 'q'] {
+            let mut cheats = CheatDetector::new();
+            assert_eq!(
+                type_text(&mut cheats, &c.to_string()),
+                (None, false),
+                "{}",
+                c
+            );
+        }
+        // Not even after a code is finished
+        let mut cheats = CheatDetector::new();
+        type_text(&mut cheats, "iddqd");
+        assert_eq!(type_text(&mut cheats, "m"), (None, false));
+
+        // Once a code is under way its letters are taken, the rest aren't
+        let mut cheats = CheatDetector::new();
+        assert_eq!(type_text(&mut cheats, "idm"), (None, true));
+        assert_eq!(type_text(&mut cheats, "q"), (None, false));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::info::{MF_MISSILE, MF_SHOOTABLE, MF_SOLID};
+use crate::level::Level;
+use crate::mobj::Target;
+use crate::wad_reader::LineDef;
+
+pub const PLAYER_RADIUS: f32 = 16.0;
+pub const PLAYER_HEIGHT: f32 = 56.0;
+// The highest step that can be walked up without jumping
+pub const MAX_STEP_HEIGHT: f32 = 24.0;
+// Linedef flag that blocks players and monsters even on two-sided lines
+pub const ML_BLOCKING: i16 = 1;
+// Moves longer than this are split up so nothing skips through thin walls
+pub const MAX_STEP: f32 = 15.0;
+
+// Axis aligned box: left, right, bottom, top
+type BoundingBox = (f32, f32, f32, f32);
+
+fn bounding_box(x: f32, y: f32, radius: f32) -> BoundingBox {
+    (x - radius, x + radius, y - radius, y + radius)
+}
+
+// Which side of the line a point is on: false for the front (right) side
+pub fn point_on_line_side(x: f32, y: f32, start: (f32, f32), end: (f32, f32)) -> bool {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    (x - start.0) * dy - (y - start.1) * dx < 0.0
+}
+
+// True if the box has corners on both sides of the line
+fn box_crosses_line(bbox: BoundingBox, start: (f32, f32), end: (f32, f32)) -> bool {
+    let (left, right, bottom, top) = bbox;
+    let line_box = (
+        start.0.min(end.0),
+        start.0.max(end.0),
+        start.1.min(end.1),
+        start.1.max(end.1),
+    );
+    if right <= line_box.0 || left >= line_box.1 || top <= line_box.2 || bottom >= line_box.3 {
+        return false;
+    }
+
+    let corners = [(left, bottom), (left, top), (right, bottom), (right, top)];
+    let first = point_on_line_side(corners[0].0, corners[0].1, start, end);
+    corners[1..]
+        .iter()
+        .any(|&(x, y)| point_on_line_side(x, y, start, end) != first)
+}
+
+fn blocks_movement(line: &LineDef) -> bool {
+    line.back_sidedef == -1 || line.flags & ML_BLOCKING != 0
+}
+
+pub enum Blocked {
+    Line(usize),
+    Thing(Target),
+    // The sector itself is too low or too high to move into
+    Sector,
+}
+
+// The floor and ceiling heights something standing at a spot is held between,
+// and the lines that set them
+#[derive(Clone, Copy)]
+pub struct Opening {
+    pub floor_z: f32,
+    pub ceiling_z: f32,
+    // The lowest floor touched, for monsters that won't walk off ledges
+    pub dropoff_z: f32,
+    floor_line: Option<usize>,
+    ceiling_line: Option<usize>,
+}
+
+// Can something of `radius` stand at (x, y)? Finds the floor and ceiling it
+// would be between, including those of two-sided lines it overlaps. `who` is
+// doing the checking, and can't block itself.
+pub fn check_position(
+    level: &Level,
+    x: f32,
+    y: f32,
+    radius: f32,
+    who: Target,
+) -> Result<Opening, Blocked> {
+    // Missiles fly over and under things, and away from whoever fired them
+    let missile = match who {
+        Target::Mobj(index) => level.mobjs[index]
+            .as_ref()
+            .filter(|mobj| mobj.flags & MF_MISSILE != 0),
+        Target::Player => None,
+    };
+    for (target, thing) in level.bodies() {
+        let reach = thing.radius + radius;
+        if target == who || (thing.x - x).abs() >= reach || (thing.y - y).abs() >= reach {
+            continue;
+        }
+        match missile {
+            Some(missile) => {
+                if thing.flags & (MF_SOLID | MF_SHOOTABLE) == 0 || missile.target == Some(target) {
+                    continue;
+                }
+                if missile.z > thing.z + thing.height || missile.z + missile.height < thing.z {
+                    continue;
+                }
+            }
+            None if thing.flags & MF_SOLID == 0 => continue,
+            None => {}
+        }
+        return Err(Blocked::Thing(target));
+    }
+
+    line_opening(level, x, y, radius)
+}
+
+// The floor and ceiling at (x, y) for something of `radius`, from the sector
+// there and the two-sided lines it overlaps, without regard for things
+pub fn line_opening(level: &Level, x: f32, y: f32, radius: f32) -> Result<Opening, Blocked> {
+    let mut opening = match level.sector_at(x, y) {
+        Some(sector) => Opening {
+            floor_z: sector.floor_height,
+            ceiling_z: sector.ceiling_height,
+            dropoff_z: sector.floor_height,
+            floor_line: None,
+            ceiling_line: None,
+        },
+        None => return Err(Blocked::Sector),
+    };
+
+    for index in lines_touched(level, x, y, radius) {
+        let line = &level.linedefs[index];
+        if blocks_movement(line) {
+            return Err(Blocked::Line(index));
+        }
+
+        // Two-sided lines narrow the gap to the lower ceiling and the higher
+        // floor of their two sectors
+        if let (Some(front), Some(back)) = level.line_sectors(line) {
+            let top = front.ceiling_height.min(back.ceiling_height);
+            let bottom = front.floor_height.max(back.floor_height);
+            if top < opening.ceiling_z {
+                opening.ceiling_z = top;
+                opening.ceiling_line = Some(index);
+            }
+            if bottom > opening.floor_z {
+                opening.floor_z = bottom;
+                opening.floor_line = Some(index);
+            }
+            let low = front.floor_height.min(back.floor_height);
+            opening.dropoff_z = opening.dropoff_z.min(low);
+        }
+    }
+    Ok(opening)
+}
+
+// The lines a box of `radius` around (x, y) overlaps, found through the
+// blockmap
+pub fn lines_touched(level: &Level, x: f32, y: f32, radius: f32) -> Vec<usize> {
+    let bbox = bounding_box(x, y, radius);
+    let mut touched = Vec::new();
+    let (first_column, first_row) = level.blockmap.block_at(bbox.0, bbox.2);
+    let (last_column, last_row) = level.blockmap.block_at(bbox.1, bbox.3);
+    for row in first_row..=last_row {
+        for column in first_column..=last_column {
+            for &index in level.blockmap.lines(column, row) {
+                let Some(line) = level.linedefs.get(index) else {
+                    continue;
+                };
+                let Some((start, end)) = level.line_points(line) else {
+                    continue;
+                };
+                // Long lines run through several blocks
+                if box_crosses_line(bbox, start, end) && !touched.contains(&index) {
+                    touched.push(index);
+                }
+            }
+        }
+    }
+    touched
+}
+
+// Could something at height z move to (x, y)? It has to fit under the
+// ceiling and can only step up so far.
+pub fn try_move(
+    level: &Level,
+    x: f32,
+    y: f32,
+    z: f32,
+    radius: f32,
+    height: f32,
+    who: Target,
+) -> Result<Opening, Blocked> {
+    let opening = check_position(level, x, y, radius, who)?;
+    let blocked_by = |line: Option<usize>| line.map_or(Blocked::Sector, Blocked::Line);
+
+    if opening.ceiling_z - opening.floor_z < height {
+        return Err(blocked_by(opening.ceiling_line.or(opening.floor_line)));
+    }
+    if opening.ceiling_z - z < height {
+        return Err(blocked_by(opening.ceiling_line));
+    }
+    if opening.floor_z - z > MAX_STEP_HEIGHT {
+        return Err(blocked_by(opening.floor_line));
+    }
+    Ok(opening)
+}
+
+// Something that moves through the map: where it stands and how big it is
+pub struct Mover {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub who: Target,
+}
+
+// Move by (mom_x, mom_y), sliding along whatever gets in the way. Returns
+// the new position, the momentum left after any collision, and the floor and
+// ceiling at the new position.
+pub fn xy_move(
+    level: &Level,
+    mover: &Mover,
+    mom: (f32, f32),
+) -> ((f32, f32), (f32, f32), Option<Opening>) {
+    let (mut x, mut y) = (mover.x, mover.y);
+    let (mut mom_x, mut mom_y) = mom;
+    let fits =
+        |x: f32, y: f32| try_move(level, x, y, mover.z, mover.radius, mover.height, mover.who);
+
+    let steps = (mom_x.abs().max(mom_y.abs()) / MAX_STEP).ceil().max(1.0);
+    let (step_x, step_y) = (mom_x / steps, mom_y / steps);
+
+    for _ in 0..steps as usize {
+        match fits(x + step_x, y + step_y) {
+            Ok(_) => {
+                x += step_x;
+                y += step_y;
+            }
+            Err(blocked) => {
+                let ((new_x, new_y), (slide_x, slide_y)) =
+                    slide_move(&fits, (x, y), (mom_x, mom_y), blocked, level);
+                x = new_x;
+                y = new_y;
+                mom_x = slide_x;
+                mom_y = slide_y;
+                break;
+            }
+        }
+    }
+    (
+        (x, y),
+        (mom_x, mom_y),
+        check_position(level, x, y, mover.radius, mover.who).ok(),
+    )
+}
+
+// Run along a blocking wall instead of stopping dead against it
+fn slide_move(
+    fits: &dyn Fn(f32, f32) -> Result<Opening, Blocked>,
+    pos: (f32, f32),
+    mom: (f32, f32),
+    blocked: Blocked,
+    level: &Level,
+) -> ((f32, f32), (f32, f32)) {
+    let (x, y) = pos;
+    let fits = |dx: f32, dy: f32| fits(x + dx, y + dy).is_ok();
+
+    // Keep only the part of the momentum that runs along the wall
+    if let Blocked::Line(index) = blocked {
+        let points = level
+            .linedefs
+            .get(index)
+            .and_then(|line| level.line_points(line));
+        if let Some((start, end)) = points {
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let (ux, uy) = (dx / length, dy / length);
+                let along = mom.0 * ux + mom.1 * uy;
+                let slide = (ux * along, uy * along);
+                if fits(slide.0, slide.1) {
+                    return ((x + slide.0, y + slide.1), slide);
+                }
+            }
+        }
+    }
+
+    // Stairstep: try moving along one axis at a time
+    if fits(0.0, mom.1) {
+        ((x, y + mom.1), (0.0, mom.1))
+    } else if fits(mom.0, 0.0) {
+        ((x + mom.0, y), (mom.0, 0.0))
+    } else {
+        ((x, y), (0.0, 0.0))
+    }
+}
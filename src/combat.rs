@@ -0,0 +1,509 @@
+// Fighting, like the original's attack code in p_map.c and p_inter.c:
+// bullets traced along a line to whatever they hit, explosions, and the
+// damage that hurts, pains and kills things
+use crate::enemy::{point_to_angle, MELEE_RANGE};
+use crate::info::{
+    info, MobjType, WeaponType, MF_CORPSE, MF_COUNTKILL, MF_DROPOFF, MF_DROPPED, MF_FLOAT,
+    MF_JUSTHIT, MF_NOBLOOD, MF_NOCLIP, MF_NOGRAVITY, MF_SHOOTABLE, MF_SKULLFLY, MF_SOLID, S_NULL,
+};
+use crate::level::{Level, BABY};
+use crate::mobj::{heading, Body, Target};
+
+// How far bullets reach
+pub const MISSILE_RANGE: f32 = 32.0 * 64.0;
+// Tics a monster keeps after whoever last hurt it before it'll switch
+const BASE_THRESHOLD: i32 = 100;
+
+// Harm done to the player while things moved, applied by the game once the
+// tic is over
+pub struct PlayerHit {
+    pub damage: i32,
+    pub momentum: (f32, f32, f32),
+    pub source: Option<Target>,
+}
+
+// Worn armor soaks up part of every hit until its points run out
+#[derive(Clone, Copy, Default)]
+pub struct Armor {
+    // 0 for none, 1 for green, 2 for blue
+    pub kind: i32,
+    pub points: i32,
+}
+
+impl Armor {
+    // Take the armor's share of `damage`, returning how much it saved
+    pub fn absorb(&mut self, damage: i32) -> i32 {
+        let mut saved = match self.kind {
+            1 => damage / 3,
+            2 => damage / 2,
+            _ => return 0,
+        };
+        if self.points <= saved {
+            // The armor is used up
+            saved = self.points;
+            self.kind = 0;
+        }
+        self.points -= saved;
+        saved
+    }
+}
+
+// What a traced line runs into
+enum Intercept {
+    Line(usize),
+    Thing(Target),
+}
+
+fn cross(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+// How far along `from + delta` the segment from `start` to `end` is
+// crossed, as a fraction of delta
+pub fn line_fraction(
+    from: (f32, f32),
+    delta: (f32, f32),
+    start: (f32, f32),
+    end: (f32, f32),
+) -> Option<f32> {
+    let along = (end.0 - start.0, end.1 - start.1);
+    let denominator = cross(delta, along);
+    if denominator == 0.0 {
+        return None;
+    }
+    let offset = (start.0 - from.0, start.1 - from.1);
+    let fraction = cross(offset, along) / denominator;
+    let on_line = cross(offset, delta) / denominator;
+    ((0.0..=1.0).contains(&fraction) && (0.0..=1.0).contains(&on_line)).then_some(fraction)
+}
+
+// How far along `from + delta` a thing's box is entered
+fn box_fraction(from: (f32, f32), delta: (f32, f32), body: &Body) -> Option<f32> {
+    let (mut enter, mut leave) = (0.0f32, 1.0f32);
+    for (start, step, centre) in [(from.0, delta.0, body.x), (from.1, delta.1, body.y)] {
+        let (low, high) = (centre - body.radius, centre + body.radius);
+        if step == 0.0 {
+            if start < low || start > high {
+                return None;
+            }
+            continue;
+        }
+        let (a, b) = ((low - start) / step, (high - start) / step);
+        enter = enter.max(a.min(b));
+        leave = leave.min(a.max(b));
+    }
+    (enter <= leave).then_some(enter)
+}
+
+impl Level {
+    // A random angle off aim, like the original's
+    // (P_Random() - P_Random()) << shift
+    pub fn angle_spread(&mut self, shift: u32) -> f32 {
+        let delta = self.rng.next() - self.rng.next();
+        delta as f32 * 360.0 / (1u64 << (32 - shift)) as f32
+    }
+
+    // Everything a line from the shooter at `angle` crosses within `range`,
+    // nearest first
+    fn trace(
+        &self,
+        shooter: Target,
+        from: (f32, f32),
+        angle: f32,
+        range: f32,
+    ) -> Vec<(f32, Intercept)> {
+        let (dx, dy) = heading(angle);
+        let delta = (dx * range, dy * range);
+        let mut hits: Vec<(f32, Intercept)> = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let (start, end) = self.line_points(line)?;
+                Some((
+                    line_fraction(from, delta, start, end)?,
+                    Intercept::Line(index),
+                ))
+            })
+            .collect();
+        hits.extend(
+            self.bodies()
+                .filter(|&(target, _)| target != shooter)
+                .filter_map(|(target, body)| {
+                    Some((box_fraction(from, delta, &body)?, Intercept::Thing(target)))
+                }),
+        );
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits
+    }
+
+    // Look for something shootable along `angle`, up or down within the
+    // view. Returns the slope to aim at, and what it'll hit.
+    pub fn aim_line_attack(
+        &self,
+        shooter: Target,
+        angle: f32,
+        range: f32,
+    ) -> (f32, Option<Target>) {
+        let Some(body) = self.body(shooter) else {
+            return (0.0, None);
+        };
+        let shoot_z = body.z + body.height / 2.0 + 8.0;
+        let (mut top_slope, mut bottom_slope): (f32, f32) = (100.0 / 160.0, -100.0 / 160.0);
+
+        for (fraction, hit) in self.trace(shooter, (body.x, body.y), angle, range) {
+            let distance = (fraction * range).max(1.0);
+            match hit {
+                Intercept::Line(index) => {
+                    // Openings narrow the window, walls end the search
+                    let line = &self.linedefs[index];
+                    let (Some(front), Some(back)) = self.line_sectors(line) else {
+                        return (0.0, None);
+                    };
+                    let open_top = front.ceiling_height.min(back.ceiling_height);
+                    let open_bottom = front.floor_height.max(back.floor_height);
+                    if open_bottom >= open_top {
+                        return (0.0, None);
+                    }
+                    if front.floor_height != back.floor_height {
+                        bottom_slope = bottom_slope.max((open_bottom - shoot_z) / distance);
+                    }
+                    if front.ceiling_height != back.ceiling_height {
+                        top_slope = top_slope.min((open_top - shoot_z) / distance);
+                    }
+                    if top_slope <= bottom_slope {
+                        return (0.0, None);
+                    }
+                }
+                Intercept::Thing(target) => {
+                    let Some(thing) = self.body(target) else {
+                        continue;
+                    };
+                    if thing.flags & MF_SHOOTABLE == 0 {
+                        continue;
+                    }
+                    let thing_top = (thing.z + thing.height - shoot_z) / distance;
+                    let thing_bottom = (thing.z - shoot_z) / distance;
+                    if thing_top < bottom_slope || thing_bottom > top_slope {
+                        continue;
+                    }
+                    let slope = (thing_top.min(top_slope) + thing_bottom.max(bottom_slope)) / 2.0;
+                    return (slope, Some(target));
+                }
+            }
+        }
+        (0.0, None)
+    }
+
+    // Fire a bullet along `angle` and `slope`, hurting the first thing in its
+    // way. Walls and things without blood get a puff of smoke.
+    pub fn line_attack(
+        &mut self,
+        shooter: Target,
+        angle: f32,
+        range: f32,
+        slope: f32,
+        damage: i32,
+    ) {
+        let Some(body) = self.body(shooter) else {
+            return;
+        };
+        let shoot_z = body.z + body.height / 2.0 + 8.0;
+        let (dx, dy) = heading(angle);
+        let at = |fraction: f32| {
+            let distance = fraction * range;
+            (
+                body.x + dx * distance,
+                body.y + dy * distance,
+                shoot_z + slope * distance,
+            )
+        };
+
+        for (fraction, hit) in self.trace(shooter, (body.x, body.y), angle, range) {
+            let distance = (fraction * range).max(1.0);
+            match hit {
+                Intercept::Line(index) => {
+                    if self.linedefs[index].linedef_type != 0 {
+                        self.shoot_special_line(index, shooter);
+                    }
+                    let line = &self.linedefs[index];
+                    if let (Some(front), Some(back)) = self.line_sectors(line) {
+                        let open_top = front.ceiling_height.min(back.ceiling_height);
+                        let open_bottom = front.floor_height.max(back.floor_height);
+                        let under = front.floor_height == back.floor_height
+                            || (open_bottom - shoot_z) / distance <= slope;
+                        let over = front.ceiling_height == back.ceiling_height
+                            || (open_top - shoot_z) / distance >= slope;
+                        if under && over {
+                            continue;
+                        }
+                    }
+                    // Puff a little short of the wall
+                    let (x, y, z) = at(fraction - 4.0 / range);
+                    self.spawn_puff(x, y, z, range);
+                    return;
+                }
+                Intercept::Thing(target) => {
+                    let Some(thing) = self.body(target) else {
+                        continue;
+                    };
+                    if thing.flags & MF_SHOOTABLE == 0 {
+                        continue;
+                    }
+                    let thing_top = (thing.z + thing.height - shoot_z) / distance;
+                    let thing_bottom = (thing.z - shoot_z) / distance;
+                    if thing_top < slope || thing_bottom > slope {
+                        continue;
+                    }
+                    let (x, y, z) = at(fraction - 10.0 / range);
+                    if thing.flags & MF_NOBLOOD != 0 {
+                        self.spawn_puff(x, y, z, range);
+                    } else {
+                        self.spawn_blood(x, y, z, damage);
+                    }
+                    if damage > 0 {
+                        self.damage_mobj(target, Some(body), Some(shooter), damage);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    // Smoke where a bullet hit, without the spark for a punch
+    pub fn spawn_puff(&mut self, x: f32, y: f32, z: f32, range: f32) {
+        let z = z + (self.rng.next() - self.rng.next()) as f32 / 64.0;
+        let index = self.spawn_mobj(MobjType::Puff, x, y, 0.0);
+        if let Some(puff) = self.mobjs[index].as_mut() {
+            puff.z = z;
+            puff.mom_z = 1.0;
+        }
+        self.shorten_tics(index);
+        if range == MELEE_RANGE {
+            self.set_mobj_state(index, info().mobj(MobjType::Puff).spawn_state + 2);
+        }
+    }
+
+    // A splash of blood, smaller for lighter hits
+    fn spawn_blood(&mut self, x: f32, y: f32, z: f32, damage: i32) {
+        let z = z + (self.rng.next() - self.rng.next()) as f32 / 64.0;
+        let index = self.spawn_mobj(MobjType::Blood, x, y, 0.0);
+        if let Some(blood) = self.mobjs[index].as_mut() {
+            blood.z = z;
+            blood.mom_z = 2.0;
+        }
+        self.shorten_tics(index);
+        let spawn_state = info().mobj(MobjType::Blood).spawn_state;
+        if (9..=12).contains(&damage) {
+            self.set_mobj_state(index, spawn_state + 1);
+        } else if damage < 9 {
+            self.set_mobj_state(index, spawn_state + 2);
+        }
+    }
+
+    // Add to a thing's momentum. The player's is changed by the game.
+    pub fn push(&mut self, target: Target, momentum: (f32, f32, f32)) {
+        match target {
+            Target::Player => self.player_hits.push(PlayerHit {
+                damage: 0,
+                momentum,
+                source: None,
+            }),
+            Target::Mobj(index) => {
+                if let Some(mobj) = self.mobjs[index].as_mut() {
+                    mobj.mom_x += momentum.0;
+                    mobj.mom_y += momentum.1;
+                    mobj.mom_z += momentum.2;
+                }
+            }
+        }
+    }
+
+    // Hurt a target. `inflictor` is what hit it, which knocks it back, and
+    // `source` who's responsible, who it'll turn on.
+    pub fn damage_mobj(
+        &mut self,
+        target: Target,
+        inflictor: Option<Body>,
+        source: Option<Target>,
+        damage: i32,
+    ) {
+        let Some(body) = self.body(target) else {
+            return;
+        };
+        if body.flags & MF_SHOOTABLE == 0 || body.health <= 0 {
+            return;
+        }
+        // I'm too young to die halves what the player takes
+        let damage = if target == Target::Player && self.skill == BABY {
+            damage >> 1
+        } else {
+            damage
+        };
+        if let Target::Mobj(index) = target {
+            if let Some(mobj) = self.mobjs[index].as_mut() {
+                if mobj.flags & MF_SKULLFLY != 0 {
+                    mobj.mom_x = 0.0;
+                    mobj.mom_y = 0.0;
+                    mobj.mom_z = 0.0;
+                }
+            }
+        }
+
+        // Knock it away from whatever hit it, except with the chainsaw, which
+        // holds its victim in place
+        let chainsaw = source == Some(Target::Player) && self.player_weapon == WeaponType::Chainsaw;
+        if let Some(inflictor) = inflictor.filter(|_| body.flags & MF_NOCLIP == 0 && !chainsaw) {
+            let mass = self
+                .kind_of(target)
+                .map_or(100, |kind| info().mobj(kind).mass);
+            let mut angle = point_to_angle((inflictor.x, inflictor.y), (body.x, body.y));
+            let mut thrust = damage as f32 * 12.5 / mass.max(1) as f32;
+            // Sometimes a killing blow from below knocks it forwards instead
+            if damage < 40
+                && damage > body.health
+                && body.z - inflictor.z > 64.0
+                && self.rng.next() & 1 != 0
+            {
+                angle += 180.0;
+                thrust *= 4.0;
+            }
+            let (dx, dy) = heading(angle);
+            self.push(target, (dx * thrust, dy * thrust, 0.0));
+        }
+
+        let index = match target {
+            Target::Player => {
+                // The end of E1M8 can't kill
+                let end_of_e1m8 = self
+                    .sector_at(body.x, body.y)
+                    .is_some_and(|sector| sector.special == 11);
+                let damage = if end_of_e1m8 && damage >= body.health {
+                    body.health - 1
+                } else {
+                    damage
+                };
+                // Invulnerability and god mode shrug off all but telefrags
+                if (self.player_invulnerable || self.player_god) && damage < 1000 {
+                    return;
+                }
+                let saved = self.player_armor.absorb(damage);
+                if let Some(player) = self.player_body.as_mut() {
+                    player.health = (player.health - (damage - saved)).max(0);
+                    if player.health == 0 {
+                        player.flags &= !(MF_SOLID | MF_SHOOTABLE);
+                    }
+                }
+                self.player_hits.push(PlayerHit {
+                    damage,
+                    momentum: (0.0, 0.0, 0.0),
+                    source,
+                });
+                return;
+            }
+            Target::Mobj(index) => index,
+        };
+
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.health -= damage;
+        if mobj.health <= 0 {
+            self.kill_mobj(index);
+            return;
+        }
+
+        let kind = mobj.kind;
+        let info = info().mobj(kind);
+        if self.rng.next() < info.pain_chance && mobj.flags & MF_SKULLFLY == 0 {
+            // Fight back at once
+            mobj.flags |= MF_JUSTHIT;
+            self.set_mobj_state(index, info.pain_state);
+        }
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.reaction_time = 0;
+
+        // Turn on whoever did it, unless already set on someone else. Nobody
+        // fights an arch-vile's flames, and it won't fight back.
+        let Some(source) = source.filter(|&source| source != target) else {
+            return;
+        };
+        let attacker_kind = self.kind_of(source);
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        if (mobj.threshold == 0 || kind == MobjType::Vile) && attacker_kind != Some(MobjType::Vile)
+        {
+            mobj.target = Some(source);
+            mobj.threshold = BASE_THRESHOLD;
+            if mobj.state == info.spawn_state && info.see_state != S_NULL {
+                self.set_mobj_state(index, info.see_state);
+            }
+        }
+    }
+
+    // Knock a thing down dead, or blown to bits if it took enough damage.
+    // Some monsters drop their gun.
+    fn kill_mobj(&mut self, index: usize) {
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.flags &= !(MF_SHOOTABLE | MF_FLOAT | MF_SKULLFLY);
+        if mobj.kind != MobjType::Skull {
+            mobj.flags &= !MF_NOGRAVITY;
+        }
+        mobj.flags |= MF_CORPSE | MF_DROPOFF;
+        mobj.height /= 4.0;
+        // Monsters killing each other count for the player too
+        if mobj.flags & MF_COUNTKILL != 0 {
+            self.stats.kills += 1;
+        }
+
+        let (kind, x, y) = (mobj.kind, mobj.x, mobj.y);
+        let info = info().mobj(kind);
+        let gibbed = mobj.health < -info.spawn_health && info.xdeath_state != S_NULL;
+        let state = if gibbed {
+            info.xdeath_state
+        } else {
+            info.death_state
+        };
+        if self.set_mobj_state(index, state) {
+            self.shorten_tics(index);
+        }
+
+        let drop = match kind {
+            MobjType::Possessed | MobjType::WolfSS => MobjType::Clip,
+            MobjType::ShotGuy => MobjType::Shotgun,
+            MobjType::ChainGuy => MobjType::Chaingun,
+            _ => return,
+        };
+        let item = self.spawn_mobj(drop, x, y, 0.0);
+        if let Some(item) = self.mobjs[item].as_mut() {
+            item.flags |= MF_DROPPED;
+        }
+    }
+
+    // Blast everything within `damage` units that the explosion can see,
+    // harder the closer it is
+    pub fn radius_attack(&mut self, spot: Body, source: Option<Target>, damage: i32) {
+        let victims: Vec<(Target, i32)> = self
+            .bodies()
+            .filter(|&(target, body)| {
+                body.flags & MF_SHOOTABLE != 0
+                    // The biggest monsters shrug off blasts
+                    && !matches!(self.kind_of(target), Some(MobjType::Cyborg | MobjType::Spider))
+            })
+            .filter_map(|(target, body)| {
+                let distance = (body.x - spot.x).abs().max((body.y - spot.y).abs());
+                let distance = (distance - body.radius).max(0.0) as i32;
+                (distance < damage && self.check_sight(&body, &spot))
+                    .then_some((target, damage - distance))
+            })
+            .collect();
+        for (target, amount) in victims {
+            self.damage_mobj(target, Some(spot), source, amount);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// The settings file, like the original's default.cfg: one setting per line,
+// a name and a number, read at startup and written back on the way out
+use crate::game::Options;
+use crate::menu::{MOUSE_SENSITIVITY_MAX, SCREEN_SIZE_MAX, SCREEN_SIZE_MIN};
+use std::fs;
+use std::io;
+
+pub const CONFIG_PATH: &str = "default.cfg";
+
+impl Options {
+    // The settings saved last time, with the defaults for anything missing
+    // or unreadable
+    pub fn load(path: &str) -> Options {
+        let mut options = Options::new();
+        let Ok(text) = fs::read_to_string(path) else {
+            return options;
+        };
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(value)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let Ok(value) = value.parse::<usize>() else {
+                println!("Bad value for {} in {}", name, path);
+                continue;
+            };
+            match name {
+                "mouse_sensitivity" => options.mouse_sensitivity = value.min(MOUSE_SENSITIVITY_MAX),
+                "screenblocks" => {
+                    options.screen_size = value.clamp(SCREEN_SIZE_MIN, SCREEN_SIZE_MAX)
+                }
+                "show_messages" => options.show_messages = value != 0,
+                "interpolate" => options.interpolate = value != 0,
+                _ => {}
+            }
+        }
+        options
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let text = format!(
+            "mouse_sensitivity\t\t{}\nscreenblocks\t\t{}\nshow_messages\t\t{}\ninterpolate\t\t{}\n",
+            self.mouse_sensitivity,
+            self.screen_size,
+            self.show_messages as usize,
+            self.interpolate as usize,
+        );
+        fs::write(path, text)
+    }
+}
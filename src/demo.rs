@@ -0,0 +1,227 @@
+use crate::ticcmd::TicCmd;
+use std::io;
+
+// Marks the end of the tic commands in a demo lump
+const DEMO_MARKER: u8 = 0x80;
+const MAX_PLAYERS: usize = 4;
+// Recorded demos are written as Doom 1.9's
+const DEMO_VERSION: u8 = 109;
+
+// A recorded game: the level it starts on and every tic of input for the
+// player who recorded it
+pub struct Demo {
+    pub skill: usize,
+    pub episode: usize,
+    pub map: usize,
+    // The -deathmatch, -respawn, -fast and -nomonsters options it was
+    // recorded with
+    pub deathmatch: bool,
+    pub respawn: bool,
+    pub fast: bool,
+    pub no_monsters: bool,
+    // Which of the four players recorded it
+    pub player: usize,
+    pub cmds: Vec<TicCmd>,
+}
+
+impl Demo {
+    pub fn from_bytes(data: &[u8]) -> io::Result<Demo> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let first = *data.first().ok_or_else(|| invalid("empty demo"))?;
+
+        // Versions before 1.4 start straight with the skill level and have
+        // no options in the header
+        let (skill, episode, map, players_at) = if first <= 4 {
+            (data[0], data.get(1), data.get(2), 3)
+        } else {
+            (
+                *data
+                    .get(1)
+                    .ok_or_else(|| invalid("demo header too short"))?,
+                data.get(2),
+                data.get(3),
+                9,
+            )
+        };
+        let (episode, map) = match (episode, map) {
+            (Some(&episode), Some(&map)) => (episode as usize, map as usize),
+            _ => return Err(invalid("demo header too short")),
+        };
+
+        // Newer headers go on with deathmatch, respawn, fast, nomonsters and
+        // the recording player
+        let option = |at: usize| players_at == 9 && data.get(at).is_some_and(|&on| on != 0);
+        let (deathmatch, respawn, fast, no_monsters) = (option(4), option(5), option(6), option(7));
+        let player = match data.get(8) {
+            Some(&player) if players_at == 9 && (player as usize) < MAX_PLAYERS => player as usize,
+            _ => 0,
+        };
+
+        let in_game = data
+            .get(players_at..players_at + MAX_PLAYERS)
+            .ok_or_else(|| invalid("demo header too short"))?;
+        let players = in_game.iter().filter(|&&on| on != 0).count().max(1);
+        if in_game[player] == 0 && in_game.iter().any(|&on| on != 0) {
+            return Err(invalid("recording player not in the demo"));
+        }
+        // The players in the game take turns in each tic, in order
+        let slot = 4 * in_game[..player].iter().filter(|&&on| on != 0).count();
+
+        // Each tic holds 4 bytes per player in the game, we only replay the
+        // recording player's
+        let mut cmds = Vec::new();
+        let mut pos = players_at + MAX_PLAYERS;
+        while let Some(&byte) = data.get(pos) {
+            if byte == DEMO_MARKER {
+                break;
+            }
+            let tic = data
+                .get(pos + slot..pos + slot + 4)
+                .ok_or_else(|| invalid("demo ends mid-tic"))?;
+            cmds.push(TicCmd {
+                forward_move: tic[0] as i8,
+                side_move: tic[1] as i8,
+                angle_turn: (tic[2] as i16) << 8,
+                buttons: tic[3],
+            });
+            pos += 4 * players;
+        }
+
+        Ok(Demo {
+            skill: skill as usize,
+            episode,
+            map,
+            deathmatch,
+            respawn,
+            fast,
+            no_monsters,
+            player,
+            cmds,
+        })
+    }
+
+    // Add a tic to a demo being recorded. Turns only keep their top byte,
+    // so the game plays what comes back: exactly what a replay will see.
+    pub fn record(&mut self, cmd: TicCmd) -> TicCmd {
+        let turn = ((cmd.angle_turn as i32 + 128) >> 8) as u8;
+        let cmd = TicCmd {
+            angle_turn: (turn as i16) << 8,
+            ..cmd
+        };
+        self.cmds.push(cmd);
+        cmd
+    }
+
+    // The demo as a version 1.9 lump with just the recording player in it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![
+            DEMO_VERSION,
+            self.skill as u8,
+            self.episode as u8,
+            self.map as u8,
+            self.deathmatch as u8,
+            self.respawn as u8,
+            self.fast as u8,
+            self.no_monsters as u8,
+            self.player as u8,
+        ];
+        let mut in_game = [0; MAX_PLAYERS];
+        in_game[self.player] = 1;
+        data.extend_from_slice(&in_game);
+        for cmd in &self.cmds {
+            data.extend_from_slice(&[
+                cmd.forward_move as u8,
+                cmd.side_move as u8,
+                (cmd.angle_turn >> 8) as u8,
+                cmd.buttons,
+            ]);
+        }
+        data.push(DEMO_MARKER);
+        data
+    }
+
+    // The map name this demo was recorded on
+    pub fn map_name(&self, commercial: bool) -> String {
+        if commercial {
+            format!("MAP{:02}", self.map)
+        } else {
+            format!("E{}M{}", self.episode, self.map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(angle_turn: i16) -> TicCmd {
+        TicCmd {
+            angle_turn,
+            ..TicCmd::default()
+        }
+    }
+
+    #[test]
+    fn recorded_demo_reads_back_the_same() {
+        let mut demo = Demo {
+            skill: 3,
+            episode: 1,
+            map: 5,
+            deathmatch: false,
+            respawn: true,
+            fast: true,
+            no_monsters: false,
+            player: 2,
+            cmds: Vec::new(),
+        };
+        let played = [
+            demo.record(TicCmd {
+                forward_move: 50,
+                side_move: -40,
+                angle_turn: 0,
+                buttons: 1,
+            }),
+            demo.record(turn(-300)),
+            demo.record(turn(-129)),
+            demo.record(turn(-1)),
+            demo.record(turn(383)),
+        ];
+
+        // Turns round to the nearest top byte, negative ones too
+        let turns: Vec<i16> = played.iter().map(|cmd| cmd.angle_turn).collect();
+        assert_eq!(turns, [0, -256, -256, 0, 256]);
+        assert_eq!(played[0].forward_move, 50);
+        assert_eq!(played[0].side_move, -40);
+
+        let replay = Demo::from_bytes(&demo.to_bytes()).unwrap();
+        assert_eq!(replay.cmds, played);
+        assert_eq!(
+            (replay.skill, replay.episode, replay.map, replay.player),
+            (3, 1, 5, 2)
+        );
+        assert!(replay.respawn && replay.fast);
+        assert!(!replay.deathmatch && !replay.no_monsters);
+    }
+
+    #[test]
+    fn replays_the_recording_players_slot() {
+        // Players 1 and 3 in a game recorded by player 3
+        let mut data = vec![DEMO_VERSION, 2, 1, 1, 1, 0, 0, 0, 2, 1, 0, 1, 0];
+        data.extend_from_slice(&[10, 0, 0, 0, 20, 0, 0, 1]);
+        data.extend_from_slice(&[11, 0, 0, 0, 21, 0, 0, 2]);
+        data.push(DEMO_MARKER);
+
+        let demo = Demo::from_bytes(&data).unwrap();
+        assert_eq!(demo.player, 2);
+        let moves: Vec<(i8, u8)> = demo
+            .cmds
+            .iter()
+            .map(|cmd| (cmd.forward_move, cmd.buttons))
+            .collect();
+        assert_eq!(moves, [(20, 1), (21, 2)]);
+
+        // A recording player who isn't in the game has nothing to replay
+        data[8] = 1;
+        assert!(Demo::from_bytes(&data).is_err());
+    }
+}
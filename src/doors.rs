@@ -0,0 +1,275 @@
+// Doors, like the original's p_doors.c: ceilings that rise to let the player
+// through, wait, and come back down
+use crate::floors::{MoveResult, Plane};
+use crate::level::Level;
+use crate::mobj::Target;
+use crate::pickup::Card;
+use crate::specials::SectorThinker;
+use crate::strings::{PD_BLUEK, PD_BLUEO, PD_REDK, PD_REDO, PD_YELLOWK, PD_YELLOWO};
+
+const DOOR_SPEED: f32 = 2.0;
+// Tics an opened door waits before closing again
+const DOOR_WAIT: i32 = 150;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DoorKind {
+    // Opens, waits and closes
+    Normal,
+    // Closes, waits 30 seconds and opens
+    Close30ThenOpen,
+    Close,
+    Open,
+    BlazeRaise,
+    BlazeOpen,
+    BlazeClose,
+    // Waits 5 minutes, then opens and closes like a normal door
+    RaiseIn5Mins,
+}
+
+pub struct Door {
+    pub kind: DoorKind,
+    pub sector: usize,
+    pub top_height: f32,
+    pub speed: f32,
+    // 1 up, 0 waiting at the top, -1 down, 2 waiting to start
+    pub direction: i32,
+    pub top_wait: i32,
+    pub top_countdown: i32,
+}
+
+// The key a locked door or switch needs, either colour of keycard or skull
+// key, and what to say without it
+fn needed_key(special: i16) -> Option<(Card, Card, &'static str)> {
+    match special {
+        26 | 32 => Some((Card::Blue, Card::BlueSkull, PD_BLUEK)),
+        27 | 34 => Some((Card::Yellow, Card::YellowSkull, PD_YELLOWK)),
+        28 | 33 => Some((Card::Red, Card::RedSkull, PD_REDK)),
+        99 | 133 => Some((Card::Blue, Card::BlueSkull, PD_BLUEO)),
+        134 | 135 => Some((Card::Red, Card::RedSkull, PD_REDO)),
+        136 | 137 => Some((Card::Yellow, Card::YellowSkull, PD_YELLOWO)),
+        _ => None,
+    }
+}
+
+impl Level {
+    // Does the player have the key for a line? Says so if not.
+    fn has_key(&mut self, special: i16) -> bool {
+        let Some((card, skull, message)) = needed_key(special) else {
+            return true;
+        };
+        if self.player_cards[card as usize] || self.player_cards[skull as usize] {
+            return true;
+        }
+        self.player_message = Some(message);
+        false
+    }
+
+    // Set the doors in every sector tagged `tag` moving
+    pub fn do_door(&mut self, tag: i16, kind: DoorKind) -> bool {
+        let mut started = false;
+        let mut search = None;
+        while let Some(sector) = self.find_sector_from_tag(tag, search) {
+            search = Some(sector);
+            if self.sector_thinkers[sector].is_some() {
+                continue;
+            }
+            started = true;
+
+            let top_height = self.lowest_ceiling_surrounding(sector) - 4.0;
+            let mut door = Door {
+                kind,
+                sector,
+                top_height,
+                speed: DOOR_SPEED,
+                direction: 1,
+                top_wait: DOOR_WAIT,
+                top_countdown: 0,
+            };
+            match kind {
+                DoorKind::BlazeClose => {
+                    door.direction = -1;
+                    door.speed = DOOR_SPEED * 4.0;
+                }
+                DoorKind::Close => door.direction = -1,
+                DoorKind::Close30ThenOpen => {
+                    door.top_height = self.sectors[sector].ceiling_height;
+                    door.direction = -1;
+                }
+                DoorKind::BlazeRaise | DoorKind::BlazeOpen => door.speed = DOOR_SPEED * 4.0,
+                DoorKind::Normal | DoorKind::Open | DoorKind::RaiseIn5Mins => {}
+            }
+            self.add_thinker(sector, SectorThinker::Door(door));
+        }
+        started
+    }
+
+    // A door switch that needs a key
+    pub fn do_locked_door(&mut self, index: usize, kind: DoorKind, who: Target) -> bool {
+        if who != Target::Player {
+            return false;
+        }
+        let line = &self.linedefs[index];
+        let (special, tag) = (line.linedef_type, line.tag);
+        if !self.has_key(special) {
+            return false;
+        }
+        self.do_door(tag, kind)
+    }
+
+    // Use a door directly: the sector behind the line opens. Using a door
+    // that's already moving sends it back the other way.
+    pub fn vertical_door(&mut self, index: usize, who: Target) {
+        let line = &self.linedefs[index];
+        let (special, back) = (line.linedef_type, line.back_sidedef);
+        if needed_key(special).is_some() && (who != Target::Player || !self.has_key(special)) {
+            return;
+        }
+        let Some(sector) = self.side_sector_index(back) else {
+            return;
+        };
+
+        if let Some(thinker) = self.sector_thinkers[sector] {
+            if matches!(special, 1 | 26..=28 | 117) {
+                if let Some(SectorThinker::Door(door)) = self.thinkers[thinker].as_mut() {
+                    if door.direction == -1 {
+                        door.direction = 1;
+                    } else if who == Target::Player {
+                        // Monsters never close doors
+                        door.direction = -1;
+                    }
+                }
+                return;
+            }
+        }
+
+        let mut door = Door {
+            kind: DoorKind::Normal,
+            sector,
+            top_height: self.lowest_ceiling_surrounding(sector) - 4.0,
+            speed: DOOR_SPEED,
+            direction: 1,
+            top_wait: DOOR_WAIT,
+            top_countdown: 0,
+        };
+        match special {
+            31..=34 => {
+                door.kind = DoorKind::Open;
+                self.linedefs[index].linedef_type = 0;
+            }
+            117 => {
+                door.kind = DoorKind::BlazeRaise;
+                door.speed = DOOR_SPEED * 4.0;
+            }
+            118 => {
+                door.kind = DoorKind::BlazeOpen;
+                door.speed = DOOR_SPEED * 4.0;
+                self.linedefs[index].linedef_type = 0;
+            }
+            _ => {}
+        }
+        self.add_thinker(sector, SectorThinker::Door(door));
+    }
+
+    // Sector special 10: a door that shuts 30 seconds into the level
+    pub fn spawn_door_close_in_30(&mut self, sector: usize) {
+        self.sectors[sector].special = 0;
+        let door = Door {
+            kind: DoorKind::Normal,
+            sector,
+            top_height: self.sectors[sector].ceiling_height,
+            speed: DOOR_SPEED,
+            direction: 0,
+            top_wait: DOOR_WAIT,
+            top_countdown: 30 * 35,
+        };
+        self.add_thinker(sector, SectorThinker::Door(door));
+    }
+
+    // Sector special 14: a door that opens 5 minutes into the level
+    pub fn spawn_door_raise_in_5_mins(&mut self, sector: usize) {
+        self.sectors[sector].special = 0;
+        let door = Door {
+            kind: DoorKind::RaiseIn5Mins,
+            sector,
+            top_height: self.lowest_ceiling_surrounding(sector) - 4.0,
+            speed: DOOR_SPEED,
+            direction: 2,
+            top_wait: DOOR_WAIT,
+            top_countdown: 5 * 60 * 35,
+        };
+        self.add_thinker(sector, SectorThinker::Door(door));
+    }
+
+    // Returns false once the door is done for good
+    pub fn tick_door(&mut self, door: &mut Door) -> bool {
+        match door.direction {
+            0 => {
+                door.top_countdown -= 1;
+                if door.top_countdown == 0 {
+                    match door.kind {
+                        DoorKind::BlazeRaise | DoorKind::Normal => door.direction = -1,
+                        DoorKind::Close30ThenOpen => door.direction = 1,
+                        _ => {}
+                    }
+                }
+            }
+            -1 => {
+                let floor = self.sectors[door.sector].floor_height;
+                let result =
+                    self.move_plane(door.sector, door.speed, floor, false, Plane::Ceiling, -1);
+                match result {
+                    MoveResult::PastDest => match door.kind {
+                        DoorKind::Close30ThenOpen => {
+                            door.direction = 0;
+                            door.top_countdown = 35 * 30;
+                        }
+                        DoorKind::BlazeRaise
+                        | DoorKind::BlazeClose
+                        | DoorKind::Normal
+                        | DoorKind::Close => return false,
+                        _ => {}
+                    },
+                    // Something's in the way: go back up, unless it's
+                    // meant to stay shut
+                    MoveResult::Crushed => {
+                        if !matches!(door.kind, DoorKind::BlazeClose | DoorKind::Close) {
+                            door.direction = 1;
+                        }
+                    }
+                    MoveResult::Ok => {}
+                }
+            }
+            1 => {
+                let result = self.move_plane(
+                    door.sector,
+                    door.speed,
+                    door.top_height,
+                    false,
+                    Plane::Ceiling,
+                    1,
+                );
+                if result == MoveResult::PastDest {
+                    match door.kind {
+                        DoorKind::BlazeRaise | DoorKind::Normal => {
+                            door.direction = 0;
+                            door.top_countdown = door.top_wait;
+                        }
+                        DoorKind::Close30ThenOpen | DoorKind::BlazeOpen | DoorKind::Open => {
+                            return false
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            2 => {
+                door.top_countdown -= 1;
+                if door.top_countdown == 0 && door.kind == DoorKind::RaiseIn5Mins {
+                    door.direction = 1;
+                    door.kind = DoorKind::Normal;
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+}
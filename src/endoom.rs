@@ -0,0 +1,75 @@
+use crate::wad_reader::WadData;
+
+// ENDOOM is an 80x25 text mode screen: a character and a colour byte per cell
+const COLUMNS: usize = 80;
+const ROWS: usize = 25;
+
+// Code page 437 glyphs for the control characters and the upper half
+const CP437_LOW: [char; 32] = [
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', '►', '◄', '↕',
+    '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', ' ',
+];
+
+// CGA colour numbers in ANSI order (CGA has blue and red swapped, and so on)
+const CGA_TO_ANSI: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];
+
+fn cp437_to_char(byte: u8) -> char {
+    match byte {
+        0x00..=0x1F => CP437_LOW[byte as usize],
+        0x7F => '⌂',
+        0x80..=0xFF => CP437_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+// Turn the ENDOOM lump into lines of text with ANSI colour escapes
+pub fn render_endoom(data: &[u8]) -> Vec<String> {
+    data.chunks_exact(COLUMNS * 2)
+        .take(ROWS)
+        .map(|row| {
+            let mut line = String::new();
+            for cell in row.chunks_exact(2) {
+                let (glyph, attr) = (cell[0], cell[1]);
+                let foreground = CGA_TO_ANSI[(attr & 0x07) as usize];
+                let bright = attr & 0x08 != 0;
+                let background = CGA_TO_ANSI[((attr >> 4) & 0x07) as usize];
+                let blink = attr & 0x80 != 0;
+
+                line.push_str(&format!(
+                    "\x1b[0;{}{};{}m",
+                    if blink { "5;" } else { "" },
+                    if bright {
+                        90 + foreground
+                    } else {
+                        30 + foreground
+                    },
+                    40 + background
+                ));
+                line.push(cp437_to_char(glyph));
+            }
+            line.push_str("\x1b[0m");
+            line
+        })
+        .collect()
+}
+
+// Print the exit screen to the terminal, like DOS Doom did on quitting
+pub fn print_endoom(wad: &WadData) {
+    match wad.read_lump("ENDOOM") {
+        Ok(data) => {
+            for line in render_endoom(&data) {
+                println!("{}", line);
+            }
+        }
+        Err(e) => println!("No ENDOOM screen to show: {}", e),
+    }
+}
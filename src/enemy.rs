@@ -0,0 +1,1209 @@
+// Monster thinking, like the original's p_enemy.c: waiting for the player
+// to show up, chasing them around the map and deciding when to attack
+use crate::collision::{check_position, try_move, MAX_STEP_HEIGHT};
+use crate::combat::MISSILE_RANGE;
+use crate::doors::DoorKind;
+use crate::floors::FloorKind;
+use crate::info::{
+    info, Action, MobjType, MF_AMBUSH, MF_CORPSE, MF_DROPOFF, MF_FLOAT, MF_INFLOAT,
+    MF_JUSTATTACKED, MF_JUSTHIT, MF_SHADOW, MF_SHOOTABLE, MF_SKULLFLY, MF_SOLID, S_NULL,
+};
+use crate::level::{parse_map_name, Level, EASY};
+use crate::mobj::{heading, Body, Target};
+
+// Monsters bite and claw within this range
+pub const MELEE_RANGE: f32 = 64.0;
+// How far a floating monster rises or sinks in a tic
+pub const FLOAT_SPEED: f32 = 4.0;
+// Linedef flag: the second of these a sound crosses stops it
+const ML_SOUNDBLOCK: i16 = 0x40;
+// How fast a lost soul charges
+const SKULL_SPEED: f32 = 20.0;
+// The most lost souls a level can hold before pain elementals stop spitting
+const MAX_SKULLS: usize = 20;
+// The angle between a mancubus's fireballs
+const FAT_SPREAD: f32 = 90.0 / 8.0;
+// How sharply a revenant's missile turns to follow its target
+const TRACE_ANGLE: f32 = 16.875;
+// What comes out of the Icon of Sin's spawn cubes, by random roll below
+// each number
+const SPAWN_FLY_TYPES: [(i32, MobjType); 10] = [
+    (50, MobjType::Troop),
+    (90, MobjType::Sergeant),
+    (120, MobjType::Shadows),
+    (130, MobjType::Pain),
+    (160, MobjType::Head),
+    (162, MobjType::Vile),
+    (172, MobjType::Undead),
+    (192, MobjType::Baby),
+    (222, MobjType::Fatso),
+    (246, MobjType::Knight),
+];
+
+// Walking directions, anticlockwise from east like the original's
+const EAST: usize = 0;
+const NORTH_EAST: usize = 1;
+const NORTH: usize = 2;
+const NORTH_WEST: usize = 3;
+const WEST: usize = 4;
+const SOUTH_WEST: usize = 5;
+const SOUTH: usize = 6;
+const SOUTH_EAST: usize = 7;
+pub const NO_DIR: usize = 8;
+
+const OPPOSITE: [usize; 9] = [
+    WEST, SOUTH_WEST, SOUTH, SOUTH_EAST, EAST, NORTH_EAST, NORTH, NORTH_WEST, NO_DIR,
+];
+// Indexed by (target is south) * 2 + (target is east)
+const DIAGONALS: [usize; 4] = [NORTH_WEST, NORTH_EAST, SOUTH_WEST, SOUTH_EAST];
+
+const DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+const DIR_X: [f32; 8] = [
+    1.0, DIAGONAL, 0.0, -DIAGONAL, -1.0, -DIAGONAL, 0.0, DIAGONAL,
+];
+const DIR_Y: [f32; 8] = [
+    0.0, DIAGONAL, 1.0, DIAGONAL, 0.0, -DIAGONAL, -1.0, -DIAGONAL,
+];
+
+// Doom's cheap distance estimate, which monsters' decisions are tuned to
+pub fn approx_distance(dx: f32, dy: f32) -> f32 {
+    let (dx, dy) = (dx.abs(), dy.abs());
+    dx + dy - dx.min(dy) / 2.0
+}
+
+// The angle from one point to another, in Player::angle degrees
+pub fn point_to_angle(from: (f32, f32), to: (f32, f32)) -> f32 {
+    (to.0 - from.0)
+        .atan2(to.1 - from.1)
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+fn dir_angle(dir: usize) -> f32 {
+    (90.0 - dir as f32 * 45.0).rem_euclid(360.0)
+}
+
+// Turn in 45 degree steps towards a walking direction
+fn turn_towards(angle: f32, goal: f32) -> f32 {
+    let snapped = (angle / 45.0).round() * 45.0;
+    let delta = (goal - snapped + 540.0).rem_euclid(360.0) - 180.0;
+    let turn = if delta > 0.0 {
+        45.0
+    } else if delta < 0.0 {
+        -45.0
+    } else {
+        0.0
+    };
+    (snapped + turn).rem_euclid(360.0)
+}
+
+impl Level {
+    // Run the code a state starts with
+    pub fn run_action(&mut self, index: usize, action: Action) {
+        match action {
+            Action::Look => self.look(index),
+            // Footstep sounds come later, until then these just chase
+            Action::Chase | Action::Metal | Action::Hoof | Action::BabyMetal => self.chase(index),
+            Action::VileChase => self.vile_chase(index),
+            Action::FaceTarget | Action::FatRaise | Action::SkelWhoosh | Action::VileStart => {
+                self.face_target(index)
+            }
+            Action::CPosRefire => self.refire(index, 40),
+            Action::SpidRefire => self.refire(index, 10),
+            Action::PosAttack | Action::CPosAttack => self.bullet_attack(index, 1),
+            Action::SPosAttack => self.bullet_attack(index, 3),
+            Action::TroopAttack => self.melee_or_missile(index, 8, 3, Some(MobjType::TroopShot)),
+            Action::SargAttack => self.melee_or_missile(index, 10, 4, None),
+            Action::HeadAttack => self.melee_or_missile(index, 6, 10, Some(MobjType::HeadShot)),
+            Action::BruisAttack => self.melee_or_missile(index, 8, 10, Some(MobjType::BruiserShot)),
+            Action::SkelFist => self.melee_or_missile(index, 10, 6, None),
+            Action::CyberAttack => self.missile_attack(index, MobjType::Rocket),
+            Action::BspiAttack => self.missile_attack(index, MobjType::ArachPlaz),
+            Action::SkelMissile => self.skel_missile(index),
+            Action::Tracer => self.tracer(index),
+            Action::FatAttack1 => self.fat_attack(index, -FAT_SPREAD, [0.0, -FAT_SPREAD]),
+            Action::FatAttack2 => self.fat_attack(index, FAT_SPREAD, [0.0, FAT_SPREAD * 2.0]),
+            Action::FatAttack3 => {
+                self.fat_attack(index, 0.0, [-FAT_SPREAD / 2.0, FAT_SPREAD / 2.0])
+            }
+            Action::SkullAttack => self.skull_attack(index),
+            Action::PainAttack => {
+                self.face_target(index);
+                if let Some(angle) = self.mobjs[index].as_ref().map(|mobj| mobj.angle) {
+                    self.pain_shoot_skull(index, angle);
+                }
+            }
+            // A dying pain elemental lets out three more lost souls
+            Action::PainDie => {
+                self.fall(index);
+                if let Some(angle) = self.mobjs[index].as_ref().map(|mobj| mobj.angle) {
+                    for turn in [90.0, 180.0, 270.0] {
+                        self.pain_shoot_skull(index, (angle + turn).rem_euclid(360.0));
+                    }
+                }
+            }
+            Action::VileTarget => self.vile_target(index),
+            Action::VileAttack => self.vile_attack(index),
+            Action::StartFire | Action::Fire | Action::FireCrackle => self.fire(index),
+            Action::Explode => {
+                if let Some(mobj) = &self.mobjs[index] {
+                    let (body, source) = (mobj.body(), mobj.target);
+                    self.radius_attack(body, source, 128);
+                }
+            }
+            Action::BFGSpray => self.bfg_spray(index),
+            Action::Fall => self.fall(index),
+            Action::KeenDie => self.keen_die(index),
+            Action::BossDeath => self.boss_death(index),
+            Action::BrainAwake => self.brain_target_on = 0,
+            Action::BrainSpit => self.brain_spit(index),
+            Action::SpawnFly => self.spawn_fly(index),
+            Action::BrainScream => self.brain_scream(index),
+            Action::BrainExplode => {
+                if let Some(mobj) = &self.mobjs[index] {
+                    let (x, y) = (mobj.x, mobj.y);
+                    let spread = (self.rng.next() - self.rng.next()) as f32 / 32.0;
+                    self.brain_explosion(x + spread, y);
+                }
+            }
+            Action::BrainDie => self.exit_requested = true,
+            // These only make sounds, which come later
+            Action::Pain
+            | Action::Scream
+            | Action::XScream
+            | Action::PlayerScream
+            | Action::BrainPain
+            | Action::None => {}
+            // Weapon actions are run by the player's psprites instead
+            Action::WeaponReady
+            | Action::Lower
+            | Action::Raise
+            | Action::Punch
+            | Action::ReFire
+            | Action::FirePistol
+            | Action::Light0
+            | Action::Light1
+            | Action::Light2
+            | Action::FireShotgun
+            | Action::FireShotgun2
+            | Action::CheckReload
+            | Action::OpenShotgun2
+            | Action::LoadShotgun2
+            | Action::CloseShotgun2
+            | Action::FireCGun
+            | Action::GunFlash
+            | Action::FireMissile
+            | Action::Saw
+            | Action::FirePlasma
+            | Action::BFGsound
+            | Action::FireBFG => {}
+        }
+    }
+
+    // Corpses don't get in the way
+    fn fall(&mut self, index: usize) {
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.flags &= !MF_SOLID;
+        }
+    }
+
+    // The last of a type of boss dying opens up the way on, or ends the
+    // level, on the maps it's the boss of
+    fn boss_death(&mut self, index: usize) {
+        let Some(kind) = self.mobjs[index].as_ref().map(|mobj| mobj.kind) else {
+            return;
+        };
+        let Some((episode, mission)) = parse_map_name(&self.map) else {
+            return;
+        };
+        let boss = match (episode, mission) {
+            (0, 7) => matches!(kind, MobjType::Fatso | MobjType::Baby),
+            (0, _) => false,
+            (1, 8) => kind == MobjType::Bruiser,
+            (2, 8) | (4, 6) => kind == MobjType::Cyborg,
+            (3, 8) | (4, 8) => kind == MobjType::Spider,
+            (4, _) => false,
+            (_, mission) => mission == 8,
+        };
+        // Only a living player wins
+        let player_alive = self.player_body.is_some_and(|body| body.health > 0);
+        if !boss || !player_alive {
+            return;
+        }
+        if self.others_alive(index, kind) {
+            return;
+        }
+
+        match (episode, mission, kind) {
+            (0, 7, MobjType::Fatso) | (1, _, _) | (4, 8, _) => {
+                self.do_floor(666, FloorKind::LowerFloorToLowest, None);
+            }
+            (0, 7, MobjType::Baby) => {
+                self.do_floor(667, FloorKind::RaiseToTexture, None);
+            }
+            (4, 6, _) => {
+                self.do_door(666, DoorKind::BlazeOpen);
+            }
+            _ => self.exit_requested = true,
+        }
+    }
+
+    // Commander Keens open a door once they're all dead
+    fn keen_die(&mut self, index: usize) {
+        self.fall(index);
+        if !self.others_alive(index, MobjType::Keen) {
+            self.do_door(666, DoorKind::Open);
+        }
+    }
+
+    // The Icon of Sin's shooter fires a spawn cube at each of the map's
+    // targets in turn, only every other time on the easy skills. The cube
+    // is timed to burst when it reaches the target.
+    fn brain_spit(&mut self, index: usize) {
+        self.brain_easy = !self.brain_easy;
+        if self.skill <= EASY && !self.brain_easy {
+            return;
+        }
+        let targets: Vec<usize> = self
+            .mobjs
+            .iter()
+            .enumerate()
+            .filter(|(_, mobj)| {
+                mobj.as_ref()
+                    .is_some_and(|m| m.kind == MobjType::BossTarget)
+            })
+            .map(|(target, _)| target)
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let target = targets[self.brain_target_on % targets.len()];
+        self.brain_target_on = (self.brain_target_on + 1) % targets.len();
+
+        let (Some(spitter), Some(dest)) = (&self.mobjs[index], &self.mobjs[target]) else {
+            return;
+        };
+        let distance = dest.y - spitter.y;
+        let Some(cube) = self.spawn_missile(index, Target::Mobj(target), MobjType::SpawnShot)
+        else {
+            return;
+        };
+        if let Some(cube) = self.mobjs[cube].as_mut() {
+            cube.target = Some(Target::Mobj(target));
+            let tics = info().states[cube.state].tics.max(1);
+            cube.reaction_time = (distance / cube.mom_y / tics as f32) as i32;
+        }
+    }
+
+    // A spawn cube that's reached its target bursts into flame and a random
+    // monster, which crushes anything in the way
+    fn spawn_fly(&mut self, index: usize) {
+        let Some(cube) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        cube.reaction_time -= 1;
+        if cube.reaction_time != 0 {
+            return;
+        }
+        let Some(dest) = cube.target.and_then(|target| self.body(target)) else {
+            self.mobjs[index] = None;
+            return;
+        };
+
+        let fire = self.spawn_mobj(MobjType::SpawnFire, dest.x, dest.y, 0.0);
+        if let Some(fire) = self.mobjs[fire].as_mut() {
+            fire.z = dest.z;
+        }
+        let roll = self.rng.next();
+        let kind = SPAWN_FLY_TYPES
+            .iter()
+            .find(|&&(below, _)| roll < below)
+            .map_or(MobjType::Bruiser, |&(_, kind)| kind);
+        let monster = self.spawn_mobj(kind, dest.x, dest.y, 0.0);
+        if let Some(mobj) = self.mobjs[monster].as_mut() {
+            mobj.z = dest.z;
+        }
+        if self.look_for_players(monster, true) {
+            self.set_mobj_state(monster, info().mobj(kind).see_state);
+        }
+        self.telefrag(Target::Mobj(monster), dest.x, dest.y);
+        self.mobjs[index] = None;
+    }
+
+    // The Icon of Sin dies in a wall of explosions
+    fn brain_scream(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (x, y) = (mobj.x, mobj.y);
+        let mut explosion_x = x - 196.0;
+        while explosion_x < x + 320.0 {
+            self.brain_explosion(explosion_x, y - 320.0);
+            explosion_x += 8.0;
+        }
+    }
+
+    // One explosion of the Icon of Sin's death, rising from somewhere up
+    // its wall
+    fn brain_explosion(&mut self, x: f32, y: f32) {
+        let z = 128.0 + self.rng.next() as f32 * 2.0;
+        let explosion = self.spawn_mobj(MobjType::Rocket, x, y, 0.0);
+        let rise = self.rng.next() as f32 / 128.0;
+        self.set_mobj_state(explosion, info().mobj(MobjType::BossBrain).explode_state);
+        let cut = self.rng.next() & 7;
+        if let Some(mobj) = self.mobjs[explosion].as_mut() {
+            mobj.z = z;
+            mobj.mom_z = rise;
+            mobj.tics = (mobj.tics - cut).max(1);
+        }
+    }
+
+    // Is anything else of this type still alive?
+    fn others_alive(&self, index: usize, kind: MobjType) -> bool {
+        self.mobjs.iter().enumerate().any(|(other, mobj)| {
+            other != index
+                && mobj
+                    .as_ref()
+                    .is_some_and(|m| m.kind == kind && m.health > 0)
+        })
+    }
+
+    fn target_of(&self, index: usize) -> Option<Target> {
+        self.mobjs[index].as_ref()?.target
+    }
+
+    // Zombies fire bullets at the target, spread a little off aim
+    fn bullet_attack(&mut self, index: usize, pellets: usize) {
+        if self.target_of(index).is_none() {
+            return;
+        }
+        self.face_target(index);
+        let Some(angle) = self.mobjs[index].as_ref().map(|mobj| mobj.angle) else {
+            return;
+        };
+        let shooter = Target::Mobj(index);
+        let (slope, _) = self.aim_line_attack(shooter, angle, MISSILE_RANGE);
+        for _ in 0..pellets {
+            let angle = (angle + self.angle_spread(20)).rem_euclid(360.0);
+            let damage = (self.rng.next() % 5 + 1) * 3;
+            self.line_attack(shooter, angle, MISSILE_RANGE, slope, damage);
+        }
+    }
+
+    // Claw or bite the target if it's in reach, for damage rolled on a
+    // `sides` sided die times `multiplier`. False if it's too far away.
+    fn melee(&mut self, index: usize, sides: i32, multiplier: i32) -> bool {
+        let Some(target) = self.target_of(index) else {
+            return false;
+        };
+        if !self.check_melee_range(index, target) {
+            return false;
+        }
+        let damage = (self.rng.next() % sides + 1) * multiplier;
+        let attacker = self.mobjs[index].as_ref().map(|mobj| mobj.body());
+        self.damage_mobj(target, attacker, Some(Target::Mobj(index)), damage);
+        true
+    }
+
+    // Attack up close if possible, otherwise throw a missile if the monster
+    // has one
+    fn melee_or_missile(
+        &mut self,
+        index: usize,
+        sides: i32,
+        multiplier: i32,
+        missile: Option<MobjType>,
+    ) {
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        self.face_target(index);
+        if self.melee(index, sides, multiplier) {
+            return;
+        }
+        if let Some(kind) = missile {
+            self.spawn_missile(index, target, kind);
+        }
+    }
+
+    fn missile_attack(&mut self, index: usize, kind: MobjType) {
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        self.face_target(index);
+        self.spawn_missile(index, target, kind);
+    }
+
+    // Turn a missile by `turn` degrees, keeping its speed
+    fn turn_missile(&mut self, missile: usize, turn: f32) {
+        let Some(mobj) = self.mobjs[missile].as_mut() else {
+            return;
+        };
+        mobj.angle = (mobj.angle + turn).rem_euclid(360.0);
+        let speed = info().mobj(mobj.kind).speed;
+        let (dx, dy) = heading(mobj.angle);
+        mobj.mom_x = speed * dx;
+        mobj.mom_y = speed * dy;
+    }
+
+    // A mancubus fires a pair of fireballs: the monster turns by `turn`
+    // first, then each fireball is turned by its own amount
+    fn fat_attack(&mut self, index: usize, turn: f32, spread: [f32; 2]) {
+        self.face_target(index);
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.angle = (mobj.angle + turn).rem_euclid(360.0);
+        }
+        for turn in spread {
+            if let Some(missile) = self.spawn_missile(index, target, MobjType::FatShot) {
+                self.turn_missile(missile, turn);
+            }
+        }
+    }
+
+    // A revenant's homing missile, fired from its shoulder
+    fn skel_missile(&mut self, index: usize) {
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        self.face_target(index);
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.z += 16.0;
+        }
+        let missile = self.spawn_missile(index, target, MobjType::Tracer);
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.z -= 16.0;
+        }
+        let Some(missile) = missile.and_then(|missile| self.mobjs[missile].as_mut()) else {
+            return;
+        };
+        missile.x += missile.mom_x;
+        missile.y += missile.mom_y;
+        missile.tracer = Some(target);
+    }
+
+    // Steer a homing missile towards its target, leaving a trail of smoke
+    fn tracer(&mut self, index: usize) {
+        if self.stats.time & 3 != 0 {
+            return;
+        }
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (x, y, z) = (mobj.x, mobj.y, mobj.z);
+        let (mom_x, mom_y, tracer) = (mobj.mom_x, mobj.mom_y, mobj.tracer);
+        self.spawn_puff(x, y, z, MISSILE_RANGE);
+        let smoke = self.spawn_mobj(MobjType::Smoke, x - mom_x, y - mom_y, 0.0);
+        if let Some(smoke) = self.mobjs[smoke].as_mut() {
+            smoke.z = z;
+            smoke.mom_z = 1.0;
+        }
+        self.shorten_tics(smoke);
+
+        let Some(dest) = tracer.and_then(|tracer| self.body(tracer)) else {
+            return;
+        };
+        if dest.health <= 0 {
+            return;
+        }
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        let goal = point_to_angle((mobj.x, mobj.y), (dest.x, dest.y));
+        let delta = (goal - mobj.angle + 540.0).rem_euclid(360.0) - 180.0;
+        mobj.angle = if delta.abs() <= TRACE_ANGLE {
+            goal
+        } else {
+            (mobj.angle + TRACE_ANGLE * delta.signum()).rem_euclid(360.0)
+        };
+        let speed = info().mobj(mobj.kind).speed;
+        let (dx, dy) = heading(mobj.angle);
+        mobj.mom_x = speed * dx;
+        mobj.mom_y = speed * dy;
+
+        // Climb or dive towards the target's chest
+        let time = (approx_distance(dest.x - mobj.x, dest.y - mobj.y) / speed).max(1.0);
+        let slope = (dest.z + 40.0 - mobj.z) / time;
+        if slope < mobj.mom_z {
+            mobj.mom_z -= 1.0 / 8.0;
+        } else {
+            mobj.mom_z += 1.0 / 8.0;
+        }
+    }
+
+    // A lost soul charges at its target
+    fn skull_attack(&mut self, index: usize) {
+        let Some(dest) = self.target_of(index).and_then(|target| self.body(target)) else {
+            return;
+        };
+        self.face_target(index);
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.flags |= MF_SKULLFLY;
+        let (dx, dy) = heading(mobj.angle);
+        mobj.mom_x = SKULL_SPEED * dx;
+        mobj.mom_y = SKULL_SPEED * dy;
+        let time = (approx_distance(dest.x - mobj.x, dest.y - mobj.y) / SKULL_SPEED).max(1.0);
+        mobj.mom_z = (dest.z + dest.height / 2.0 - mobj.z) / time;
+    }
+
+    // A pain elemental spits out a lost soul, which charges straight away.
+    // One spat into a wall dies at once.
+    fn pain_shoot_skull(&mut self, index: usize, angle: f32) {
+        let skulls = self
+            .mobjs
+            .iter()
+            .flatten()
+            .filter(|mobj| mobj.kind == MobjType::Skull)
+            .count();
+        if skulls > MAX_SKULLS {
+            return;
+        }
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (body, target) = (mobj.body(), mobj.target);
+        let skull_radius = info().mobj(MobjType::Skull).radius;
+        let prestep = 4.0 + 3.0 * (info().mobj(mobj.kind).radius + skull_radius) / 2.0;
+        let (dx, dy) = heading(angle);
+        let (x, y, z) = (body.x + prestep * dx, body.y + prestep * dy, body.z + 8.0);
+
+        let skull = self.spawn_mobj(MobjType::Skull, x, y, angle);
+        let Some(mobj) = self.mobjs[skull].as_mut() else {
+            return;
+        };
+        mobj.z = z;
+        let height = mobj.height;
+        if try_move(self, x, y, z, skull_radius, height, Target::Mobj(skull)).is_err() {
+            self.damage_mobj(
+                Target::Mobj(skull),
+                Some(body),
+                Some(Target::Mobj(index)),
+                10000,
+            );
+            return;
+        }
+        if let Some(mobj) = self.mobjs[skull].as_mut() {
+            mobj.target = target;
+        }
+        self.skull_attack(skull);
+    }
+
+    // The arch-vile sets its flames going under the target
+    fn vile_target(&mut self, index: usize) {
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        self.face_target(index);
+        let Some(dest) = self.body(target) else {
+            return;
+        };
+        let fire = self.spawn_mobj(MobjType::Fire, dest.x, dest.y, 0.0);
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.tracer = Some(Target::Mobj(fire));
+        }
+        if let Some(mobj) = self.mobjs[fire].as_mut() {
+            mobj.target = Some(Target::Mobj(index));
+            mobj.tracer = Some(target);
+        }
+        self.fire(fire);
+    }
+
+    // Keep an arch-vile's flames in front of its victim, while the vile can
+    // still see them
+    fn fire(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (Some(dest), Some(vile)) = (mobj.tracer, mobj.target) else {
+            return;
+        };
+        let (Some(dest), Some(vile)) = (self.body(dest), self.body(vile)) else {
+            return;
+        };
+        if !self.check_sight(&vile, &dest) {
+            return;
+        }
+        let (dx, dy) = heading(dest.angle);
+        let (x, y) = (dest.x + 24.0 * dx, dest.y + 24.0 * dy);
+        let sector = self
+            .sector_at(x, y)
+            .map(|sector| (sector.floor_height, sector.ceiling_height));
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.x = x;
+        mobj.y = y;
+        mobj.z = dest.z;
+        if let Some((floor_z, ceiling_z)) = sector {
+            mobj.floor_z = floor_z;
+            mobj.ceiling_z = ceiling_z;
+        }
+    }
+
+    // The flames erupt: the victim is hurt and thrown in the air, and the
+    // blast catches everything around
+    fn vile_attack(&mut self, index: usize) {
+        let Some(target) = self.target_of(index) else {
+            return;
+        };
+        self.face_target(index);
+        if !self.can_see(index, target) {
+            return;
+        }
+        let Some(vile) = self.mobjs[index].as_ref().map(|mobj| mobj.body()) else {
+            return;
+        };
+        self.damage_mobj(target, Some(vile), Some(Target::Mobj(index)), 20);
+        let mass = self
+            .kind_of(target)
+            .map_or(100, |kind| info().mobj(kind).mass);
+        self.push(target, (0.0, 0.0, 1000.0 / mass.max(1) as f32));
+
+        // Move the flames between the vile and its victim, and set them off
+        let fire = self.mobjs[index].as_ref().and_then(|mobj| mobj.tracer);
+        let (Some(Target::Mobj(fire)), Some(dest)) = (fire, self.body(target)) else {
+            return;
+        };
+        let (dx, dy) = heading(vile.angle);
+        let Some(mobj) = self.mobjs[fire].as_mut() else {
+            return;
+        };
+        mobj.x = dest.x - 24.0 * dx;
+        mobj.y = dest.y - 24.0 * dy;
+        let body = mobj.body();
+        self.radius_attack(body, Some(Target::Mobj(index)), 70);
+    }
+
+    // Look around for a corpse to bring back to life, otherwise chase
+    fn vile_chase(&mut self, index: usize) {
+        if let Some(corpse) = self.corpse_in_reach(index) {
+            // Face the corpse for the gesture, then go back to the target
+            let target = self.target_of(index);
+            if let Some(mobj) = self.mobjs[index].as_mut() {
+                mobj.target = Some(Target::Mobj(corpse));
+            }
+            self.face_target(index);
+            if let Some(mobj) = self.mobjs[index].as_mut() {
+                mobj.target = target;
+            }
+            self.set_mobj_state(index, info().mobj(MobjType::Vile).heal_state);
+
+            let Some(mobj) = self.mobjs[corpse].as_mut() else {
+                return;
+            };
+            let info = info().mobj(mobj.kind);
+            mobj.height *= 4.0;
+            mobj.flags = info.flags;
+            mobj.health = info.spawn_health;
+            mobj.target = None;
+            self.set_mobj_state(corpse, info.raise_state);
+            return;
+        }
+        self.chase(index);
+    }
+
+    // A corpse lying where the arch-vile is about to step, with room to
+    // stand back up
+    fn corpse_in_reach(&mut self, index: usize) -> Option<usize> {
+        let mobj = self.mobjs[index].as_ref()?;
+        if mobj.move_dir >= NO_DIR {
+            return None;
+        }
+        let speed = info().mobj(mobj.kind).speed;
+        let x = mobj.x + speed * DIR_X[mobj.move_dir];
+        let y = mobj.y + speed * DIR_Y[mobj.move_dir];
+        let vile_radius = info().mobj(MobjType::Vile).radius;
+
+        let corpse = (0..self.mobjs.len()).find(|&other| {
+            let Some(corpse) = &self.mobjs[other] else {
+                return false;
+            };
+            let info = info().mobj(corpse.kind);
+            let reach = vile_radius + info.radius;
+            corpse.flags & MF_CORPSE != 0
+                && corpse.tics == -1
+                && info.raise_state != S_NULL
+                && (corpse.x - x).abs() <= reach
+                && (corpse.y - y).abs() <= reach
+                && check_position(self, corpse.x, corpse.y, corpse.radius, Target::Mobj(other))
+                    .is_ok()
+        })?;
+        if let Some(mobj) = self.mobjs[corpse].as_mut() {
+            mobj.mom_x = 0.0;
+            mobj.mom_y = 0.0;
+        }
+        Some(corpse)
+    }
+
+    // The BFG's blast: forty rays fanned out from where its shooter was
+    // facing, each hurting the first thing it finds
+    fn bfg_spray(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (angle, shooter) = (mobj.angle, mobj.target);
+        let Some(shooter) = shooter else {
+            return;
+        };
+        for ray in 0..40 {
+            let angle = (angle - 45.0 + 90.0 / 40.0 * ray as f32).rem_euclid(360.0);
+            let (_, hit) = self.aim_line_attack(shooter, angle, 16.0 * 64.0);
+            let Some((hit, body)) = hit.and_then(|hit| Some((hit, self.body(hit)?))) else {
+                continue;
+            };
+            let flash = self.spawn_mobj(MobjType::ExtraBfg, body.x, body.y, 0.0);
+            if let Some(flash) = self.mobjs[flash].as_mut() {
+                flash.z = body.z + body.height / 4.0;
+            }
+            let damage = (0..15).map(|_| (self.rng.next() & 7) + 1).sum();
+            let inflictor = self.body(shooter);
+            self.damage_mobj(hit, inflictor, Some(shooter), damage);
+        }
+    }
+
+    fn can_see(&self, index: usize, target: Target) -> bool {
+        let looker = self.mobjs[index].as_ref().map(|mobj| mobj.body());
+        match (looker, self.body(target)) {
+            (Some(looker), Some(target)) => self.check_sight(&looker, &target),
+            _ => false,
+        }
+    }
+
+    // Stand still until the player is seen or heard
+    fn look(&mut self, index: usize) {
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.threshold = 0;
+        let (x, y, kind) = (mobj.x, mobj.y, mobj.kind);
+        let ambush = mobj.flags & MF_AMBUSH != 0;
+
+        // Whoever made a noise nearby is a target, though monsters lying in
+        // ambush wait until they can see them too
+        let heard = self
+            .sector_index_at(x, y)
+            .and_then(|sector| self.sound_targets[sector])
+            .filter(|&target| {
+                self.body(target)
+                    .is_some_and(|body| body.flags & MF_SHOOTABLE != 0)
+            });
+        let noticed = match heard {
+            Some(target) => {
+                if let Some(mobj) = self.mobjs[index].as_mut() {
+                    mobj.target = Some(target);
+                }
+                !ambush || self.can_see(index, target)
+            }
+            None => false,
+        };
+
+        if noticed || self.look_for_players(index, false) {
+            self.set_mobj_state(index, info().mobj(kind).see_state);
+        }
+    }
+
+    // Target the player if they're alive and in view. Without `all_around`
+    // only the front half is watched, unless the player is close behind.
+    fn look_for_players(&mut self, index: usize, all_around: bool) -> bool {
+        let (Some(mobj), Some(player)) = (&self.mobjs[index], self.player_body) else {
+            return false;
+        };
+        if player.health <= 0 || !self.can_see(index, Target::Player) {
+            return false;
+        }
+        if !all_around {
+            let angle = point_to_angle((mobj.x, mobj.y), (player.x, player.y));
+            let behind = (angle - mobj.angle).rem_euclid(360.0);
+            let distance = approx_distance(player.x - mobj.x, player.y - mobj.y);
+            if behind > 90.0 && behind < 270.0 && distance > MELEE_RANGE {
+                return false;
+            }
+        }
+
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.target = Some(Target::Player);
+        }
+        true
+    }
+
+    // Walk after the target, attacking when it's in range
+    fn chase(&mut self, index: usize) {
+        let target = self.mobjs[index].as_ref().and_then(|mobj| mobj.target);
+        let target_body = target.and_then(|target| self.body(target));
+        let fast = self.fast_monsters;
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        let kind = mobj.kind;
+
+        if mobj.reaction_time > 0 {
+            mobj.reaction_time -= 1;
+        }
+        // A grudge against whoever hurt it wears off, or ends with them
+        if mobj.threshold > 0 {
+            if target_body.is_none_or(|body| body.health <= 0) {
+                mobj.threshold = 0;
+            } else {
+                mobj.threshold -= 1;
+            }
+        }
+        if mobj.move_dir < NO_DIR {
+            mobj.angle = turn_towards(mobj.angle, dir_angle(mobj.move_dir));
+        }
+
+        let (Some(target), Some(target_body)) = (target, target_body) else {
+            self.lost_target(index, kind);
+            return;
+        };
+        if target_body.flags & MF_SHOOTABLE == 0 {
+            self.lost_target(index, kind);
+            return;
+        }
+
+        // Don't attack twice in a row
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        if mobj.flags & MF_JUSTATTACKED != 0 {
+            mobj.flags &= !MF_JUSTATTACKED;
+            if !fast {
+                self.new_chase_dir(index, &target_body);
+            }
+            return;
+        }
+
+        let info = info().mobj(kind);
+        if info.melee_state != S_NULL && self.check_melee_range(index, target) {
+            self.set_mobj_state(index, info.melee_state);
+            return;
+        }
+        if info.missile_state != S_NULL {
+            let still_walking = self.mobjs[index]
+                .as_ref()
+                .is_some_and(|mobj| mobj.move_count != 0);
+            if (fast || !still_walking) && self.check_missile_range(index, target) {
+                self.set_mobj_state(index, info.missile_state);
+                if let Some(mobj) = self.mobjs[index].as_mut() {
+                    mobj.flags |= MF_JUSTATTACKED;
+                }
+                return;
+            }
+        }
+
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.move_count -= 1;
+        if mobj.move_count < 0 || !self.step_move(index) {
+            self.new_chase_dir(index, &target_body);
+        }
+    }
+
+    // The target is gone: look for another, or go back to waiting
+    fn lost_target(&mut self, index: usize, kind: MobjType) {
+        if !self.look_for_players(index, true) {
+            self.set_mobj_state(index, info().mobj(kind).spawn_state);
+        }
+    }
+
+    fn face_target(&mut self, index: usize) {
+        let target = self.mobjs[index].as_ref().and_then(|mobj| mobj.target);
+        let Some(target) = target.and_then(|target| self.body(target)) else {
+            return;
+        };
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.flags &= !MF_AMBUSH;
+        mobj.angle = point_to_angle((mobj.x, mobj.y), (target.x, target.y));
+        // Partly invisible targets are hard to aim at
+        if target.flags & MF_SHADOW != 0 {
+            let wobble = self.rng.next() - self.rng.next();
+            mobj.angle = (mobj.angle + wobble as f32 * 360.0 / 2048.0).rem_euclid(360.0);
+        }
+    }
+
+    // Keep firing while the target stays in view, with a chance to stop
+    fn refire(&mut self, index: usize, keep_firing: i32) {
+        self.face_target(index);
+        if self.rng.next() < keep_firing {
+            return;
+        }
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let kind = mobj.kind;
+        let visible = mobj.target.is_some_and(|target| {
+            self.body(target).is_some_and(|body| body.health > 0) && self.can_see(index, target)
+        });
+        if !visible {
+            self.set_mobj_state(index, info().mobj(kind).see_state);
+        }
+    }
+
+    fn check_melee_range(&self, index: usize, target: Target) -> bool {
+        let (Some(mobj), Some(body)) = (&self.mobjs[index], self.body(target)) else {
+            return false;
+        };
+        let distance = approx_distance(body.x - mobj.x, body.y - mobj.y);
+        distance < MELEE_RANGE - 20.0 + body.radius && self.can_see(index, target)
+    }
+
+    // Decide whether to fire: the further away the target, the less likely
+    fn check_missile_range(&mut self, index: usize, target: Target) -> bool {
+        if !self.can_see(index, target) {
+            return false;
+        }
+        let target_body = self.body(target);
+        let (Some(mobj), Some(body)) = (self.mobjs[index].as_mut(), target_body) else {
+            return false;
+        };
+        // Shoot straight back at whoever just did the hurting
+        if mobj.flags & MF_JUSTHIT != 0 {
+            mobj.flags &= !MF_JUSTHIT;
+            return true;
+        }
+        if mobj.reaction_time > 0 {
+            return false;
+        }
+
+        let mut distance = approx_distance(body.x - mobj.x, body.y - mobj.y) - 64.0;
+        // Monsters without a melee attack fire more often
+        if info().mobj(mobj.kind).melee_state == S_NULL {
+            distance -= 128.0;
+        }
+        match mobj.kind {
+            MobjType::Vile if distance > 14.0 * 64.0 => return false,
+            MobjType::Undead if distance < 196.0 => return false,
+            MobjType::Undead | MobjType::Cyborg | MobjType::Spider | MobjType::Skull => {
+                distance /= 2.0
+            }
+            _ => {}
+        }
+        distance = distance.min(200.0);
+        if mobj.kind == MobjType::Cyborg {
+            distance = distance.min(160.0);
+        }
+        self.rng.next() as f32 >= distance
+    }
+
+    // Take a step in the direction the monster is walking. Floating monsters
+    // rise or sink when something's in their way.
+    fn step_move(&mut self, index: usize) -> bool {
+        let Some(mobj) = &self.mobjs[index] else {
+            return false;
+        };
+        if mobj.move_dir >= NO_DIR {
+            return false;
+        }
+        let speed = info().mobj(mobj.kind).speed;
+        let x = mobj.x + speed * DIR_X[mobj.move_dir];
+        let y = mobj.y + speed * DIR_Y[mobj.move_dir];
+
+        let result = self.try_monster_move(index, x, y);
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return false;
+        };
+        match result {
+            Ok(()) => {
+                mobj.flags &= !MF_INFLOAT;
+                if mobj.flags & MF_FLOAT == 0 {
+                    mobj.z = mobj.floor_z;
+                }
+                true
+            }
+            Err(Some(floor_z)) if mobj.flags & MF_FLOAT != 0 => {
+                if mobj.z < floor_z {
+                    mobj.z += FLOAT_SPEED;
+                } else {
+                    mobj.z -= FLOAT_SPEED;
+                }
+                mobj.flags |= MF_INFLOAT;
+                true
+            }
+            // Blocked: try opening whatever door is in the way
+            Err(_) => {
+                let radius = mobj.radius;
+                let lines = self.special_lines_touched(x, y, radius);
+                if lines.is_empty() {
+                    return false;
+                }
+                if let Some(mobj) = self.mobjs[index].as_mut() {
+                    mobj.move_dir = NO_DIR;
+                }
+                let mut good = false;
+                for line in lines {
+                    good |= self.use_special_line(line, false, Target::Mobj(index));
+                }
+                good
+            }
+        }
+    }
+
+    // Move to (x, y) if the monster fits there and wouldn't step off a
+    // ledge. When the gap is tall enough but at the wrong height, the error
+    // holds the floor height there so floating monsters can adjust.
+    fn try_monster_move(&mut self, index: usize, x: f32, y: f32) -> Result<(), Option<f32>> {
+        let Some(mobj) = &self.mobjs[index] else {
+            return Err(None);
+        };
+        let (z, height, flags) = (mobj.z, mobj.height, mobj.flags);
+        let from = (mobj.x, mobj.y);
+        let opening =
+            check_position(self, x, y, mobj.radius, Target::Mobj(index)).map_err(|_| None)?;
+
+        if opening.ceiling_z - opening.floor_z < height {
+            return Err(None);
+        }
+        if opening.ceiling_z - z < height || opening.floor_z - z > MAX_STEP_HEIGHT {
+            return Err(Some(opening.floor_z));
+        }
+        let careful = flags & (MF_DROPOFF | MF_FLOAT) == 0;
+        if careful && opening.floor_z - opening.dropoff_z > MAX_STEP_HEIGHT {
+            return Err(Some(opening.floor_z));
+        }
+
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.x = x;
+            mobj.y = y;
+            mobj.floor_z = opening.floor_z;
+            mobj.ceiling_z = opening.ceiling_z;
+        }
+        self.cross_special_lines(from, (x, y), Target::Mobj(index));
+        Ok(())
+    }
+
+    fn try_walk(&mut self, index: usize, dir: usize) -> bool {
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.move_dir = dir;
+        }
+        if !self.step_move(index) {
+            return false;
+        }
+        let steps = self.rng.next() & 15;
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.move_count = steps;
+        }
+        true
+    }
+
+    // Pick a new direction to walk in: straight at the target if possible,
+    // otherwise anything but back the way it came
+    fn new_chase_dir(&mut self, index: usize, target: &Body) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let old_dir = mobj.move_dir;
+        let turnaround = OPPOSITE[old_dir];
+        let (dx, dy) = (target.x - mobj.x, target.y - mobj.y);
+
+        let mut d1 = if dx > 10.0 {
+            EAST
+        } else if dx < -10.0 {
+            WEST
+        } else {
+            NO_DIR
+        };
+        let mut d2 = if dy < -10.0 {
+            SOUTH
+        } else if dy > 10.0 {
+            NORTH
+        } else {
+            NO_DIR
+        };
+
+        // Try cutting straight across
+        if d1 != NO_DIR && d2 != NO_DIR {
+            let dir = DIAGONALS[(dy < 0.0) as usize * 2 + (dx > 0.0) as usize];
+            if dir != turnaround && self.try_walk(index, dir) {
+                return;
+            }
+        }
+
+        // Then the two straight directions, the more useful one first
+        if self.rng.next() > 200 || dy.abs() > dx.abs() {
+            std::mem::swap(&mut d1, &mut d2);
+        }
+        for dir in [d1, d2] {
+            if dir != NO_DIR && dir != turnaround && self.try_walk(index, dir) {
+                return;
+            }
+        }
+
+        // Keep going the same way, or search for any way at all
+        if old_dir != NO_DIR && self.try_walk(index, old_dir) {
+            return;
+        }
+        let dirs: Vec<usize> = if self.rng.next() & 1 != 0 {
+            (EAST..=SOUTH_EAST).collect()
+        } else {
+            (EAST..=SOUTH_EAST).rev().collect()
+        };
+        for dir in dirs {
+            if dir != turnaround && self.try_walk(index, dir) {
+                return;
+            }
+        }
+        if turnaround != NO_DIR && self.try_walk(index, turnaround) {
+            return;
+        }
+
+        // Stuck
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.move_dir = NO_DIR;
+        }
+    }
+
+    // A noise wakes every monster within earshot: it floods out through
+    // open two-sided lines, stopping at the second sound blocking one
+    pub fn noise_alert(&mut self, target: Target, x: f32, y: f32) {
+        let Some(sector) = self.sector_index_at(x, y) else {
+            return;
+        };
+        let mut reached = vec![None; self.sectors.len()];
+        self.recursive_sound(sector, 0, target, &mut reached);
+    }
+
+    fn recursive_sound(
+        &mut self,
+        sector: usize,
+        blocks: u8,
+        target: Target,
+        reached: &mut [Option<u8>],
+    ) {
+        // Already reached through no more sound blocking lines
+        if reached[sector].is_some_and(|earlier| earlier <= blocks) {
+            return;
+        }
+        reached[sector] = Some(blocks);
+        self.sound_targets[sector] = Some(target);
+
+        for i in 0..self.sector_lines[sector].len() {
+            let line = &self.linedefs[self.sector_lines[sector][i]];
+            let sound_block = line.flags & ML_SOUNDBLOCK != 0;
+            let front = self.side_sector_index(line.front_sidedef);
+            let back = self.side_sector_index(line.back_sidedef);
+            let (Some(front), Some(back)) = (front, back) else {
+                continue;
+            };
+            // Closed doors keep the sound in
+            let (front_sector, back_sector) = (&self.sectors[front], &self.sectors[back]);
+            let top = front_sector.ceiling_height.min(back_sector.ceiling_height);
+            let bottom = front_sector.floor_height.max(back_sector.floor_height);
+            if top <= bottom {
+                continue;
+            }
+
+            let other = if front == sector { back } else { front };
+            if !sound_block {
+                self.recursive_sound(other, blocks, target, reached);
+            } else if blocks == 0 {
+                self.recursive_sound(other, 1, target, reached);
+            }
+        }
+    }
+}
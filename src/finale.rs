@@ -0,0 +1,347 @@
+use crate::font::Font;
+use crate::level::parse_map_name;
+use crate::render::{draw_flat, Graphics, SCREEN_Y_OFFSET};
+use crate::strings::*;
+use crate::wad_reader::WadData;
+
+// Tics per typed character, and how long the full text stays up
+const TEXT_SPEED: u32 = 3;
+const TEXT_WAIT: u32 = 250;
+const TEXT_X: i32 = 10;
+const TEXT_Y: i32 = 10;
+const TEXT_LINE_HEIGHT: i32 = 11;
+
+// Doom II story breaks can be skipped after this many tics
+const SKIP_DELAY: u32 = 50;
+
+// The bunny scroll pans from PFUB1 to PFUB2, then "THE END" is shot up
+const BUNNY_SCROLL_START: u32 = 230;
+const THE_END_START: u32 = 1130;
+const THE_END_STEP_START: u32 = 1180;
+const THE_END_STEP_TICS: u32 = 5;
+const THE_END_STEPS: u32 = 6;
+
+// Tics per frame of the cast call animations
+const CAST_WALK_TICS: u32 = 4;
+const CAST_ATTACK_TICS: u32 = 8;
+const CAST_DEATH_TICS: u32 = 6;
+// Walking frames shown before each attack
+const CAST_WALK_FRAMES: u32 = 12;
+
+struct CastMember {
+    name: &'static str,
+    sprite: &'static str,
+    // Sprite frame letters of each animation
+    walk: &'static str,
+    attack: &'static str,
+    death: &'static str,
+}
+
+const fn member(
+    name: &'static str,
+    sprite: &'static str,
+    walk: &'static str,
+    attack: &'static str,
+    death: &'static str,
+) -> CastMember {
+    CastMember {
+        name,
+        sprite,
+        walk,
+        attack,
+        death,
+    }
+}
+
+const CAST: [CastMember; 17] = [
+    member(CC_ZOMBIE, "POSS", "ABCD", "EF", "HIJKL"),
+    member(CC_SHOTGUN, "SPOS", "ABCD", "EF", "HIJKL"),
+    member(CC_HEAVY, "CPOS", "ABCD", "EF", "HIJKLMN"),
+    member(CC_IMP, "TROO", "ABCD", "EFG", "IJKLM"),
+    member(CC_DEMON, "SARG", "ABCD", "EFG", "IJKLMN"),
+    member(CC_LOST, "SKUL", "AB", "CD", "FGHIJK"),
+    member(CC_CACO, "HEAD", "A", "BCD", "GHIJKL"),
+    member(CC_HELL, "BOS2", "ABCD", "EFG", "IJKLMNO"),
+    member(CC_BARON, "BOSS", "ABCD", "EFG", "IJKLMNO"),
+    member(CC_ARACH, "BSPI", "ABCDEF", "AG", "JKLMNOP"),
+    member(CC_PAIN, "PAIN", "ABC", "DEF", "HIJKLM"),
+    member(CC_REVEN, "SKEL", "ABCDEF", "JK", "LMNOPQ"),
+    member(CC_MANCU, "FATT", "ABCDEF", "GH", "KLMNOPQRST"),
+    member(CC_ARCH, "VILE", "ABCDEF", "GHIJKLMNOP", "QRSTUVWXYZ"),
+    member(CC_SPIDER, "SPID", "ABCDEF", "AGH", "JKLMNOPQRS"),
+    member(CC_CYBER, "CYBR", "ABCD", "EF", "HIJKLMNOP"),
+    member(CC_HERO, "PLAY", "ABCD", "EF", "HIJKLMN"),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    // The story typed out over a flat
+    Text,
+    // The episode's closing picture, or the bunny scroll after episode 3
+    ArtScreen,
+    // Doom II's parade of monsters
+    Cast,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CastAction {
+    Walk,
+    Attack,
+    Death,
+}
+
+pub struct Finale {
+    stage: Stage,
+    // Tics since the current stage started
+    count: u32,
+    text: &'static str,
+    flat: Vec<u8>,
+    episode: usize,
+    map: usize,
+    retail: bool,
+    graphics: Graphics,
+    // A Doom II story break is over and the next map can load
+    pub finished: bool,
+
+    cast_member: usize,
+    cast_action: CastAction,
+    cast_frame: usize,
+    cast_tics: u32,
+    cast_walk_frames: u32,
+}
+
+// The story text and background flat shown after a map, if any
+fn finale_text(episode: usize, map: usize, secret: bool) -> Option<(&'static str, &'static str)> {
+    match (episode, map) {
+        (0, 15) if secret => Some((C5TEXT, "RROCK13")),
+        (0, 31) if secret => Some((C6TEXT, "RROCK19")),
+        (0, 6) => Some((C1TEXT, "SLIME16")),
+        (0, 11) => Some((C2TEXT, "RROCK14")),
+        (0, 20) => Some((C3TEXT, "RROCK07")),
+        (0, 30) => Some((C4TEXT, "RROCK17")),
+        (1, 8) => Some((E1TEXT, "FLOOR4_8")),
+        (2, 8) => Some((E2TEXT, "SFLR6_1")),
+        (3, 8) => Some((E3TEXT, "MFLR8_4")),
+        (4, 8) => Some((E4TEXT, "MFLR8_3")),
+        _ => None,
+    }
+}
+
+impl Finale {
+    // None when the story doesn't continue after this map, or after
+    // leaving it by its secret exit
+    pub fn new(wad: &WadData, last_map: &str, retail: bool, secret: bool) -> Option<Finale> {
+        let (episode, map) = parse_map_name(last_map)?;
+        let (text, flat) = finale_text(episode, map, secret)?;
+
+        // Only the pictures this finale can show, looked up by name so
+        // replacement lumps are picked up
+        let mut names: Vec<String> = match (episode, map) {
+            (0, 30) => vec!["BOSSBACK".to_string()],
+            (1, _) if retail => vec!["CREDIT".to_string()],
+            (1, _) => vec!["HELP2".to_string()],
+            (2, _) => vec!["VICTORY2".to_string()],
+            (3, _) => vec!["PFUB1".to_string(), "PFUB2".to_string()],
+            (4, _) => vec!["ENDPIC".to_string()],
+            _ => Vec::new(),
+        };
+        if episode == 3 {
+            names.extend((0..=THE_END_STEPS).map(|i| format!("END{}", i)));
+        }
+        let mut graphics = Graphics::load(wad, &names);
+
+        if (episode, map) == (0, 30) {
+            for member in &CAST {
+                let frames = format!("{}{}{}", member.walk, member.attack, member.death);
+                for frame in frames.chars() {
+                    // The cast faces the camera: rotation 1
+                    if let Some(patch) = wad.read_sprite_frame(member.sprite, frame, '1') {
+                        graphics.insert(&format!("{}{}", member.sprite, frame), patch);
+                    }
+                }
+            }
+        }
+
+        Some(Finale {
+            stage: Stage::Text,
+            count: 0,
+            text,
+            flat: wad.read_flat(flat).unwrap_or_else(|e| {
+                println!("Finale background missing: {}", e);
+                Vec::new()
+            }),
+            episode,
+            map,
+            retail,
+            graphics,
+            finished: false,
+            cast_member: 0,
+            cast_action: CastAction::Walk,
+            cast_frame: 0,
+            cast_tics: CAST_WALK_TICS,
+            cast_walk_frames: 0,
+        })
+    }
+
+    // Returns true when the finale moved on to a new screen, to be wiped in
+    pub fn tick(&mut self) -> bool {
+        self.count += 1;
+
+        match self.stage {
+            // Doom II waits for the player to skip its story breaks
+            Stage::Text if self.episode != 0 => {
+                let text_tics = self.text.len() as u32 * TEXT_SPEED + TEXT_WAIT;
+                if self.count > text_tics {
+                    self.stage = Stage::ArtScreen;
+                    self.count = 0;
+                    return true;
+                }
+            }
+            Stage::Cast => self.cast_tick(),
+            _ => {}
+        }
+        false
+    }
+
+    // The player pressed use or fire. Returns true if the screen changed.
+    pub fn responder(&mut self) -> bool {
+        match self.stage {
+            Stage::Text if self.episode == 0 && self.count > SKIP_DELAY => {
+                if self.map == 30 {
+                    self.start_cast();
+                    return true;
+                }
+                self.finished = true;
+            }
+            Stage::Cast if self.cast_action != CastAction::Death => {
+                self.set_cast_action(CastAction::Death, CAST_DEATH_TICS);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn start_cast(&mut self) {
+        self.stage = Stage::Cast;
+        self.count = 0;
+        self.cast_member = 0;
+        self.set_cast_action(CastAction::Walk, CAST_WALK_TICS);
+    }
+
+    fn set_cast_action(&mut self, action: CastAction, tics: u32) {
+        self.cast_action = action;
+        self.cast_frame = 0;
+        self.cast_tics = tics;
+        self.cast_walk_frames = 0;
+    }
+
+    fn cast_frames(&self) -> &'static str {
+        let member = &CAST[self.cast_member];
+        match self.cast_action {
+            CastAction::Walk => member.walk,
+            CastAction::Attack => member.attack,
+            CastAction::Death => member.death,
+        }
+    }
+
+    fn cast_tick(&mut self) {
+        self.cast_tics -= 1;
+        if self.cast_tics > 0 {
+            return;
+        }
+
+        let frame_count = self.cast_frames().len();
+        match self.cast_action {
+            // Walk for a while, then show off the attack
+            CastAction::Walk => {
+                self.cast_frame = (self.cast_frame + 1) % frame_count;
+                self.cast_walk_frames += 1;
+                self.cast_tics = CAST_WALK_TICS;
+                if self.cast_walk_frames == CAST_WALK_FRAMES {
+                    self.set_cast_action(CastAction::Attack, CAST_ATTACK_TICS);
+                }
+            }
+            CastAction::Attack => {
+                self.cast_frame += 1;
+                self.cast_tics = CAST_ATTACK_TICS;
+                if self.cast_frame == frame_count {
+                    self.set_cast_action(CastAction::Walk, CAST_WALK_TICS);
+                }
+            }
+            // Once the corpse has dropped, bring on the next monster
+            CastAction::Death => {
+                self.cast_frame += 1;
+                self.cast_tics = CAST_DEATH_TICS;
+                if self.cast_frame == frame_count {
+                    self.cast_member = (self.cast_member + 1) % CAST.len();
+                    self.set_cast_action(CastAction::Walk, CAST_WALK_TICS);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], font: &Font) {
+        match self.stage {
+            Stage::Text => self.draw_text(buffer, font),
+            Stage::ArtScreen if self.episode == 3 => self.draw_bunny_scroll(buffer, font),
+            Stage::ArtScreen => {
+                let page = match self.episode {
+                    1 if self.retail => "CREDIT",
+                    1 => "HELP2",
+                    2 => "VICTORY2",
+                    _ => "ENDPIC",
+                };
+                self.graphics.draw(buffer, font, page, "THE END", 0, 0);
+            }
+            Stage::Cast => self.draw_cast(buffer, font),
+        }
+    }
+
+    // Type the story out a character at a time
+    fn draw_text(&self, buffer: &mut [u32], font: &Font) {
+        draw_flat(buffer, &self.flat, self.graphics.palette());
+
+        let shown = (self.count.saturating_sub(10) / TEXT_SPEED) as usize;
+        let text: String = self.text.chars().take(shown).collect();
+        for (i, line) in text.lines().enumerate() {
+            font.draw_text(
+                buffer,
+                TEXT_X,
+                TEXT_Y + i as i32 * TEXT_LINE_HEIGHT + SCREEN_Y_OFFSET,
+                line,
+            );
+        }
+    }
+
+    fn draw_bunny_scroll(&self, buffer: &mut [u32], font: &Font) {
+        let scrolled = (320 - (self.count as i32 - BUNNY_SCROLL_START as i32) / 2).clamp(0, 320);
+        self.graphics.draw(buffer, font, "PFUB2", "", -scrolled, 0);
+        self.graphics
+            .draw(buffer, font, "PFUB1", "", 320 - scrolled, 0);
+
+        if self.count < THE_END_START {
+            return;
+        }
+        let step = if self.count < THE_END_STEP_START {
+            0
+        } else {
+            ((self.count - THE_END_STEP_START) / THE_END_STEP_TICS).min(THE_END_STEPS)
+        };
+        let (x, y) = ((320 - 13 * 8) / 2, (200 - 8 * 8) / 2);
+        self.graphics
+            .draw(buffer, font, &format!("END{}", step), "THE END", x, y);
+    }
+
+    fn draw_cast(&self, buffer: &mut [u32], font: &Font) {
+        self.graphics.draw(buffer, font, "BOSSBACK", "", 0, 0);
+
+        let member = &CAST[self.cast_member];
+        if let Some(frame) = self.cast_frames().chars().nth(self.cast_frame) {
+            let name = format!("{}{}", member.sprite, frame);
+            self.graphics.draw(buffer, font, &name, "", 160, 170);
+        }
+
+        let x = (320 - font.text_width(member.name)) / 2;
+        font.draw_text(buffer, x, 180 + SCREEN_Y_OFFSET, member.name);
+    }
+}
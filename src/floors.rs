@@ -0,0 +1,461 @@
+// Moving floors, like the original's p_floor.c: raising and lowering floors,
+// stairs and donuts, and the plane mover that doors, lifts and crushers all
+// share, squashing whatever doesn't fit
+use crate::collision::line_opening;
+use crate::info::{info, MobjType, MF_DROPPED, MF_SHOOTABLE, MF_SOLID};
+use crate::level::Level;
+use crate::mobj::Target;
+use crate::specials::{SectorThinker, ML_TWOSIDED};
+
+pub const FLOOR_SPEED: f32 = 1.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Plane {
+    Floor,
+    Ceiling,
+}
+
+// How a tic of movement went
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveResult {
+    Ok,
+    // Something's in the way
+    Crushed,
+    // Arrived
+    PastDest,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FloorKind {
+    // Down to the highest neighbouring floor
+    LowerFloor,
+    // Down to the lowest neighbouring floor
+    LowerFloorToLowest,
+    // Fast down to 8 above the highest neighbouring floor
+    TurboLower,
+    // Up to the lowest neighbouring ceiling
+    RaiseFloor,
+    // Up to the next higher neighbouring floor
+    RaiseFloorToNearest,
+    // Up by the height of the shortest lower texture around
+    RaiseToTexture,
+    // Down to the lowest neighbouring floor, taking its texture and special
+    LowerAndChange,
+    RaiseFloor24,
+    RaiseFloor24AndChange,
+    // Up to 8 below the lowest neighbouring ceiling, crushing
+    RaiseFloorCrush,
+    // Fast up to the next higher neighbouring floor
+    RaiseFloorTurbo,
+    // The outside of a donut, rising to fill the gap
+    DonutRaise,
+    RaiseFloor512,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StairKind {
+    Build8,
+    Turbo16,
+}
+
+pub struct FloorMove {
+    pub kind: FloorKind,
+    pub crush: bool,
+    pub sector: usize,
+    pub direction: i32,
+    // What the floor becomes at the end, for the kinds that change it
+    pub new_special: i16,
+    pub texture: String,
+    pub dest_height: f32,
+    pub speed: f32,
+}
+
+impl Level {
+    // Move a sector's floor or ceiling `speed` towards `dest`, backing off
+    // if things don't fit unless it's a crusher
+    pub fn move_plane(
+        &mut self,
+        sector: usize,
+        speed: f32,
+        dest: f32,
+        crush: bool,
+        plane: Plane,
+        direction: i32,
+    ) -> MoveResult {
+        fn height(level: &mut Level, sector: usize, plane: Plane) -> &mut f32 {
+            let sector = &mut level.sectors[sector];
+            match plane {
+                Plane::Floor => &mut sector.floor_height,
+                Plane::Ceiling => &mut sector.ceiling_height,
+            }
+        }
+        let last = *height(self, sector, plane);
+        let arriving = if direction < 0 {
+            last - speed < dest
+        } else {
+            last + speed > dest
+        };
+        if arriving {
+            *height(self, sector, plane) = dest;
+            if self.change_sector(sector, crush) {
+                *height(self, sector, plane) = last;
+                self.change_sector(sector, crush);
+            }
+            return MoveResult::PastDest;
+        }
+
+        *height(self, sector, plane) = last + speed * direction as f32;
+        if !self.change_sector(sector, crush) {
+            return MoveResult::Ok;
+        }
+        // Nothing gets in the way of a rising ceiling. Crushers keep
+        // pressing down on whatever's under them, anything else backs off.
+        if plane == Plane::Ceiling && direction > 0 {
+            return MoveResult::Ok;
+        }
+        let pressing = plane == Plane::Ceiling || direction > 0;
+        if !(crush && pressing) {
+            *height(self, sector, plane) = last;
+            self.change_sector(sector, crush);
+        }
+        MoveResult::Crushed
+    }
+
+    // A sector moved: lift, lower or squash everything in it. Returns true
+    // if anything alive no longer fits.
+    fn change_sector(&mut self, sector: usize, crush: bool) -> bool {
+        let (left, right, bottom, top) = self.sector_box(sector);
+        let near = |x: f32, y: f32, radius: f32| {
+            x + radius >= left && x - radius <= right && y + radius >= bottom && y - radius <= top
+        };
+        let crunch = crush && self.stats.time & 3 == 0;
+        let mut no_fit = false;
+
+        for index in 0..self.mobjs.len() {
+            let Some(mobj) = &self.mobjs[index] else {
+                continue;
+            };
+            if !near(mobj.x, mobj.y, mobj.radius) || self.thing_height_clip(index) {
+                continue;
+            }
+            let Some(mobj) = self.mobjs[index].as_mut() else {
+                continue;
+            };
+            // Bodies get crushed to giblets, dropped items just go
+            if mobj.health <= 0 {
+                mobj.flags &= !MF_SOLID;
+                mobj.height = 0.0;
+                mobj.radius = 0.0;
+                self.set_mobj_state(index, info().mobj(MobjType::Gibs).spawn_state);
+                continue;
+            }
+            if mobj.flags & MF_DROPPED != 0 {
+                self.mobjs[index] = None;
+                continue;
+            }
+            if mobj.flags & MF_SHOOTABLE == 0 {
+                continue;
+            }
+            no_fit = true;
+            if crunch {
+                let (x, y, z) = (mobj.x, mobj.y, mobj.z + mobj.height / 2.0);
+                self.damage_mobj(Target::Mobj(index), None, None, 10);
+                self.spray_blood(x, y, z);
+            }
+        }
+
+        // The player's own height is sorted out by the game after the tic,
+        // here it's just whether they fit
+        if let Some(player) = self.player_body {
+            if near(player.x, player.y, player.radius) && player.health > 0 {
+                let (floor_z, ceiling_z) = self.heights_at(player.x, player.y, player.radius);
+                if let Some(body) = self.player_body.as_mut() {
+                    body.z = body.z.max(floor_z);
+                }
+                if ceiling_z - floor_z < player.height {
+                    no_fit = true;
+                    if crunch {
+                        self.damage_mobj(Target::Player, None, None, 10);
+                        self.spray_blood(player.x, player.y, player.z + player.height / 2.0);
+                    }
+                }
+            }
+        }
+        no_fit
+    }
+
+    // Blood squirting out of something being crushed
+    fn spray_blood(&mut self, x: f32, y: f32, z: f32) {
+        let index = self.spawn_mobj(MobjType::Blood, x, y, 0.0);
+        let mom_x = (self.rng.next() - self.rng.next()) as f32 / 16.0;
+        let mom_y = (self.rng.next() - self.rng.next()) as f32 / 16.0;
+        if let Some(blood) = self.mobjs[index].as_mut() {
+            blood.z = z;
+            blood.mom_x = mom_x;
+            blood.mom_y = mom_y;
+        }
+    }
+
+    // The floor and ceiling around a spot, falling back to the sector's own
+    // for things stuck in walls
+    pub fn heights_at(&self, x: f32, y: f32, radius: f32) -> (f32, f32) {
+        match line_opening(self, x, y, radius) {
+            Ok(opening) => (opening.floor_z, opening.ceiling_z),
+            Err(_) => self.sector_at(x, y).map_or((0.0, 0.0), |sector| {
+                (sector.floor_height, sector.ceiling_height)
+            }),
+        }
+    }
+
+    // Keep a thing on the floor it was standing on, or under the ceiling.
+    // Returns false if it no longer fits.
+    fn thing_height_clip(&mut self, index: usize) -> bool {
+        let Some(mobj) = &self.mobjs[index] else {
+            return true;
+        };
+        let (floor_z, ceiling_z) = self.heights_at(mobj.x, mobj.y, mobj.radius);
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return true;
+        };
+        let on_floor = mobj.z == mobj.floor_z;
+        mobj.floor_z = floor_z;
+        mobj.ceiling_z = ceiling_z;
+        if on_floor {
+            mobj.z = floor_z;
+        } else if mobj.z + mobj.height > ceiling_z {
+            mobj.z = ceiling_z - mobj.height;
+        }
+        ceiling_z - floor_z >= mobj.height
+    }
+
+    // Start floors moving in every sector tagged `tag`. `front` is the
+    // sector in front of the line that did it, for the kinds that copy its
+    // floor.
+    pub fn do_floor(&mut self, tag: i16, kind: FloorKind, front: Option<usize>) -> bool {
+        let mut started = false;
+        let mut sector = None;
+        while let Some(index) = self.find_sector_from_tag(tag, sector) {
+            sector = Some(index);
+            if self.sector_thinkers[index].is_some() {
+                continue;
+            }
+            started = true;
+
+            let floor_height = self.sectors[index].floor_height;
+            let mut floor = FloorMove {
+                kind,
+                crush: false,
+                sector: index,
+                direction: 1,
+                new_special: 0,
+                texture: String::new(),
+                dest_height: floor_height,
+                speed: FLOOR_SPEED,
+            };
+            match kind {
+                FloorKind::LowerFloor => {
+                    floor.direction = -1;
+                    floor.dest_height = self.highest_floor_surrounding(index);
+                }
+                FloorKind::LowerFloorToLowest => {
+                    floor.direction = -1;
+                    floor.dest_height = self.lowest_floor_surrounding(index);
+                }
+                FloorKind::TurboLower => {
+                    floor.direction = -1;
+                    floor.speed = FLOOR_SPEED * 4.0;
+                    floor.dest_height = self.highest_floor_surrounding(index);
+                    if floor.dest_height != floor_height {
+                        floor.dest_height += 8.0;
+                    }
+                }
+                FloorKind::RaiseFloor | FloorKind::RaiseFloorCrush => {
+                    let ceiling = self.sectors[index].ceiling_height;
+                    floor.dest_height = self.lowest_ceiling_surrounding(index).min(ceiling);
+                    if kind == FloorKind::RaiseFloorCrush {
+                        floor.crush = true;
+                        floor.dest_height -= 8.0;
+                    }
+                }
+                FloorKind::RaiseFloorTurbo => {
+                    floor.speed = FLOOR_SPEED * 4.0;
+                    floor.dest_height = self.next_highest_floor(index, floor_height);
+                }
+                FloorKind::RaiseFloorToNearest => {
+                    floor.dest_height = self.next_highest_floor(index, floor_height);
+                }
+                FloorKind::RaiseFloor24 => floor.dest_height = floor_height + 24.0,
+                FloorKind::RaiseFloor512 => floor.dest_height = floor_height + 512.0,
+                FloorKind::RaiseFloor24AndChange => {
+                    floor.dest_height = floor_height + 24.0;
+                    if let Some(front) = front {
+                        let (texture, special) = {
+                            let front = &self.sectors[front];
+                            (front.floor_texture.clone(), front.special)
+                        };
+                        self.sectors[index].floor_texture = texture;
+                        self.sectors[index].special = special;
+                    }
+                }
+                FloorKind::RaiseToTexture => {
+                    floor.dest_height = floor_height + self.shortest_lower_texture(index);
+                }
+                FloorKind::LowerAndChange => {
+                    floor.direction = -1;
+                    floor.dest_height = self.lowest_floor_surrounding(index);
+                    floor.texture = self.sectors[index].floor_texture.clone();
+                    // Take on the look of the neighbour it ends up level with
+                    let neighbour = self
+                        .neighbours(index)
+                        .find(|&other| self.sectors[other].floor_height == floor.dest_height);
+                    if let Some(other) = neighbour {
+                        floor.texture = self.sectors[other].floor_texture.clone();
+                        floor.new_special = self.sectors[other].special;
+                    }
+                }
+                FloorKind::DonutRaise => {}
+            }
+            self.add_thinker(index, SectorThinker::Floor(floor));
+        }
+        started
+    }
+
+    // Returns false once the floor has arrived
+    pub fn tick_floor(&mut self, floor: &mut FloorMove) -> bool {
+        let result = self.move_plane(
+            floor.sector,
+            floor.speed,
+            floor.dest_height,
+            floor.crush,
+            Plane::Floor,
+            floor.direction,
+        );
+        if result != MoveResult::PastDest {
+            return true;
+        }
+        let changes = match floor.direction {
+            1 => floor.kind == FloorKind::DonutRaise,
+            _ => floor.kind == FloorKind::LowerAndChange,
+        };
+        if changes {
+            let sector = &mut self.sectors[floor.sector];
+            sector.special = floor.new_special;
+            sector.floor_texture = floor.texture.clone();
+        }
+        false
+    }
+
+    // Raise a flight of stairs: the tagged sector, then each neighbour
+    // through its back sides with the same floor texture, a step higher each
+    pub fn build_stairs(&mut self, tag: i16, kind: StairKind) -> bool {
+        let (speed, step) = match kind {
+            StairKind::Build8 => (FLOOR_SPEED / 4.0, 8.0),
+            StairKind::Turbo16 => (FLOOR_SPEED * 4.0, 16.0),
+        };
+        let stair = |sector: usize, height: f32| {
+            SectorThinker::Floor(FloorMove {
+                kind: FloorKind::LowerFloor,
+                crush: false,
+                sector,
+                direction: 1,
+                new_special: 0,
+                texture: String::new(),
+                dest_height: height,
+                speed,
+            })
+        };
+
+        let mut started = false;
+        let mut search = None;
+        while let Some(mut sector) = self.find_sector_from_tag(tag, search) {
+            search = Some(sector);
+            if self.sector_thinkers[sector].is_some() {
+                continue;
+            }
+            started = true;
+            let mut height = self.sectors[sector].floor_height + step;
+            self.add_thinker(sector, stair(sector, height));
+            let texture = self.sectors[sector].floor_texture.clone();
+
+            // The original carries on searching for tagged sectors from the
+            // top of the stairs
+            loop {
+                let mut next_step = None;
+                for &line in &self.sector_lines[sector] {
+                    let line = &self.linedefs[line];
+                    if line.flags & ML_TWOSIDED == 0
+                        || self.side_sector_index(line.front_sidedef) != Some(sector)
+                    {
+                        continue;
+                    }
+                    let Some(next) = self.side_sector_index(line.back_sidedef) else {
+                        continue;
+                    };
+                    if self.sectors[next].floor_texture != texture {
+                        continue;
+                    }
+                    height += step;
+                    if self.sector_thinkers[next].is_none() {
+                        next_step = Some(next);
+                        break;
+                    }
+                }
+                let Some(next) = next_step else {
+                    break;
+                };
+                sector = next;
+                search = Some(next);
+                self.add_thinker(sector, stair(sector, height));
+            }
+        }
+        started
+    }
+
+    // Lower the middle of a donut to the level outside it, while the ring
+    // around it rises to match
+    pub fn do_donut(&mut self, tag: i16) -> bool {
+        let mut started = false;
+        let mut search = None;
+        while let Some(inner) = self.find_sector_from_tag(tag, search) {
+            search = Some(inner);
+            if self.sector_thinkers[inner].is_some() {
+                continue;
+            }
+            started = true;
+            let Some(ring) = self.sector_lines[inner]
+                .first()
+                .and_then(|&line| self.next_sector(line, inner))
+            else {
+                continue;
+            };
+            let outside = self.sector_lines[ring].iter().find_map(|&line| {
+                let back = self.side_sector_index(self.linedefs[line].back_sidedef)?;
+                (back != inner).then_some(back)
+            });
+            let Some(outside) = outside else {
+                continue;
+            };
+
+            let (height, texture) = {
+                let outside = &self.sectors[outside];
+                (outside.floor_height, outside.floor_texture.clone())
+            };
+            let floor = |kind, sector, direction, texture| {
+                SectorThinker::Floor(FloorMove {
+                    kind,
+                    crush: false,
+                    sector,
+                    direction,
+                    new_special: 0,
+                    texture,
+                    dest_height: height,
+                    speed: FLOOR_SPEED / 2.0,
+                })
+            };
+            self.add_thinker(ring, floor(FloorKind::DonutRaise, ring, 1, texture));
+            let hole = floor(FloorKind::LowerFloor, inner, -1, String::new());
+            self.add_thinker(inner, hole);
+        }
+        started
+    }
+}
@@ -0,0 +1,205 @@
+use crate::render::{draw_patch, fill_block, SCALE};
+use crate::wad_reader::{Patch, WadData};
+
+// The STCFN lumps cover '!' (STCFN033) up to '_' (STCFN095), uppercase only
+const FONT_START: u8 = b'!';
+const FONT_END: u8 = b'_';
+const SPACE_WIDTH: i32 = 4;
+
+// Colour used by the built-in font, close to the red of the WAD font
+const BUILTIN_COLOR: u32 = 0xD00000;
+const BUILTIN_WIDTH: i32 = 5;
+const BUILTIN_HEIGHT: i32 = 7;
+
+// 5x7 bitmaps for '!'..'_', one byte per row with bit 4 as the leftmost pixel
+const BUILTIN_GLYPHS: [[u8; 7]; 63] = [
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+];
+
+enum Glyphs {
+    // STCFN patches from the WAD plus the palette to draw them with
+    Wad {
+        patches: Vec<Option<Patch>>,
+        palette: Vec<u32>,
+    },
+    Builtin,
+}
+
+pub struct Font {
+    glyphs: Glyphs,
+    pub height: i32,
+}
+
+impl Font {
+    // Load the STCFN font from the WAD, falling back to the built-in one
+    pub fn load(wad: &WadData) -> Font {
+        let palette = match wad.read_palette(0) {
+            Ok(palette) => palette,
+            Err(e) => {
+                println!("Failed to read palette, using built-in font: {}", e);
+                return Font::builtin();
+            }
+        };
+
+        let patches: Vec<Option<Patch>> = (FONT_START..=FONT_END)
+            .map(|c| wad.read_patch(&format!("STCFN{:03}", c)).ok())
+            .collect();
+
+        if patches.iter().all(|p| p.is_none()) {
+            println!("STCFN lumps not found, using built-in font");
+            return Font::builtin();
+        }
+
+        let height = patches
+            .iter()
+            .flatten()
+            .map(|p| p.height as i32)
+            .max()
+            .unwrap_or(BUILTIN_HEIGHT);
+
+        Font {
+            glyphs: Glyphs::Wad { patches, palette },
+            height,
+        }
+    }
+
+    pub fn builtin() -> Font {
+        Font {
+            glyphs: Glyphs::Builtin,
+            height: BUILTIN_HEIGHT,
+        }
+    }
+
+    // Index into the glyph tables, None for characters the font doesn't have
+    fn glyph_index(c: char) -> Option<usize> {
+        let c = c.to_ascii_uppercase();
+        if c.is_ascii() && (FONT_START..=FONT_END).contains(&(c as u8)) {
+            Some((c as u8 - FONT_START) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn char_width(&self, c: char) -> i32 {
+        match (&self.glyphs, Font::glyph_index(c)) {
+            (Glyphs::Wad { patches, .. }, Some(i)) => match &patches[i] {
+                Some(patch) => patch.width as i32,
+                None => SPACE_WIDTH,
+            },
+            (Glyphs::Builtin, Some(_)) => BUILTIN_WIDTH + 1,
+            (_, None) => SPACE_WIDTH,
+        }
+    }
+
+    // Width in 320x200 screen units of the longest line of text
+    pub fn text_width(&self, text: &str) -> i32 {
+        text.lines()
+            .map(|line| line.chars().map(|c| self.char_width(c)).sum())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Draw text with its top left corner at (x, y) in 320x200 screen units
+    pub fn draw_text(&self, buffer: &mut [u32], x: i32, y: i32, text: &str) {
+        let mut cx = x;
+        let mut cy = y;
+
+        for c in text.chars() {
+            if c == '\n' {
+                cx = x;
+                cy += self.height + 1;
+                continue;
+            }
+
+            match (&self.glyphs, Font::glyph_index(c)) {
+                (Glyphs::Wad { patches, palette }, Some(i)) => {
+                    if let Some(patch) = &patches[i] {
+                        // Font patches are placed by their own offsets, like
+                        // any other patch
+                        draw_patch(buffer, patch, palette, cx, cy);
+                    }
+                }
+                (Glyphs::Builtin, Some(i)) => {
+                    for (row, bits) in BUILTIN_GLYPHS[i].iter().enumerate() {
+                        for col in 0..BUILTIN_WIDTH {
+                            if bits & (0x10 >> col) != 0 {
+                                fill_block(
+                                    buffer,
+                                    (cx + col) * SCALE as i32,
+                                    (cy + row as i32) * SCALE as i32,
+                                    SCALE,
+                                    BUILTIN_COLOR,
+                                );
+                            }
+                        }
+                    }
+                }
+                (_, None) => {}
+            }
+
+            cx += self.char_width(c);
+        }
+    }
+}
@@ -1,14 +1,177 @@
+use crate::cheats::{Cheat, CheatDetector};
+use crate::collision::{check_position, xy_move, Mover, PLAYER_HEIGHT, PLAYER_RADIUS};
+use crate::combat::{Armor, PlayerHit};
+use crate::demo::Demo;
+use crate::enemy::point_to_angle;
+use crate::finale::Finale;
+use crate::info::{WeaponType, MF_SHOOTABLE, MF_SOLID};
+use crate::intermission::Intermission;
+use crate::level::{next_map, parse_map_name, Level, LevelOptions, MapStart, NIGHTMARE};
+use crate::menu::{Menu, MenuAction};
+use crate::mobj::{Body, Target};
+use crate::pickup::{Power, MAX_AMMO};
+use crate::savegame::{self, save_path, SaveHeader};
+use crate::strings::{
+    GGSAVED, STSTR_BEHOLD, STSTR_BEHOLDX, STSTR_CLEV, STSTR_DQDOFF, STSTR_DQDON, STSTR_KFAADDED,
+    STSTR_NCOFF, STSTR_NCON,
+};
+use crate::teleport::TELEPORT_FREEZE;
+use crate::ticcmd::{TicCmd, BT_ATTACK, BT_CHANGE, BT_USE, BT_WEAPONSHIFT};
+use crate::wad_reader::WadData;
+use crate::weapon::{Psprite, WeaponSet};
 use minifb::Key;
-use std::time::Instant;
+use std::fs;
+use std::time::{Duration, Instant};
+
+// What the game is currently showing
+pub enum Scene {
+    // The title screen and the other full screen pages of the attract loop
+    DemoScreen { page: String, tics_left: u32 },
+    Level,
+    Intermission(Intermission),
+    // Story text between chapters and the end of episode screens
+    Finale(Finale),
+}
+
+// The attract loop runs until a game is started: title pages and demos
+#[derive(Clone, Copy)]
+enum AttractStep {
+    Page(&'static str, u32),
+    Demo(&'static str),
+}
+
+fn attract_sequence(commercial: bool, retail: bool) -> Vec<AttractStep> {
+    let title_tics = if commercial { 35 * 11 } else { 170 };
+    let third_page = if commercial {
+        AttractStep::Page("TITLEPIC", title_tics)
+    } else if retail {
+        AttractStep::Page("CREDIT", 200)
+    } else {
+        AttractStep::Page("HELP2", 200)
+    };
+
+    let mut sequence = vec![
+        AttractStep::Page("TITLEPIC", title_tics),
+        AttractStep::Demo("DEMO1"),
+        AttractStep::Page("CREDIT", 200),
+        AttractStep::Demo("DEMO2"),
+        third_page,
+        AttractStep::Demo("DEMO3"),
+    ];
+    if retail {
+        sequence.push(AttractStep::Demo("DEMO4"));
+    }
+    sequence
+}
 
 pub struct Game {
-    last_update: Instant,
     pub render_map: bool,
-    last_toggle_time: Instant,
+    pub show_stats: bool,
+    pub menu: Menu,
+    pub options: Options,
+    pub map: String,
+    pub episode: usize,
+    pub skill: usize,
+    // Doom II style WAD with MAPxx maps instead of ExMy episodes
+    commercial: bool,
+    // The Ultimate Doom, with a fourth episode and demo
+    retail: bool,
+    // Just the first episode, without the plasma rifle and BFG
+    shareware: bool,
+    message: Option<(String, Instant)>,
+    pub quit_requested: bool,
+    // The last level was left through a secret exit
+    secret_exit: bool,
+    // Which of the four players we are, and whether deathmatch spots are used
+    pub player_number: usize,
+    pub deathmatch: bool,
+    // The -nomonsters, -respawn and -fast options
+    pub no_monsters: bool,
+    pub respawn: bool,
+    pub fast: bool,
+    // Set on state changes, the renderer melts the old screen into the new one
+    pub wipe_pending: bool,
+    pub scene: Scene,
+    pub level: Option<Level>,
+    wad: WadData,
+    // The simulation runs in fixed 35Hz tics counted from this instant, no
+    // matter how often the input thread gets to run
+    clock_start: Instant,
+    gametic: u64,
+    // Mouse movement piled up since the last tic's command was built
+    mouse_dx: f32,
+    // Use only triggers again after the button has been let go
+    use_down: bool,
+    // Tics a turn key has been held, for the slow start to keyboard turns
+    turn_held: u32,
+    // Position in the attract loop, None once a game has been started
+    attract_step: Option<usize>,
+    // The demo being played back and the next tic to replay. A single demo
+    // from the command line quits when it's over instead of going on with
+    // the attract loop.
+    demo: Option<Demo>,
+    demo_tic: usize,
+    single_demo: bool,
+    // The demo being recorded with -record, and the file it goes to
+    demo_record: Option<(String, Demo)>,
+    // Cheat codes being typed, and how much iddt shows on the automap: 1
+    // for every line, 2 for things as well
+    cheats: CheatDetector,
+    pub automap_cheat: usize,
 }
 
-const PLAYER_SPEED: f32 = 100.0; // Pixels per second
-const PLAYER_ROTATION_SPEED: f32 = 180.0; // Degrees per second
+const MOUSE_TURN_SPEED: f32 = 0.25; // Degrees per pixel at the default sensitivity
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(4);
+const TICRATE: f64 = 35.0;
+// Keyboard movement per tic, walking and running like vanilla
+const FORWARD_MOVE: [i8; 2] = [0x19, 0x32];
+const SIDE_MOVE: [i8; 2] = [0x18, 0x28];
+// Turning is slow for the first few tics a turn key is held
+const ANGLE_TURN: [i16; 2] = [640, 1280];
+const SLOW_TURN: i16 = 320;
+const SLOW_TURN_TICS: u32 = 6;
+const MAX_PLAYER_MOVE: i8 = FORWARD_MOVE[1];
+
+// Commands push the player's momentum, which friction slows every tic.
+// Units are map units per tic.
+const THRUST_SCALE: f32 = 2048.0 / 65536.0;
+const FRICTION: f32 = 0xE800 as f32 / 65536.0;
+const STOP_SPEED: f32 = 0x1000 as f32 / 65536.0;
+const MAX_MOVE: f32 = 30.0;
+
+// Heights in map units, vertical speeds in map units per tic
+const VIEW_HEIGHT: f32 = 41.0;
+const GRAVITY: f32 = 1.0;
+const MAX_BOB: f32 = 16.0;
+// The view bobs up and down once every this many tics while moving
+const BOB_PERIOD: u32 = 20;
+// The view never comes closer than this to the ceiling
+const CEILING_GAP: f32 = 4.0;
+// The dead sink to this eye height, turning to face their killer this many
+// degrees a tic
+const DEAD_VIEW_HEIGHT: f32 = 6.0;
+const DEATH_TURN: f32 = 5.0;
+
+// Settings changed through the options menu
+pub struct Options {
+    pub mouse_sensitivity: usize,
+    // 3..=11 like the original, 10 and up draw the full view
+    pub screen_size: usize,
+    pub show_messages: bool,
+    // Draw the player between the last two tics instead of snapping per tic
+    pub interpolate: bool,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Options {
+            mouse_sensitivity: 5,
+            screen_size: 10,
+            show_messages: true,
+            interpolate: true,
+        }
+    }
+}
 
 pub struct BoundedFloat {
     value: f32,
@@ -25,6 +188,10 @@ impl BoundedFloat {
         self.value
     }
 
+    pub fn set(&mut self, value: f32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
     pub fn add(&mut self, amount: f32) {
         self.value = (self.value + amount).clamp(self.min, self.max);
     }
@@ -40,17 +207,193 @@ pub struct Player {
     pub x: BoundedFloat,
     pub y: BoundedFloat,
     pub angle: f32,
+    // Momentum in map units per tic
+    pub mom_x: f32,
+    pub mom_y: f32,
+    // Feet height, and the eye height the view is drawn from
+    pub z: f32,
+    pub mom_z: f32,
+    pub view_z: f32,
+    // Eye height above the feet, squashed after landings and step ups
+    pub view_height: f32,
+    pub delta_view_height: f32,
+    pub bob: f32,
+    // The floor and ceiling the player is currently between
+    pub floor_z: f32,
+    pub ceiling_z: f32,
+    // Position and angle at the start of the current tic, for interpolation
+    last: (f32, f32, f32),
+    pub health: i32,
+    pub armor: Armor,
+    // Keys held, indexed by Card
+    pub cards: [bool; 6],
+    // Tics left on each powerup, indexed by Power
+    pub powers: [i32; 6],
+    // Red and gold screen flashes from getting hurt and picking things up
+    pub damage_count: i32,
+    pub bonus_count: i32,
+
+    // The weapons carried, the one in hand and the one to switch to once
+    // it's put away
+    pub weapon_owned: [bool; 9],
+    pub ready_weapon: WeaponType,
+    pub pending_weapon: Option<WeaponType>,
+    // Indexed by AmmoType, with twice the room once a backpack is found
+    pub ammo: [i32; 4],
+    pub max_ammo: [i32; 4],
+    pub backpack: bool,
+    // The weapon and its muzzle flash, drawn over the view
+    pub psprites: [Psprite; 2],
+    // Steps of light a weapon's muzzle flash adds to the view
+    pub extra_light: i32,
+    // Shots fired in a row with the trigger held down
+    pub refire: i32,
+    // Set while the trigger is held, for weapons that fire once per press
+    pub attack_down: bool,
+    // This tic's command, the weapon code reads its buttons
+    pub cmd: TicCmd,
+    // Set by the chainsaw, which drags the player on towards its victim
+    pub just_attacked: bool,
+    // Who last hurt the player, for the dead to turn and look at
+    pub attacker: Option<Target>,
+    // Tics left frozen in place after teleporting
+    pub reaction_time: i32,
+    // The iddqd and idclip cheats
+    pub god_mode: bool,
+    pub no_clip: bool,
 }
 
 impl Player {
     pub fn new() -> Self {
         Player {
-            x: BoundedFloat::new(0., -10000., 10000.),
-            y: BoundedFloat::new(0., -10000., 10000.),
+            // Map coordinates are 16 bit
+            x: BoundedFloat::new(0., -32768., 32767.),
+            y: BoundedFloat::new(0., -32768., 32767.),
             angle: 0.0,
+            mom_x: 0.0,
+            mom_y: 0.0,
+            z: 0.0,
+            mom_z: 0.0,
+            view_z: VIEW_HEIGHT,
+            view_height: VIEW_HEIGHT,
+            delta_view_height: 0.0,
+            bob: 0.0,
+            floor_z: 0.0,
+            ceiling_z: f32::MAX,
+            last: (0.0, 0.0, 0.0),
+            health: 100,
+            armor: Armor::default(),
+            cards: [false; 6],
+            powers: [0; 6],
+            damage_count: 0,
+            bonus_count: 0,
+            // Everyone starts with a fist and a pistol with 50 bullets
+            weapon_owned: [true, true, false, false, false, false, false, false, false],
+            ready_weapon: WeaponType::Pistol,
+            pending_weapon: None,
+            ammo: [50, 0, 0, 0],
+            max_ammo: MAX_AMMO,
+            backpack: false,
+            psprites: [Psprite::default(); 2],
+            extra_light: 0,
+            refire: 0,
+            attack_down: false,
+            cmd: TicCmd::default(),
+            just_attacked: false,
+            attacker: None,
+            reaction_time: 0,
+            god_mode: false,
+            no_clip: false,
         }
     }
 
+    // Appear at a map start, standing still
+    pub fn spawn(&mut self, start: &MapStart, level: Option<&Level>) {
+        self.x.set(start.x);
+        self.y.set(start.y);
+        self.angle = start.angle;
+        self.mom_x = 0.0;
+        self.mom_y = 0.0;
+        self.attacker = None;
+        self.extra_light = 0;
+        self.place_on_floor(level);
+        self.start_tic();
+    }
+
+    // A teleporter sent the player to `dest`: land on the floor there facing
+    // the way it says, unable to move for a moment
+    pub fn teleport(&mut self, dest: &MapStart, level: &Level) {
+        self.x.set(dest.x);
+        self.y.set(dest.y);
+        self.angle = dest.angle;
+        self.mom_x = 0.0;
+        self.mom_y = 0.0;
+        self.mom_z = 0.0;
+        (self.floor_z, self.ceiling_z) = level.heights_at(dest.x, dest.y, PLAYER_RADIUS);
+        self.z = self.floor_z;
+        self.view_z = self.z + self.view_height;
+        self.reaction_time = TELEPORT_FREEZE;
+        // Don't draw the player sliding across the map
+        self.start_tic();
+    }
+
+    // Put the player's feet on the floor wherever they are standing
+    pub fn place_on_floor(&mut self, level: Option<&Level>) {
+        let (x, y) = (self.x.get_value(), self.y.get_value());
+        if let Some(opening) =
+            level.and_then(|level| check_position(level, x, y, PLAYER_RADIUS, Target::Player).ok())
+        {
+            self.floor_z = opening.floor_z;
+            self.ceiling_z = opening.ceiling_z;
+        }
+        self.z = self.floor_z;
+        self.mom_z = 0.0;
+        self.view_height = VIEW_HEIGHT;
+        self.delta_view_height = 0.0;
+        self.view_z = self.z + VIEW_HEIGHT;
+    }
+
+    fn on_ground(&self) -> bool {
+        self.z <= self.floor_z
+    }
+
+    // Where the player is for monsters looking for them or bumping into them
+    pub fn body(&self) -> Body {
+        let flags = if self.health > 0 {
+            MF_SOLID | MF_SHOOTABLE | self.power_flags()
+        } else {
+            0
+        };
+        Body {
+            x: self.x.get_value(),
+            y: self.y.get_value(),
+            z: self.z,
+            radius: PLAYER_RADIUS,
+            height: PLAYER_HEIGHT,
+            angle: self.angle,
+            flags,
+            health: self.health,
+        }
+    }
+
+    // Remember where the player was before this tic moves them
+    pub fn start_tic(&mut self) {
+        self.last = (self.x.get_value(), self.y.get_value(), self.angle);
+    }
+
+    // Position and angle to draw, `fraction` of the way through the tic
+    pub fn view(&self, fraction: f32) -> (f32, f32, f32) {
+        let (last_x, last_y, last_angle) = self.last;
+        let lerp = |from: f32, to: f32| from + (to - from) * fraction;
+        // Turn the short way round when the angle wraps
+        let turn = (self.angle - last_angle + 540.0).rem_euclid(360.0) - 180.0;
+        (
+            lerp(last_x, self.x.get_value()),
+            lerp(last_y, self.y.get_value()),
+            last_angle + turn * fraction,
+        )
+    }
+
     pub fn move_x(&mut self, delta: f32) {
         self.x.add(delta);
     }
@@ -62,68 +405,1185 @@ impl Player {
     pub fn rotate(&mut self, delta_angle: f32) {
         self.angle = (self.angle + delta_angle) % 360.0;
     }
+
+    // Push the player along `angle` (in degrees, like Player::angle)
+    pub fn thrust(&mut self, angle: f32, amount: f32) {
+        let angle = angle.to_radians();
+        self.mom_x += amount * angle.sin();
+        self.mom_y += amount * angle.cos();
+    }
+
+    // Apply one tic of a command: turn, thrust relative to the direction the
+    // player is facing, then slide along with the momentum and fall
+    pub fn apply_cmd(&mut self, cmd: &TicCmd, level: Option<&Level>) {
+        self.cmd = *cmd;
+
+        // Just teleported: momentum still carries the player, but they can't
+        // turn or push
+        if self.reaction_time > 0 {
+            self.reaction_time -= 1;
+        } else {
+            self.move_player(cmd);
+        }
+
+        self.calc_height(level.map_or(0, |level| level.stats.time));
+        self.xy_movement(cmd, level);
+        self.z_movement();
+    }
+
+    fn move_player(&mut self, cmd: &TicCmd) {
+        // Doom's turns are in 1/65536ths of a circle and positive turns left
+        self.rotate(-(cmd.angle_turn as f32) * 360.0 / 65536.0);
+
+        // No air control: only thrust with both feet on the ground
+        if self.on_ground() {
+            if cmd.forward_move != 0 {
+                self.thrust(self.angle, cmd.forward_move as f32 * THRUST_SCALE);
+            }
+            if cmd.side_move != 0 {
+                self.thrust(self.angle + 90.0, cmd.side_move as f32 * THRUST_SCALE);
+            }
+        }
+    }
+
+    // Work out the eye height, with view bobbing from the player's speed
+    fn calc_height(&mut self, level_time: u32) {
+        self.bob = ((self.mom_x * self.mom_x + self.mom_y * self.mom_y) / 4.0).min(MAX_BOB);
+
+        if !self.on_ground() {
+            self.view_z = (self.z + VIEW_HEIGHT).min(self.ceiling_z - CEILING_GAP);
+            return;
+        }
+
+        let phase = (level_time % BOB_PERIOD) as f32 / BOB_PERIOD as f32;
+        let bob = self.bob / 2.0 * (phase * std::f32::consts::TAU).sin();
+
+        // Ease the eye height back up after a landing or a step up. The
+        // dead's view just sinks.
+        if self.health > 0 {
+            self.view_height += self.delta_view_height;
+            if self.view_height > VIEW_HEIGHT {
+                self.view_height = VIEW_HEIGHT;
+                self.delta_view_height = 0.0;
+            }
+            if self.view_height < VIEW_HEIGHT / 2.0 {
+                self.view_height = VIEW_HEIGHT / 2.0;
+                if self.delta_view_height <= 0.0 {
+                    self.delta_view_height = 1.0 / 65536.0;
+                }
+            }
+            if self.delta_view_height != 0.0 {
+                self.delta_view_height += 0.25;
+            }
+        }
+
+        self.view_z = (self.z + self.view_height + bob).min(self.ceiling_z - CEILING_GAP);
+    }
+
+    // Suffer what was done to the player during the tic: knocked about, hurt
+    // and maybe killed
+    pub fn take_hit(&mut self, hit: &PlayerHit, level: Option<&mut Level>, set: WeaponSet) {
+        self.mom_x += hit.momentum.0;
+        self.mom_y += hit.momentum.1;
+        self.mom_z += hit.momentum.2;
+        if hit.damage <= 0 || self.health <= 0 {
+            return;
+        }
+        let damage = hit.damage - self.armor.absorb(hit.damage);
+        self.health = (self.health - damage).max(0);
+        self.attacker = hit.source;
+        self.damage_count = (self.damage_count + damage).min(100);
+        if self.health == 0 {
+            self.drop_weapon(level, set);
+        }
+    }
+
+    // Dead: sink to the floor and turn to face the killer
+    fn death_think(&mut self, level: Option<&Level>) {
+        self.view_height = (self.view_height - 1.0).max(DEAD_VIEW_HEIGHT);
+        self.delta_view_height = 0.0;
+
+        let killer = self
+            .attacker
+            .filter(|&attacker| attacker != Target::Player)
+            .and_then(|attacker| level?.body(attacker));
+        // The red flash fades once the killer is in view
+        let Some(killer) = killer else {
+            self.damage_count = (self.damage_count - 1).max(0);
+            return;
+        };
+        let position = (self.x.get_value(), self.y.get_value());
+        let goal = point_to_angle(position, (killer.x, killer.y));
+        let delta = (goal - self.angle + 540.0).rem_euclid(360.0) - 180.0;
+        if delta.abs() < DEATH_TURN {
+            self.angle = goal;
+            self.damage_count = (self.damage_count - 1).max(0);
+        } else {
+            self.angle = (self.angle + DEATH_TURN * delta.signum()).rem_euclid(360.0);
+        }
+    }
+
+    // The floor or ceiling moved: ride it if standing on it, or get pushed
+    // down under it
+    pub fn height_clip(&mut self, level: &Level) {
+        let (floor_z, ceiling_z) =
+            level.heights_at(self.x.get_value(), self.y.get_value(), PLAYER_RADIUS);
+        let on_floor = self.z == self.floor_z;
+        self.floor_z = floor_z;
+        self.ceiling_z = ceiling_z;
+        if on_floor {
+            self.z = floor_z;
+        } else if self.z + PLAYER_HEIGHT > ceiling_z {
+            self.z = ceiling_z - PLAYER_HEIGHT;
+        }
+    }
+
+    // Step up onto higher floors, fall under gravity and land
+    fn z_movement(&mut self) {
+        if self.z < self.floor_z {
+            // Stepped up: the view catches up over a few tics
+            self.view_height -= self.floor_z - self.z;
+            self.delta_view_height = (VIEW_HEIGHT - self.view_height) / 8.0;
+        }
+
+        self.z += self.mom_z;
+
+        if self.z <= self.floor_z {
+            // A hard landing squats the view
+            if self.mom_z < -GRAVITY * 8.0 {
+                self.delta_view_height = self.mom_z / 8.0;
+            }
+            self.mom_z = self.mom_z.max(0.0);
+            self.z = self.floor_z;
+        } else if self.mom_z == 0.0 {
+            self.mom_z = -GRAVITY * 2.0;
+        } else {
+            self.mom_z -= GRAVITY;
+        }
+
+        if self.z + PLAYER_HEIGHT > self.ceiling_z {
+            self.mom_z = self.mom_z.min(0.0);
+            self.z = self.ceiling_z - PLAYER_HEIGHT;
+        }
+    }
+
+    fn xy_movement(&mut self, cmd: &TicCmd, level: Option<&Level>) {
+        self.mom_x = self.mom_x.clamp(-MAX_MOVE, MAX_MOVE);
+        self.mom_y = self.mom_y.clamp(-MAX_MOVE, MAX_MOVE);
+        match level {
+            // idclip goes through everything, onto whatever floor is there
+            Some(level) if self.no_clip => {
+                self.move_x(self.mom_x);
+                self.move_y(self.mom_y);
+                if let Some(sector) = level.sector_at(self.x.get_value(), self.y.get_value()) {
+                    self.floor_z = sector.floor_height;
+                    self.ceiling_z = sector.ceiling_height;
+                }
+            }
+            // Walls and solid things stop the player, who slides along them
+            Some(level) => {
+                let mover = Mover {
+                    x: self.x.get_value(),
+                    y: self.y.get_value(),
+                    z: self.z,
+                    radius: PLAYER_RADIUS,
+                    height: PLAYER_HEIGHT,
+                    who: Target::Player,
+                };
+                let ((x, y), (mom_x, mom_y), opening) =
+                    xy_move(level, &mover, (self.mom_x, self.mom_y));
+                self.x.set(x);
+                self.y.set(y);
+                self.mom_x = mom_x;
+                self.mom_y = mom_y;
+                if let Some(opening) = opening {
+                    self.floor_z = opening.floor_z;
+                    self.ceiling_z = opening.ceiling_z;
+                }
+            }
+            None => {
+                self.move_x(self.mom_x);
+                self.move_y(self.mom_y);
+            }
+        }
+
+        // Friction only works on the ground
+        if !self.on_ground() {
+            return;
+        }
+
+        // Come to a stop once the player has let go and slowed right down
+        let stopping = cmd.forward_move == 0 && cmd.side_move == 0;
+        if stopping && self.mom_x.abs() < STOP_SPEED && self.mom_y.abs() < STOP_SPEED {
+            self.mom_x = 0.0;
+            self.mom_y = 0.0;
+        } else {
+            self.mom_x *= FRICTION;
+            self.mom_y *= FRICTION;
+        }
+    }
+}
+
+// Demo files are named like the original's, with .lmp added
+fn lmp_path(name: &str) -> String {
+    if name.ends_with(".lmp") {
+        name.to_string()
+    } else {
+        format!("{}.lmp", name)
+    }
+}
+
+// Let monsters and attacks know where the player is and what they're holding
+fn sync_player(level: &mut Level, player: &Player) {
+    level.player_body = Some(player.body());
+    level.player_weapon = player.ready_weapon;
+    level.player_armor = player.armor;
+    level.player_invulnerable = player.has_power(Power::Invulnerability);
+    level.player_god = player.god_mode;
+    level.player_cards = player.cards;
 }
 
 impl Game {
-    pub fn new() -> Self {
-        Game {
-            last_update: Instant::now(),
+    pub fn new(wad: &WadData) -> Self {
+        let commercial = wad.has_lump("MAP01");
+        let map = if commercial { "MAP01" } else { "E1M1" };
+
+        let mut game = Game {
             render_map: false,
-            last_toggle_time: Instant::now(),
+            show_stats: false,
+            menu: Menu::new(wad),
+            options: Options::new(),
+            map: map.to_string(),
+            episode: 1,
+            skill: 2,
+            commercial,
+            retail: wad.has_lump("E4M1"),
+            shareware: !commercial && !wad.has_lump("E2M1"),
+            message: None,
+            quit_requested: false,
+            secret_exit: false,
+            player_number: 0,
+            deathmatch: false,
+            no_monsters: false,
+            respawn: false,
+            fast: false,
+            wipe_pending: false,
+            scene: Scene::Level,
+            level: None,
+            wad: wad.clone(),
+            clock_start: Instant::now(),
+            gametic: 0,
+            mouse_dx: 0.0,
+            use_down: false,
+            turn_held: 0,
+            attract_step: None,
+            demo: None,
+            demo_tic: 0,
+            single_demo: false,
+            demo_record: None,
+            cheats: CheatDetector::new(),
+            automap_cheat: 0,
+        };
+        game.advance_demo(&mut Player::new());
+        game
+    }
+
+    // Move on to the next title page or demo of the attract loop
+    fn advance_demo(&mut self, player: &mut Player) {
+        let sequence = attract_sequence(self.commercial, self.retail);
+        self.demo = None;
+        self.wipe_pending = true;
+
+        loop {
+            let step = self
+                .attract_step
+                .map_or(0, |step| (step + 1) % sequence.len());
+            self.attract_step = Some(step);
+
+            match sequence[step] {
+                AttractStep::Page(page, tics) => {
+                    self.scene = Scene::DemoScreen {
+                        page: page.to_string(),
+                        tics_left: tics,
+                    };
+                    return;
+                }
+                // Skip demos that are missing or can't be read
+                AttractStep::Demo(name) => {
+                    let demo = self
+                        .wad
+                        .read_lump(name)
+                        .and_then(|data| Demo::from_bytes(&data));
+                    match demo {
+                        Ok(demo) => {
+                            if self.play_demo(demo, player) {
+                                return;
+                            }
+                        }
+                        Err(e) => println!("Failed to play {}: {}", name, e),
+                    }
+                }
+            }
         }
     }
 
-    pub fn handle_input(&mut self, keys: &[Key], player: &mut Player) {
-        let now = Instant::now();
-        let delta_time = now.duration_since(self.last_update).as_secs_f32();
-        self.last_update = now;
+    fn play_demo(&mut self, demo: Demo, player: &mut Player) -> bool {
+        self.skill = demo.skill;
+        self.episode = demo.episode;
+        *player = Player::new();
+        // Set before loading, the level is played with the demo's options
+        let map = demo.map_name(self.commercial);
+        self.demo = Some(demo);
+        self.demo_tic = 0;
+        self.load_level(&map, player);
+        if self.level.is_none() {
+            self.demo = None;
+        }
+        self.level.is_some()
+    }
+
+    // -playdemo and -timedemo: play a .lmp file, or a demo lump if there's
+    // no such file, and quit at the end
+    pub fn play_demo_file(&mut self, name: &str, player: &mut Player) -> bool {
+        let path = lmp_path(name);
+        let demo = fs::read(&path)
+            .or_else(|_| self.wad.read_lump(&name.to_uppercase()))
+            .and_then(|data| Demo::from_bytes(&data));
+        let demo = match demo {
+            Ok(demo) => demo,
+            Err(e) => {
+                println!("Failed to play {}: {}", path, e);
+                return false;
+            }
+        };
+        self.attract_step = None;
+        self.single_demo = true;
+        self.play_demo(demo, player)
+    }
 
-        if keys.contains(&Key::W) {
-            player.move_y(PLAYER_SPEED * delta_time);
-            println!(
-                "W is pressed. Player y increased to: {}",
-                player.y.get_value()
-            );
+    // The demo being played ran out of commands
+    fn end_demo(&mut self, player: &mut Player) {
+        self.demo = None;
+        if self.single_demo {
+            self.quit_requested = true;
+        } else {
+            self.advance_demo(player);
         }
-        if keys.contains(&Key::A) {
-            player.move_x(-PLAYER_SPEED * delta_time);
-            println!(
-                "A is pressed. Player x decreased to: {}",
-                player.x.get_value()
-            );
-        }
-        if keys.contains(&Key::S) {
-            player.move_y(-PLAYER_SPEED * delta_time);
-            println!(
-                "S is pressed. Player y decreased to: {}",
-                player.y.get_value()
-            );
+    }
+
+    // Play a demo through as fast as possible without drawing anything, like
+    // the original's -timedemo, and say how it ended. The same demo always
+    // ends the same way, so the last line can be compared between builds.
+    pub fn time_demo(&mut self, name: &str) {
+        let mut player = Player::new();
+        if !self.play_demo_file(name, &mut player) {
+            return;
+        }
+        let start = Instant::now();
+        let tics = self.run_demo(&mut player);
+        println!(
+            "timed {} gametics in {:.2} seconds",
+            tics,
+            start.elapsed().as_secs_f64()
+        );
+
+        let Some(level) = &self.level else {
+            return;
+        };
+        let stats = &level.stats;
+        println!(
+            "{} at tic {}: player at ({:.3}, {:.3}) facing {:.3}, health {}, armor {}, \
+             kills {}/{}, items {}/{}, secrets {}/{}",
+            self.map,
+            stats.time,
+            player.x.get_value(),
+            player.y.get_value(),
+            player.angle,
+            player.health,
+            player.armor.points,
+            stats.kills,
+            stats.total_kills,
+            stats.items,
+            stats.total_items,
+            stats.secrets,
+            stats.total_secrets
+        );
+    }
+
+    // Tick the demo started by play_demo_file through to its end, returning
+    // how many gametics it ran for
+    fn run_demo(&mut self, player: &mut Player) -> u32 {
+        let mut tics = 0;
+        loop {
+            self.tick(TicCmd::default(), player);
+            // The last tic only found the demo over
+            if self.quit_requested {
+                return tics;
+            }
+            tics += 1;
+        }
+    }
+
+    // -record: keep every tic of the game just started, for end_recording to
+    // write out
+    pub fn record_demo(&mut self, name: &str) {
+        let Some((_, map)) = parse_map_name(&self.map) else {
+            return;
+        };
+        let options = self.level_options();
+        let demo = Demo {
+            skill: self.skill,
+            episode: self.episode,
+            map,
+            deathmatch: options.deathmatch,
+            respawn: options.respawn,
+            fast: options.fast,
+            no_monsters: options.no_monsters,
+            player: self.player_number,
+            cmds: Vec::new(),
+        };
+        self.demo_record = Some((lmp_path(name), demo));
+    }
+
+    pub fn end_recording(&mut self) {
+        let Some((path, demo)) = self.demo_record.take() else {
+            return;
+        };
+        match fs::write(&path, demo.to_bytes()) {
+            Ok(()) => println!("Demo {} recorded", path),
+            Err(e) => println!("Failed to write {}: {}", path, e),
+        }
+    }
+
+    // The next recorded command of the demo being played, None when it's over
+    fn next_demo_cmd(&mut self) -> Option<TicCmd> {
+        let cmd = self.demo.as_ref()?.cmds.get(self.demo_tic).copied();
+        self.demo_tic += 1;
+        cmd
+    }
+
+    // How levels are played: demos bring their own options, anything else
+    // uses the command line's
+    fn level_options(&self) -> LevelOptions {
+        let (deathmatch, no_monsters, respawn, fast) = match &self.demo {
+            Some(demo) => (demo.deathmatch, demo.no_monsters, demo.respawn, demo.fast),
+            None => (self.deathmatch, self.no_monsters, self.respawn, self.fast),
+        };
+        LevelOptions {
+            skill: self.skill,
+            deathmatch,
+            no_monsters,
+            respawn,
+            fast,
+        }
+    }
+
+    pub fn load_level(&mut self, map: &str, player: &mut Player) {
+        let options = self.level_options();
+        self.level = match Level::load(&self.wad, map, options) {
+            Ok(level) => Some(level),
+            Err(e) => {
+                println!("Failed to load {}: {}", map, e);
+                None
+            }
+        };
+        self.map = map.to_string();
+        self.scene = Scene::Level;
+        // The living carry their health and inventory on to the next map,
+        // the dead start over
+        if player.health <= 0 {
+            *player = Player::new();
+        }
+
+        // Demos start where the player who recorded them did
+        let number = self
+            .demo
+            .as_ref()
+            .map_or(self.player_number, |demo| demo.player);
+        let start = self
+            .level
+            .as_mut()
+            .and_then(|level| level.player_start(number, options.deathmatch));
+        match start {
+            Some(start) => player.spawn(&start, self.level.as_ref()),
+            None => {
+                println!("{} has no start for player {}", map, number + 1);
+                player.place_on_floor(self.level.as_ref());
+            }
+        }
+        let set = self.weapon_set();
+        player.setup_psprites(self.level.as_mut(), set);
+        if let Some(level) = self.level.as_mut() {
+            sync_player(level, player);
+        }
+    }
+
+    fn weapon_set(&self) -> WeaponSet {
+        WeaponSet {
+            commercial: self.commercial,
+            shareware: self.shareware,
+        }
+    }
+
+    // The player hit an exit: tally up the level on the intermission screen.
+    // Doom II's secret exits go nowhere without the secret levels.
+    pub fn exit_level(&mut self, secret: bool) {
+        self.secret_exit = secret && (!self.commercial || self.wad.has_lump("MAP31"));
+        if let Some(level) = &self.level {
+            let next = next_map(&self.map, self.secret_exit);
+            let intermission = Intermission::new(&self.map, next, &level.stats);
+            self.scene = Scene::Intermission(intermission);
+            self.wipe_pending = true;
+        }
+    }
+
+    // The intermission is over: tell the next part of the story if there is
+    // one, otherwise move on to the next map
+    fn world_done(&mut self, player: &mut Player) {
+        if let Scene::Intermission(_) = self.scene {
+            if let Some(finale) = Finale::new(&self.wad, &self.map, self.retail, self.secret_exit) {
+                self.scene = Scene::Finale(finale);
+                self.wipe_pending = true;
+                return;
+            }
+        }
+
+        match next_map(&self.map, self.secret_exit) {
+            Some(next) => {
+                player.finish_level();
+                self.load_level(&next, player);
+                self.wipe_pending = true;
+            }
+            // Nothing comes after this map
+            None => {
+                self.scene = Scene::Level;
+                self.menu.open();
+            }
+        }
+    }
+
+    // How far the clock is into the next tic, for drawing between tics
+    pub fn tic_fraction(&self) -> f32 {
+        let tics = self.clock_start.elapsed().as_secs_f64() * TICRATE;
+        (tics - self.gametic as f64).clamp(0.0, 1.0) as f32
+    }
+
+    // Turn the keys held down and the mouse movement into this tic's command
+    fn build_ticcmd(&mut self, keys: &[Key]) -> TicCmd {
+        let mut cmd = TicCmd::default();
+        let speed = (keys.contains(&Key::LeftShift) || keys.contains(&Key::RightShift)) as usize;
+        // Alt turns the arrow keys into strafing
+        let strafe = keys.contains(&Key::LeftAlt) || keys.contains(&Key::RightAlt);
+        let (left, right) = (keys.contains(&Key::Left), keys.contains(&Key::Right));
+
+        if left || right {
+            self.turn_held += 1;
+        } else {
+            self.turn_held = 0;
+        }
+        let turn = if self.turn_held < SLOW_TURN_TICS {
+            SLOW_TURN
+        } else {
+            ANGLE_TURN[speed]
+        };
+
+        let mut forward: i8 = 0;
+        let mut side: i8 = 0;
+        if strafe {
+            if right {
+                side += SIDE_MOVE[speed];
+            }
+            if left {
+                side -= SIDE_MOVE[speed];
+            }
+        } else {
+            if right {
+                cmd.angle_turn -= turn;
+            }
+            if left {
+                cmd.angle_turn += turn;
+            }
+        }
+        if keys.contains(&Key::W) || keys.contains(&Key::Up) {
+            forward += FORWARD_MOVE[speed];
+        }
+        if keys.contains(&Key::S) || keys.contains(&Key::Down) {
+            forward -= FORWARD_MOVE[speed];
         }
         if keys.contains(&Key::D) {
-            player.move_x(PLAYER_SPEED * delta_time);
-            println!(
-                "D is pressed. Player x increased to: {}",
-                player.x.get_value()
-            );
-        }
-        if keys.contains(&Key::Left) {
-            player.rotate(-PLAYER_ROTATION_SPEED * delta_time);
-            println!("Left is pressed. Player angle: {}", player.angle);
-        }
-        if keys.contains(&Key::Right) {
-            player.rotate(PLAYER_ROTATION_SPEED * delta_time);
-            println!("Right is pressed. Player angle: {}", player.angle);
-        }
-        if keys.contains(&Key::M) {
-            if now.duration_since(self.last_toggle_time).as_millis() > 200 {
+            side += SIDE_MOVE[speed];
+        }
+        if keys.contains(&Key::A) {
+            side -= SIDE_MOVE[speed];
+        }
+        cmd.forward_move = forward.clamp(-MAX_PLAYER_MOVE, MAX_PLAYER_MOVE);
+        cmd.side_move = side.clamp(-MAX_PLAYER_MOVE, MAX_PLAYER_MOVE);
+
+        // Mouse right turns right, which is a negative turn
+        let sensitivity = (self.options.mouse_sensitivity + 5) as f32 / 10.0;
+        let degrees = self.mouse_dx * sensitivity * MOUSE_TURN_SPEED;
+        cmd.angle_turn = cmd
+            .angle_turn
+            .saturating_sub((degrees * 65536.0 / 360.0) as i16);
+        self.mouse_dx = 0.0;
+
+        if keys.contains(&Key::LeftCtrl) {
+            cmd.buttons |= BT_ATTACK;
+        }
+        if keys.contains(&Key::Space) {
+            cmd.buttons |= BT_USE;
+        }
+
+        // 1 to 7 switch weapons
+        let weapon_keys = [
+            Key::Key1,
+            Key::Key2,
+            Key::Key3,
+            Key::Key4,
+            Key::Key5,
+            Key::Key6,
+            Key::Key7,
+        ];
+        if let Some(number) = weapon_keys.iter().position(|key| keys.contains(key)) {
+            cmd.buttons |= BT_CHANGE | (number as u8) << BT_WEAPONSHIFT;
+        }
+        cmd
+    }
+
+    fn tick(&mut self, cmd: TicCmd, player: &mut Player) {
+        // Demos replace the player's input with the recorded commands. They
+        // go on through the intermissions, which the commands hurry along.
+        let cmd = if self.demo.is_some() {
+            self.next_demo_cmd()
+        } else {
+            Some(cmd)
+        };
+        let Some(mut cmd) = cmd else {
+            self.end_demo(player);
+            return;
+        };
+        if let Some((_, demo)) = &mut self.demo_record {
+            cmd = demo.record(cmd);
+        }
+
+        let mut advance_demo = false;
+        let mut world_done = false;
+        player.start_tic();
+
+        match &mut self.scene {
+            Scene::DemoScreen { tics_left, .. } => {
+                *tics_left = tics_left.saturating_sub(1);
+                advance_demo = *tics_left == 0;
+            }
+            Scene::Level => {
+                if let Some(level) = &mut self.level {
+                    level.stats.time += 1;
+                }
+                self.player_think(&cmd, player);
+                // Monsters move after the player, knowing where they went,
+                // and whatever they did to the player lands once they're done
+                let set = self.weapon_set();
+                if let Some(level) = &mut self.level {
+                    level.tick_mobjs();
+                    level.tick_specials();
+                    for hit in std::mem::take(&mut level.player_hits) {
+                        player.take_hit(&hit, Some(level), set);
+                    }
+                    player.height_clip(level);
+                    sync_player(level, player);
+                }
+                // An exit line, switch or boss death ends the level
+                let exit = self.level.as_mut().and_then(|level| {
+                    std::mem::take(&mut level.exit_requested).then_some(level.secret_exit)
+                });
+                if let Some(secret) = exit {
+                    self.exit_level(secret);
+                }
+            }
+            // Fire or use hurries the tally along, once per press
+            Scene::Intermission(intermission) => {
+                let attack = cmd.buttons & BT_ATTACK != 0;
+                let use_pressed = cmd.buttons & BT_USE != 0;
+                if (attack && !player.attack_down) || (use_pressed && !self.use_down) {
+                    intermission.accelerate();
+                }
+                player.attack_down = attack;
+                self.use_down = use_pressed;
+                intermission.tick();
+                world_done = intermission.finished;
+            }
+            Scene::Finale(finale) => {
+                if finale.tick() {
+                    self.wipe_pending = true;
+                }
+                world_done = finale.finished;
+            }
+        }
+
+        if advance_demo {
+            self.advance_demo(player);
+        } else if world_done {
+            self.world_done(player);
+        }
+    }
+
+    // Move the player by one tic's command and check for the level's exits
+    fn player_think(&mut self, cmd: &TicCmd, player: &mut Player) {
+        let use_pressed = cmd.buttons & BT_USE != 0;
+        let use_triggered = use_pressed && !self.use_down;
+        self.use_down = use_pressed;
+        let set = self.weapon_set();
+
+        // The dead can only watch, until use restarts the level
+        if player.health <= 0 {
+            player.death_think(self.level.as_ref());
+            player.apply_cmd(&TicCmd::default(), self.level.as_ref());
+            if let Some(level) = self.level.as_mut() {
+                sync_player(level, player);
+            }
+            player.move_psprites(self.level.as_mut(), set);
+            if use_triggered {
+                let map = self.map.clone();
+                self.load_level(&map, player);
+                self.wipe_pending = true;
+            }
+            return;
+        }
+
+        // A chainsaw hit pulls the player forwards
+        let mut cmd = *cmd;
+        if player.just_attacked {
+            player.just_attacked = false;
+            cmd.angle_turn = 0;
+            cmd.forward_move = 100;
+            cmd.side_move = 0;
+        }
+
+        let old_position = (player.x.get_value(), player.y.get_value());
+        player.apply_cmd(&cmd, self.level.as_ref());
+        let position = (player.x.get_value(), player.y.get_value());
+        if let Some(level) = self.level.as_mut() {
+            sync_player(level, player);
+            level.player_in_special_sector(player.has_power(Power::IronFeet));
+            // The end of E1M8 takes god mode away
+            player.god_mode = level.player_god;
+        }
+
+        // Pick up whatever was walked over
+        let messages = match self.level.as_mut() {
+            Some(level) => player.touch_specials(level, set),
+            None => Vec::new(),
+        };
+        for message in messages {
+            self.show_message(message);
+        }
+
+        player.change_weapon(set);
+        player.move_psprites(self.level.as_mut(), set);
+        player.tick_powers();
+
+        // Lines walked over or used set off their specials
+        let mut message = None;
+        if let Some(level) = self.level.as_mut() {
+            if !player.no_clip {
+                level.cross_special_lines(old_position, position, Target::Player);
+            }
+            if use_triggered {
+                level.use_lines(Target::Player);
+            }
+            message = level.player_message.take();
+            if let Some(dest) = level.player_teleport.take() {
+                player.teleport(&dest, level);
+                sync_player(level, player);
+            }
+        }
+        if let Some(message) = message {
+            self.show_message(message);
+        }
+    }
+
+    pub fn show_message(&mut self, text: &str) {
+        if self.options.show_messages {
+            self.message = Some((text.to_string(), Instant::now()));
+        }
+    }
+
+    // The message to draw at the top of the screen, if one is still showing
+    pub fn current_message(&self) -> Option<&str> {
+        match &self.message {
+            Some((text, shown_at)) if shown_at.elapsed() < MESSAGE_TIMEOUT => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn start_new_game(&mut self, episode: usize, skill: usize, player: &mut Player) {
+        let map = if self.commercial {
+            "MAP01".to_string()
+        } else {
+            format!("E{}M1", episode + 1)
+        };
+        self.init_new(map, episode + 1, skill, player);
+    }
+
+    // Straight into a game from the command line, like the original's -warp
+    // and -skill
+    pub fn autostart(&mut self, episode: usize, map: usize, skill: usize, player: &mut Player) {
+        let name = if self.commercial {
+            format!("MAP{:02}", map)
+        } else {
+            format!("E{}M{}", episode, map)
+        };
+        if !self.wad.has_lump(&name) {
+            println!("Can't warp to {}, it isn't in the WAD", name);
+            return;
+        }
+        self.init_new(name, episode, skill, player);
+    }
+
+    // Start afresh on `map`, like the original's G_InitNew
+    fn init_new(&mut self, map: String, episode: usize, skill: usize, player: &mut Player) {
+        self.map = map;
+        self.episode = episode;
+        self.skill = skill;
+        self.attract_step = None;
+        self.demo = None;
+        let map = self.map.clone();
+        *player = Player::new();
+        self.load_level(&map, player);
+        self.wipe_pending = true;
+        println!("Starting {} on skill {}", self.map, skill + 1);
+    }
+
+    // Watch for cheat codes typed during a level. True if the key went into
+    // one, so it isn't taken for anything else.
+    fn cheat_responder(&mut self, key: Key, player: &mut Player) -> bool {
+        // Cheating during a demo would throw it out of step
+        if !matches!(self.scene, Scene::Level) || self.level.is_none() || self.demo.is_some() {
+            return false;
+        }
+        let cheat = self.cheats.responder(key);
+        let typing = cheat.is_some() || self.cheats.typing();
+        if let Some(cheat) = cheat {
+            self.apply_cheat(cheat, player);
+        }
+        typing
+    }
+
+    fn apply_cheat(&mut self, cheat: Cheat, player: &mut Player) {
+        match cheat {
+            // iddt only works with the automap up
+            Cheat::MapReveal => {
+                if self.render_map && !self.deathmatch {
+                    self.automap_cheat = (self.automap_cheat + 1) % 3;
+                }
+            }
+            Cheat::ChangeLevel(first, second) => self.change_level_cheat(first, second, player),
+            // The rest are no help on Nightmare
+            _ if self.skill == NIGHTMARE => {}
+            Cheat::God => {
+                player.god_mode = !player.god_mode;
+                if player.god_mode {
+                    player.health = 100;
+                    self.show_message(STSTR_DQDON);
+                } else {
+                    self.show_message(STSTR_DQDOFF);
+                }
+            }
+            Cheat::KeysFullAmmo => {
+                player.armor = Armor {
+                    kind: 2,
+                    points: 200,
+                };
+                player.weapon_owned = [true; 9];
+                player.ammo = player.max_ammo;
+                player.cards = [true; 6];
+                self.show_message(STSTR_KFAADDED);
+            }
+            Cheat::NoClip => {
+                player.no_clip = !player.no_clip;
+                self.show_message(if player.no_clip {
+                    STSTR_NCON
+                } else {
+                    STSTR_NCOFF
+                });
+            }
+            Cheat::BeholdMenu => self.show_message(STSTR_BEHOLD),
+            // Gives the powerup, or takes it away again
+            Cheat::Behold(power) => {
+                if !player.has_power(power) {
+                    player.give_power(power);
+                } else if power != Power::Strength {
+                    player.powers[power as usize] = 1;
+                } else {
+                    player.powers[power as usize] = 0;
+                }
+                self.show_message(STSTR_BEHOLDX);
+            }
+            Cheat::MyPos => {
+                let text = format!(
+                    "ang={:.1};x,y=({:.1},{:.1})",
+                    player.angle,
+                    player.x.get_value(),
+                    player.y.get_value()
+                );
+                self.show_message(&text);
+            }
+        }
+        if let Some(level) = self.level.as_mut() {
+            sync_player(level, player);
+        }
+    }
+
+    // idclev: warp to the map typed, if the WAD has it
+    fn change_level_cheat(&mut self, first: u8, second: u8, player: &mut Player) {
+        let (episode, map) = if self.commercial {
+            (1, first as usize * 10 + second as usize)
+        } else {
+            (first as usize, second as usize)
+        };
+        let name = if self.commercial {
+            format!("MAP{:02}", map)
+        } else {
+            format!("E{}M{}", episode, map)
+        };
+        if episode < 1 || map < 1 || !self.wad.has_lump(&name) {
+            return;
+        }
+        self.show_message(STSTR_CLEV);
+        self.init_new(name, episode, self.skill, player);
+    }
+
+    // Only a level being played, not a demo, can be saved
+    fn can_save(&self) -> bool {
+        matches!(self.scene, Scene::Level)
+            && self.level.is_some()
+            && self.attract_step.is_none()
+            && self.demo.is_none()
+    }
+
+    // Write the game being played to a save slot, like the original's
+    // G_DoSaveGame
+    fn save_game(&mut self, slot: usize, player: &Player) {
+        let Some(level) = &self.level else {
+            return;
+        };
+        let header = SaveHeader {
+            skill: self.skill,
+            episode: self.episode,
+            map: self.map.clone(),
+        };
+        let path = save_path(slot);
+        let description = &self.menu.save_names[slot];
+        match savegame::save_game(&path, description, &header, player, level) {
+            Ok(()) => self.show_message(GGSAVED),
+            Err(e) => self.show_message(&format!("Couldn't save game: {}", e)),
+        }
+    }
+
+    // Carry on a saved game from where it was left, like G_DoLoadGame
+    fn load_game(&mut self, slot: usize, player: &mut Player) {
+        let path = save_path(slot);
+        let (header, mut level, loaded) =
+            match savegame::load_game(&path, &self.wad, self.deathmatch) {
+                Ok(save) => save,
+                Err(e) => {
+                    self.show_message(&format!("Couldn't load game: {}", e));
+                    return;
+                }
+            };
+        self.skill = header.skill;
+        self.episode = header.episode;
+        self.map = header.map;
+        self.attract_step = None;
+        self.demo = None;
+        self.scene = Scene::Level;
+        self.wipe_pending = true;
+        *player = loaded;
+        player.start_tic();
+        level.animations.update(level.stats.time);
+        sync_player(&mut level, player);
+        self.level = Some(level);
+    }
+
+    fn handle_menu_action(&mut self, action: MenuAction, player: &mut Player) {
+        match action {
+            MenuAction::None => {}
+            MenuAction::NewGame { episode, skill } => self.start_new_game(episode, skill, player),
+            MenuAction::LoadGame(slot) => self.load_game(slot, player),
+            MenuAction::SaveGame(slot) => self.save_game(slot, player),
+            MenuAction::ToggleMessages => {
+                self.options.show_messages = !self.options.show_messages;
+                // Always show this one, even when turning messages off
+                let text = if self.options.show_messages {
+                    "Messages ON"
+                } else {
+                    "Messages OFF"
+                };
+                self.message = Some((text.to_string(), Instant::now()));
+            }
+            MenuAction::Quit => self.quit_requested = true,
+        }
+    }
+
+    // `keys` are held down now, `pressed` went down since the last call, in
+    // the order they were pressed
+    pub fn handle_input(
+        &mut self,
+        keys: &[Key],
+        pressed: &[Key],
+        mouse_dx: f32,
+        player: &mut Player,
+    ) {
+        for &key in pressed {
+            // Any key brings up the menu over the title pages and demos
+            if self.attract_step.is_some() && !self.menu.active {
+                self.menu.open();
+                continue;
+            }
+            self.menu.can_save = self.can_save();
+            let action = self.menu.responder(key, &mut self.options);
+            self.handle_menu_action(action, player);
+        }
+
+        // Mouse movement piles up until the next tic reads it
+        self.mouse_dx += mouse_dx;
+
+        // The game is paused while the menu is up, the attract loop isn't
+        let paused = self.menu.active && self.attract_step.is_none();
+
+        if !paused && self.attract_step.is_none() {
+            // Keys typed into a cheat code aren't hotkeys as well
+            let mut hotkeys = Vec::new();
+            for &key in pressed {
+                if !self.cheat_responder(key, player) {
+                    hotkeys.push(key);
+                }
+            }
+
+            // The intermission goes by the tic commands instead, so demos
+            // replay it the same way
+            let accelerate = pressed
+                .iter()
+                .any(|k| matches!(k, Key::Enter | Key::Space | Key::LeftCtrl));
+            if let (true, Scene::Finale(finale)) = (accelerate, &mut self.scene) {
+                self.wipe_pending |= finale.responder();
+            }
+
+            if hotkeys.contains(&Key::M) {
                 // Toggle map rendering when 'M' is pressed
                 self.render_map = !self.render_map;
                 println!("Map rendering: {}", self.render_map);
-                self.last_toggle_time = now;
+            }
+            if hotkeys.contains(&Key::F) {
+                // Toggle the FPS and position overlay when 'F' is pressed
+                self.show_stats = !self.show_stats;
+            }
+            // F5, the original's detail key: interpolation only changes how
+            // frames are drawn too. Not a letter, every cheat starts with i.
+            if hotkeys.contains(&Key::F5) {
+                self.options.interpolate = !self.options.interpolate;
+                let text = if self.options.interpolate {
+                    "Interpolation ON"
+                } else {
+                    "Interpolation OFF"
+                };
+                self.show_message(text);
+            }
+            // Leave through the main loop, which shuts everything down
+            if hotkeys.contains(&Key::Q) {
+                self.quit_requested = true;
+            }
+        }
+
+        // Run every tic that's due on the 35Hz clock. Tics keep being counted
+        // while paused so the game doesn't race to catch up afterwards.
+        let target = (self.clock_start.elapsed().as_secs_f64() * TICRATE) as u64;
+        while self.gametic < target {
+            self.gametic += 1;
+            let paused = self.menu.active && self.attract_step.is_none();
+            if paused {
+                self.mouse_dx = 0.0;
+            } else {
+                let cmd = self.build_ticcmd(keys);
+                self.tick(cmd, player);
             }
         }
-        if keys.contains(&Key::Q) {
-            panic!("Quitting the Game");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testwad;
+
+    // Where the test run below ends up: map, level time, player position
+    // and health, kills, items and secrets
+    const DEMO_OUTCOME: &str =
+        "E1M1 at tic 186: (482.138, 495.547), health 52, kills 1/2, items 0/0, secrets 0/0";
+
+    // The tics played: turn right towards the imp in the far corner, shoot
+    // at it and then run up the room
+    fn demo_cmds() -> Vec<TicCmd> {
+        let turn = TicCmd {
+            angle_turn: -1280,
+            ..TicCmd::default()
+        };
+        let fire = TicCmd {
+            buttons: BT_ATTACK,
+            ..TicCmd::default()
+        };
+        let run = TicCmd {
+            forward_move: 50,
+            side_move: -10,
+            ..TicCmd::default()
+        };
+        [vec![turn; 6], vec![fire; 150], vec![run; 30]].concat()
+    }
+
+    fn outcome(game: &Game, player: &Player) -> String {
+        let stats = &game.level.as_ref().expect("a level").stats;
+        format!(
+            "{} at tic {}: ({:.3}, {:.3}), health {}, kills {}/{}, items {}/{}, secrets {}/{}",
+            game.map,
+            stats.time,
+            player.x.get_value(),
+            player.y.get_value(),
+            player.health,
+            stats.kills,
+            stats.total_kills,
+            stats.items,
+            stats.total_items,
+            stats.secrets,
+            stats.total_secrets
+        )
+    }
+
+    #[test]
+    fn recorded_demo_plays_back_the_same() {
+        // Play and record
+        let wad = testwad::load("record", &testwad::room_map());
+        let mut game = Game::new(&wad);
+        let mut player = Player::new();
+        game.autostart(1, 1, 2, &mut player);
+        game.record_demo("unused");
+        for cmd in demo_cmds() {
+            game.tick(cmd, &mut player);
         }
+        let played = outcome(&game, &player);
+        let (_, demo) = game.demo_record.take().expect("a recording");
+        assert_eq!(demo.cmds.len(), demo_cmds().len());
+
+        // Then play the lump back on its own, the input left out
+        let mut lumps = testwad::room_map();
+        lumps.push(("TESTDEMO", demo.to_bytes()));
+        let wad = testwad::load("playback", &lumps);
+        let mut game = Game::new(&wad);
+        let mut player = Player::new();
+        assert!(game.play_demo_file("TESTDEMO", &mut player));
+        let tics = game.run_demo(&mut player);
+
+        assert_eq!(tics as usize, demo_cmds().len());
+        assert_eq!(outcome(&game, &player), played);
+        assert_eq!(played, DEMO_OUTCOME);
     }
 }
@@ -0,0 +1,1620 @@
+// Thing definitions modeled on Doom's info tables: every kind of map object
+// with its size, health and flags, and the animation states it runs through.
+// States are written compactly per thing and expanded into one big table.
+use std::ops::Range;
+use std::sync::OnceLock;
+
+// Thing flags
+pub const MF_SPECIAL: u32 = 0x1; // Picked up when touched
+pub const MF_SOLID: u32 = 0x2;
+pub const MF_SHOOTABLE: u32 = 0x4;
+pub const MF_NOSECTOR: u32 = 0x8; // Not drawn, but can be touched
+pub const MF_NOBLOCKMAP: u32 = 0x10; // Not touched, but can be drawn
+pub const MF_AMBUSH: u32 = 0x20; // Deaf monster: waits to see the player
+pub const MF_JUSTHIT: u32 = 0x40; // Hurt, so strikes back straight away
+pub const MF_JUSTATTACKED: u32 = 0x80; // Moves before attacking again
+pub const MF_SPAWNCEILING: u32 = 0x100; // Hangs from the ceiling
+pub const MF_NOGRAVITY: u32 = 0x200;
+pub const MF_DROPOFF: u32 = 0x400; // Can walk off ledges
+pub const MF_PICKUP: u32 = 0x800;
+pub const MF_NOCLIP: u32 = 0x1000;
+pub const MF_FLOAT: u32 = 0x4000;
+pub const MF_MISSILE: u32 = 0x10000;
+pub const MF_DROPPED: u32 = 0x20000; // Dropped by a monster, not placed
+pub const MF_SHADOW: u32 = 0x40000; // Partially invisible
+pub const MF_NOBLOOD: u32 = 0x80000;
+pub const MF_CORPSE: u32 = 0x100000;
+pub const MF_INFLOAT: u32 = 0x200000; // Rising or sinking, not chasing height
+pub const MF_COUNTKILL: u32 = 0x400000;
+pub const MF_COUNTITEM: u32 = 0x800000;
+pub const MF_SKULLFLY: u32 = 0x1000000; // A lost soul charging
+pub const MF_NOTDMATCH: u32 = 0x2000000;
+
+// Things that are only ever drawn
+const DECORATION: u32 = MF_NOBLOCKMAP;
+const OBSTACLE: u32 = MF_SOLID;
+const HANGING: u32 = MF_SOLID | MF_SPAWNCEILING | MF_NOGRAVITY;
+const HANGING_DECORATION: u32 = MF_NOBLOCKMAP | MF_SPAWNCEILING | MF_NOGRAVITY;
+const MONSTER: u32 = MF_SOLID | MF_SHOOTABLE | MF_COUNTKILL;
+const FLYING_MONSTER: u32 = MONSTER | MF_FLOAT | MF_NOGRAVITY;
+const PROJECTILE: u32 = MF_NOBLOCKMAP | MF_MISSILE | MF_DROPOFF | MF_NOGRAVITY;
+const EFFECT: u32 = MF_NOBLOCKMAP | MF_NOGRAVITY;
+const PICKUP: u32 = MF_SPECIAL;
+const COUNTED_PICKUP: u32 = MF_SPECIAL | MF_COUNTITEM;
+
+// Code a state runs when it's entered, like the original's action functions
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    None,
+    Look,
+    Chase,
+    FaceTarget,
+    PosAttack,
+    SPosAttack,
+    CPosAttack,
+    CPosRefire,
+    TroopAttack,
+    SargAttack,
+    HeadAttack,
+    BruisAttack,
+    SkullAttack,
+    BspiAttack,
+    CyberAttack,
+    PainAttack,
+    PainDie,
+    SpidRefire,
+    VileChase,
+    VileStart,
+    VileTarget,
+    VileAttack,
+    StartFire,
+    Fire,
+    FireCrackle,
+    SkelWhoosh,
+    SkelFist,
+    SkelMissile,
+    Tracer,
+    FatRaise,
+    FatAttack1,
+    FatAttack2,
+    FatAttack3,
+    Metal,
+    Hoof,
+    BabyMetal,
+    Pain,
+    Scream,
+    XScream,
+    PlayerScream,
+    Fall,
+    Explode,
+    BossDeath,
+    KeenDie,
+    BrainPain,
+    BrainScream,
+    BrainExplode,
+    BrainDie,
+    BrainAwake,
+    BrainSpit,
+    SpawnFly,
+    BFGSpray,
+    // Weapon actions, run by the player's psprites
+    WeaponReady,
+    Lower,
+    Raise,
+    Punch,
+    ReFire,
+    FirePistol,
+    Light0,
+    Light1,
+    Light2,
+    FireShotgun,
+    FireShotgun2,
+    CheckReload,
+    OpenShotgun2,
+    LoadShotgun2,
+    CloseShotgun2,
+    FireCGun,
+    GunFlash,
+    FireMissile,
+    Saw,
+    FirePlasma,
+    BFGsound,
+    FireBFG,
+}
+
+fn action_by_name(name: &str) -> Action {
+    match name {
+        "Look" => Action::Look,
+        "Chase" => Action::Chase,
+        "FaceTarget" => Action::FaceTarget,
+        "PosAttack" => Action::PosAttack,
+        "SPosAttack" => Action::SPosAttack,
+        "CPosAttack" => Action::CPosAttack,
+        "CPosRefire" => Action::CPosRefire,
+        "TroopAttack" => Action::TroopAttack,
+        "SargAttack" => Action::SargAttack,
+        "HeadAttack" => Action::HeadAttack,
+        "BruisAttack" => Action::BruisAttack,
+        "SkullAttack" => Action::SkullAttack,
+        "BspiAttack" => Action::BspiAttack,
+        "CyberAttack" => Action::CyberAttack,
+        "PainAttack" => Action::PainAttack,
+        "PainDie" => Action::PainDie,
+        "SpidRefire" => Action::SpidRefire,
+        "VileChase" => Action::VileChase,
+        "VileStart" => Action::VileStart,
+        "VileTarget" => Action::VileTarget,
+        "VileAttack" => Action::VileAttack,
+        "StartFire" => Action::StartFire,
+        "Fire" => Action::Fire,
+        "FireCrackle" => Action::FireCrackle,
+        "SkelWhoosh" => Action::SkelWhoosh,
+        "SkelFist" => Action::SkelFist,
+        "SkelMissile" => Action::SkelMissile,
+        "Tracer" => Action::Tracer,
+        "FatRaise" => Action::FatRaise,
+        "FatAttack1" => Action::FatAttack1,
+        "FatAttack2" => Action::FatAttack2,
+        "FatAttack3" => Action::FatAttack3,
+        "Metal" => Action::Metal,
+        "Hoof" => Action::Hoof,
+        "BabyMetal" => Action::BabyMetal,
+        "Pain" => Action::Pain,
+        "Scream" => Action::Scream,
+        "XScream" => Action::XScream,
+        "PlayerScream" => Action::PlayerScream,
+        "Fall" => Action::Fall,
+        "Explode" => Action::Explode,
+        "BossDeath" => Action::BossDeath,
+        "KeenDie" => Action::KeenDie,
+        "BrainPain" => Action::BrainPain,
+        "BrainScream" => Action::BrainScream,
+        "BrainExplode" => Action::BrainExplode,
+        "BrainDie" => Action::BrainDie,
+        "BrainAwake" => Action::BrainAwake,
+        "BrainSpit" => Action::BrainSpit,
+        "SpawnFly" => Action::SpawnFly,
+        "BFGSpray" => Action::BFGSpray,
+        "WeaponReady" => Action::WeaponReady,
+        "Lower" => Action::Lower,
+        "Raise" => Action::Raise,
+        "Punch" => Action::Punch,
+        "ReFire" => Action::ReFire,
+        "FirePistol" => Action::FirePistol,
+        "Light0" => Action::Light0,
+        "Light1" => Action::Light1,
+        "Light2" => Action::Light2,
+        "FireShotgun" => Action::FireShotgun,
+        "FireShotgun2" => Action::FireShotgun2,
+        "CheckReload" => Action::CheckReload,
+        "OpenShotgun2" => Action::OpenShotgun2,
+        "LoadShotgun2" => Action::LoadShotgun2,
+        "CloseShotgun2" => Action::CloseShotgun2,
+        "FireCGun" => Action::FireCGun,
+        "GunFlash" => Action::GunFlash,
+        "FireMissile" => Action::FireMissile,
+        "Saw" => Action::Saw,
+        "FirePlasma" => Action::FirePlasma,
+        "BFGsound" => Action::BFGsound,
+        "FireBFG" => Action::FireBFG,
+        _ => panic!("unknown state action {}", name),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MobjType {
+    Player,
+    Possessed,
+    ShotGuy,
+    Vile,
+    // The arch-vile's flames
+    Fire,
+    Undead,
+    Tracer,
+    // The revenant's homing missile trail
+    Smoke,
+    Fatso,
+    FatShot,
+    ChainGuy,
+    Troop,
+    Sergeant,
+    Shadows,
+    Head,
+    Bruiser,
+    BruiserShot,
+    Knight,
+    Skull,
+    Spider,
+    Baby,
+    Cyborg,
+    Pain,
+    WolfSS,
+    Keen,
+    BossBrain,
+    BossSpit,
+    BossTarget,
+    SpawnShot,
+    SpawnFire,
+    Barrel,
+    TroopShot,
+    HeadShot,
+    Rocket,
+    Plasma,
+    Bfg,
+    ArachPlaz,
+    Puff,
+    Blood,
+    TeleportFog,
+    ItemFog,
+    TeleportMan,
+    ExtraBfg,
+    GreenArmor,
+    BlueArmor,
+    HealthBonus,
+    ArmorBonus,
+    BlueCard,
+    RedCard,
+    YellowCard,
+    YellowSkull,
+    RedSkull,
+    BlueSkull,
+    Stimpack,
+    Medikit,
+    SoulSphere,
+    Invulnerability,
+    Berserk,
+    Invisibility,
+    RadSuit,
+    AllMap,
+    LightAmp,
+    MegaSphere,
+    Clip,
+    ClipBox,
+    RocketAmmo,
+    RocketBox,
+    Cell,
+    CellPack,
+    Shells,
+    ShellBox,
+    Backpack,
+    BfgWeapon,
+    Chaingun,
+    Chainsaw,
+    Launcher,
+    PlasmaGun,
+    Shotgun,
+    SuperShotgun,
+    TechLamp,
+    TechLamp2,
+    Column,
+    TallGreenColumn,
+    ShortGreenColumn,
+    TallRedColumn,
+    ShortRedColumn,
+    SkullColumn,
+    HeartColumn,
+    EvilEye,
+    FloatingSkull,
+    TornTree,
+    BlueTorch,
+    GreenTorch,
+    RedTorch,
+    ShortBlueTorch,
+    ShortGreenTorch,
+    ShortRedTorch,
+    Stalagtite,
+    TechPillar,
+    Candlestick,
+    Candelabra,
+    BloodyTwitch,
+    Meat2,
+    Meat3,
+    Meat4,
+    Meat5,
+    NoBlockTwitch,
+    NoBlockMeat2,
+    NoBlockMeat3,
+    NoBlockMeat4,
+    NoBlockMeat5,
+    DeadPlayer,
+    DeadPossessed,
+    DeadShotGuy,
+    DeadTroop,
+    DeadSergeant,
+    DeadHead,
+    DeadSkull,
+    GibbedPlayer,
+    GibbedPlayer2,
+    HeadsOnStick,
+    Gibs,
+    HeadOnStick,
+    HeadCandles,
+    DeadStick,
+    LiveStick,
+    BigTree,
+    BurningBarrel,
+    HangNoGuts,
+    HangBNoBrain,
+    HangTLookDown,
+    HangTSkull,
+    HangTLookUp,
+    HangTNoBrain,
+    ColonGibs,
+    SmallPool,
+    BrainStem,
+}
+
+impl MobjType {
+    pub const ALL: [MobjType; 137] = [
+        MobjType::Player,
+        MobjType::Possessed,
+        MobjType::ShotGuy,
+        MobjType::Vile,
+        MobjType::Fire,
+        MobjType::Undead,
+        MobjType::Tracer,
+        MobjType::Smoke,
+        MobjType::Fatso,
+        MobjType::FatShot,
+        MobjType::ChainGuy,
+        MobjType::Troop,
+        MobjType::Sergeant,
+        MobjType::Shadows,
+        MobjType::Head,
+        MobjType::Bruiser,
+        MobjType::BruiserShot,
+        MobjType::Knight,
+        MobjType::Skull,
+        MobjType::Spider,
+        MobjType::Baby,
+        MobjType::Cyborg,
+        MobjType::Pain,
+        MobjType::WolfSS,
+        MobjType::Keen,
+        MobjType::BossBrain,
+        MobjType::BossSpit,
+        MobjType::BossTarget,
+        MobjType::SpawnShot,
+        MobjType::SpawnFire,
+        MobjType::Barrel,
+        MobjType::TroopShot,
+        MobjType::HeadShot,
+        MobjType::Rocket,
+        MobjType::Plasma,
+        MobjType::Bfg,
+        MobjType::ArachPlaz,
+        MobjType::Puff,
+        MobjType::Blood,
+        MobjType::TeleportFog,
+        MobjType::ItemFog,
+        MobjType::TeleportMan,
+        MobjType::ExtraBfg,
+        MobjType::GreenArmor,
+        MobjType::BlueArmor,
+        MobjType::HealthBonus,
+        MobjType::ArmorBonus,
+        MobjType::BlueCard,
+        MobjType::RedCard,
+        MobjType::YellowCard,
+        MobjType::YellowSkull,
+        MobjType::RedSkull,
+        MobjType::BlueSkull,
+        MobjType::Stimpack,
+        MobjType::Medikit,
+        MobjType::SoulSphere,
+        MobjType::Invulnerability,
+        MobjType::Berserk,
+        MobjType::Invisibility,
+        MobjType::RadSuit,
+        MobjType::AllMap,
+        MobjType::LightAmp,
+        MobjType::MegaSphere,
+        MobjType::Clip,
+        MobjType::ClipBox,
+        MobjType::RocketAmmo,
+        MobjType::RocketBox,
+        MobjType::Cell,
+        MobjType::CellPack,
+        MobjType::Shells,
+        MobjType::ShellBox,
+        MobjType::Backpack,
+        MobjType::BfgWeapon,
+        MobjType::Chaingun,
+        MobjType::Chainsaw,
+        MobjType::Launcher,
+        MobjType::PlasmaGun,
+        MobjType::Shotgun,
+        MobjType::SuperShotgun,
+        MobjType::TechLamp,
+        MobjType::TechLamp2,
+        MobjType::Column,
+        MobjType::TallGreenColumn,
+        MobjType::ShortGreenColumn,
+        MobjType::TallRedColumn,
+        MobjType::ShortRedColumn,
+        MobjType::SkullColumn,
+        MobjType::HeartColumn,
+        MobjType::EvilEye,
+        MobjType::FloatingSkull,
+        MobjType::TornTree,
+        MobjType::BlueTorch,
+        MobjType::GreenTorch,
+        MobjType::RedTorch,
+        MobjType::ShortBlueTorch,
+        MobjType::ShortGreenTorch,
+        MobjType::ShortRedTorch,
+        MobjType::Stalagtite,
+        MobjType::TechPillar,
+        MobjType::Candlestick,
+        MobjType::Candelabra,
+        MobjType::BloodyTwitch,
+        MobjType::Meat2,
+        MobjType::Meat3,
+        MobjType::Meat4,
+        MobjType::Meat5,
+        MobjType::NoBlockTwitch,
+        MobjType::NoBlockMeat2,
+        MobjType::NoBlockMeat3,
+        MobjType::NoBlockMeat4,
+        MobjType::NoBlockMeat5,
+        MobjType::DeadPlayer,
+        MobjType::DeadPossessed,
+        MobjType::DeadShotGuy,
+        MobjType::DeadTroop,
+        MobjType::DeadSergeant,
+        MobjType::DeadHead,
+        MobjType::DeadSkull,
+        MobjType::GibbedPlayer,
+        MobjType::GibbedPlayer2,
+        MobjType::HeadsOnStick,
+        MobjType::Gibs,
+        MobjType::HeadOnStick,
+        MobjType::HeadCandles,
+        MobjType::DeadStick,
+        MobjType::LiveStick,
+        MobjType::BigTree,
+        MobjType::BurningBarrel,
+        MobjType::HangNoGuts,
+        MobjType::HangBNoBrain,
+        MobjType::HangTLookDown,
+        MobjType::HangTSkull,
+        MobjType::HangTLookUp,
+        MobjType::HangTNoBrain,
+        MobjType::ColonGibs,
+        MobjType::SmallPool,
+        MobjType::BrainStem,
+    ];
+}
+
+// The player's weapons, in the original's order: the number keys pick the
+// first seven
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeaponType {
+    Fist,
+    Pistol,
+    Shotgun,
+    Chaingun,
+    Missile,
+    Plasma,
+    Bfg,
+    Chainsaw,
+    SuperShotgun,
+}
+
+impl WeaponType {
+    pub const ALL: [WeaponType; 9] = [
+        WeaponType::Fist,
+        WeaponType::Pistol,
+        WeaponType::Shotgun,
+        WeaponType::Chaingun,
+        WeaponType::Missile,
+        WeaponType::Plasma,
+        WeaponType::Bfg,
+        WeaponType::Chainsaw,
+        WeaponType::SuperShotgun,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AmmoType {
+    Clip,
+    Shell,
+    Cell,
+    Missile,
+}
+
+pub type StateId = usize;
+// The state things go to when they disappear
+pub const S_NULL: StateId = 0;
+
+#[derive(Debug, Clone)]
+pub struct State {
+    pub sprite: &'static str,
+    pub frame: char,
+    // Drawn at full brightness whatever the sector's light
+    pub bright: bool,
+    // -1 stays in this state forever
+    pub tics: i32,
+    pub action: Action,
+    pub next: StateId,
+}
+
+#[derive(Debug, Clone)]
+pub struct MobjInfo {
+    // The THINGS type number, -1 for things never placed in maps
+    pub doomednum: i16,
+    pub spawn_health: i32,
+    pub reaction_time: i32,
+    // Out of 256
+    pub pain_chance: i32,
+    // Map units per tic, per step for walking monsters
+    pub speed: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub mass: i32,
+    pub damage: i32,
+    pub flags: u32,
+    pub spawn_state: StateId,
+    pub see_state: StateId,
+    pub pain_state: StateId,
+    pub melee_state: StateId,
+    pub missile_state: StateId,
+    pub death_state: StateId,
+    pub xdeath_state: StateId,
+    pub raise_state: StateId,
+    // The arch-vile's gesture as it brings a corpse back
+    pub heal_state: StateId,
+    // The explosions the Icon of Sin dies in
+    pub explode_state: StateId,
+}
+
+// How a weapon is raised, lowered, held and fired, and what it fires with
+#[derive(Debug, Clone)]
+pub struct WeaponInfo {
+    // None for the fist and chainsaw
+    pub ammo: Option<AmmoType>,
+    // Ammo used up by each shot
+    pub per_shot: i32,
+    pub up_state: StateId,
+    pub down_state: StateId,
+    pub ready_state: StateId,
+    pub attack_state: StateId,
+    pub flash_state: StateId,
+}
+
+pub struct Info {
+    pub states: Vec<State>,
+    mobjs: Vec<MobjInfo>,
+    weapons: Vec<WeaponInfo>,
+    // Weapon states come after all the things' states
+    pub weapon_states: Range<StateId>,
+}
+
+impl Info {
+    pub fn mobj(&self, kind: MobjType) -> &MobjInfo {
+        &self.mobjs[kind as usize]
+    }
+
+    pub fn weapon(&self, kind: WeaponType) -> &WeaponInfo {
+        &self.weapons[kind as usize]
+    }
+
+    // Which thing a THINGS entry places
+    pub fn by_doomednum(&self, doomednum: i16) -> Option<MobjType> {
+        MobjType::ALL
+            .iter()
+            .copied()
+            .find(|&kind| self.mobj(kind).doomednum == doomednum)
+    }
+}
+
+// The tables are built once, on first use
+pub fn info() -> &'static Info {
+    static INFO: OnceLock<Info> = OnceLock::new();
+    INFO.get_or_init(build_info)
+}
+
+// Where a sequence of states goes after its last one
+enum Next {
+    Loop,
+    Stop,
+    // A labelled sequence of the same thing, and a state offset into it
+    Goto(&'static str, usize),
+}
+
+struct Sequence {
+    label: &'static str,
+    sprite: &'static str,
+    frames: &'static str,
+    next: Next,
+}
+
+struct Def {
+    info: MobjInfo,
+    sequences: Vec<Sequence>,
+}
+
+impl Def {
+    fn new(doomednum: i16, health: i32, radius: f32, height: f32, flags: u32) -> Def {
+        Def {
+            info: MobjInfo {
+                doomednum,
+                spawn_health: health,
+                reaction_time: 8,
+                pain_chance: 0,
+                speed: 0.0,
+                radius,
+                height,
+                mass: 100,
+                damage: 0,
+                flags,
+                spawn_state: S_NULL,
+                see_state: S_NULL,
+                pain_state: S_NULL,
+                melee_state: S_NULL,
+                missile_state: S_NULL,
+                death_state: S_NULL,
+                xdeath_state: S_NULL,
+                raise_state: S_NULL,
+                heal_state: S_NULL,
+                explode_state: S_NULL,
+            },
+            sequences: Vec::new(),
+        }
+    }
+
+    // Monsters share the usual numbers, only health, size and speed vary
+    fn monster(doomednum: i16, health: i32, radius: f32, height: f32, speed: f32) -> Def {
+        Def::new(doomednum, health, radius, height, MONSTER).speed(speed)
+    }
+
+    fn projectile(radius: f32, height: f32, speed: f32, damage: i32) -> Def {
+        Def::new(-1, 1000, radius, height, PROJECTILE)
+            .speed(speed)
+            .damage(damage)
+    }
+
+    fn pickup(doomednum: i16, flags: u32, sprite: &'static str, frames: &'static str) -> Def {
+        let next = if frames.contains("-1") {
+            Next::Stop
+        } else {
+            Next::Loop
+        };
+        Def::new(doomednum, 1000, 20.0, 16.0, flags).states("spawn", sprite, frames, next)
+    }
+
+    fn decoration(
+        doomednum: i16,
+        flags: u32,
+        height: f32,
+        sprite: &'static str,
+        frames: &'static str,
+    ) -> Def {
+        let next = if frames.contains("-1") {
+            Next::Stop
+        } else {
+            Next::Loop
+        };
+        Def::new(doomednum, 1000, 16.0, height, flags).states("spawn", sprite, frames, next)
+    }
+
+    fn speed(mut self, speed: f32) -> Def {
+        self.info.speed = speed;
+        self
+    }
+
+    fn pain_chance(mut self, pain_chance: i32) -> Def {
+        self.info.pain_chance = pain_chance;
+        self
+    }
+
+    fn mass(mut self, mass: i32) -> Def {
+        self.info.mass = mass;
+        self
+    }
+
+    fn damage(mut self, damage: i32) -> Def {
+        self.info.damage = damage;
+        self
+    }
+
+    fn reaction_time(mut self, reaction_time: i32) -> Def {
+        self.info.reaction_time = reaction_time;
+        self
+    }
+
+    fn flags(mut self, flags: u32) -> Def {
+        self.info.flags |= flags;
+        self
+    }
+
+    // Frames are written as groups like "AABB*4 Chase": frame letters, a '*'
+    // if they're drawn fullbright, the tics each one lasts and an optional
+    // action. Groups are separated by commas.
+    fn states(
+        mut self,
+        label: &'static str,
+        sprite: &'static str,
+        frames: &'static str,
+        next: Next,
+    ) -> Def {
+        self.sequences.push(Sequence {
+            label,
+            sprite,
+            frames,
+            next,
+        });
+        self
+    }
+}
+
+// Parse one "AB*6 Action" group into (frame, bright, tics, action) states
+fn parse_group(group: &str) -> Vec<(char, bool, i32, Action)> {
+    let mut parts = group.split_whitespace();
+    let spec = parts.next().unwrap_or_else(|| panic!("empty state group"));
+    let action = parts.next().map_or(Action::None, action_by_name);
+
+    let letters: String = spec
+        .chars()
+        .take_while(|c| c.is_ascii_uppercase() || "[\\]".contains(*c))
+        .collect();
+    let rest = &spec[letters.len()..];
+    let (bright, tics) = match rest.strip_prefix('*') {
+        Some(tics) => (true, tics),
+        None => (false, rest),
+    };
+    let tics: i32 = tics
+        .parse()
+        .unwrap_or_else(|_| panic!("bad state tics in {}", group));
+
+    letters
+        .chars()
+        .map(|frame| (frame, bright, tics, action))
+        .collect()
+}
+
+// Expand sequences onto the end of the state table and point the last state
+// of each one where it goes next. Returns where every label starts.
+fn lay_out(
+    states: &mut Vec<State>,
+    sequences: &[Sequence],
+    owner: &str,
+) -> Vec<(&'static str, StateId)> {
+    let mut labels: Vec<(&'static str, StateId)> = Vec::new();
+    let mut lasts = Vec::new();
+    for sequence in sequences {
+        let first = states.len();
+        for group in sequence.frames.split(',') {
+            for (frame, bright, tics, action) in parse_group(group) {
+                let next = states.len() + 1;
+                states.push(State {
+                    sprite: sequence.sprite,
+                    frame,
+                    bright,
+                    tics,
+                    action,
+                    next,
+                });
+            }
+        }
+        labels.push((sequence.label, first));
+        lasts.push(states.len() - 1);
+    }
+
+    let find = |label: &str| -> StateId {
+        labels
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map_or_else(|| panic!("{} has no {} states", owner, label), |l| l.1)
+    };
+    for ((sequence, &(_, first)), &last) in sequences.iter().zip(labels.iter()).zip(&lasts) {
+        states[last].next = match sequence.next {
+            Next::Loop => first,
+            Next::Stop => S_NULL,
+            Next::Goto(label, offset) => find(label) + offset,
+        };
+    }
+    labels
+}
+
+fn build_info() -> Info {
+    let mut states = vec![State {
+        sprite: "TROO",
+        frame: 'A',
+        bright: false,
+        tics: -1,
+        action: Action::None,
+        next: S_NULL,
+    }];
+    let mut mobjs = Vec::with_capacity(MobjType::ALL.len());
+
+    for (i, &kind) in MobjType::ALL.iter().enumerate() {
+        assert_eq!(kind as usize, i, "MobjType::ALL is out of order");
+        let def = define(kind);
+        let mut info = def.info;
+        let labels = lay_out(&mut states, &def.sequences, &format!("{:?}", kind));
+
+        let optional = |label: &str| {
+            labels
+                .iter()
+                .find(|(name, _)| *name == label)
+                .map_or(S_NULL, |l| l.1)
+        };
+        info.spawn_state = optional("spawn");
+        info.see_state = optional("see");
+        info.pain_state = optional("pain");
+        info.melee_state = optional("melee");
+        info.missile_state = optional("missile");
+        info.death_state = optional("death");
+        info.xdeath_state = optional("xdeath");
+        info.raise_state = optional("raise");
+        info.heal_state = optional("heal");
+        info.explode_state = optional("explode");
+        mobjs.push(info);
+    }
+
+    let first_weapon_state = states.len();
+    let mut weapons = Vec::with_capacity(WeaponType::ALL.len());
+    for (i, &kind) in WeaponType::ALL.iter().enumerate() {
+        assert_eq!(kind as usize, i, "WeaponType::ALL is out of order");
+        let def = define_weapon(kind);
+        let labels = lay_out(&mut states, &def.sequences, &format!("{:?}", kind));
+
+        let state = |label: &str| {
+            labels
+                .iter()
+                .find(|(name, _)| *name == label)
+                .map_or(S_NULL, |l| l.1)
+        };
+        weapons.push(WeaponInfo {
+            ammo: def.ammo,
+            per_shot: def.per_shot,
+            up_state: state("up"),
+            down_state: state("down"),
+            ready_state: state("ready"),
+            attack_state: state("attack"),
+            flash_state: state("flash"),
+        });
+    }
+
+    Info {
+        weapon_states: first_weapon_state..states.len(),
+        states,
+        mobjs,
+        weapons,
+    }
+}
+
+struct WeaponDef {
+    ammo: Option<AmmoType>,
+    per_shot: i32,
+    sequences: Vec<Sequence>,
+}
+
+impl WeaponDef {
+    // Every weapon is raised, lowered and held the same way, in its first
+    // frame
+    fn new(ammo: Option<AmmoType>, sprite: &'static str) -> WeaponDef {
+        WeaponDef {
+            ammo,
+            per_shot: 1,
+            sequences: Vec::new(),
+        }
+        .states("ready", sprite, "A1 WeaponReady", Next::Loop)
+        .states("down", sprite, "A1 Lower", Next::Loop)
+        .states("up", sprite, "A1 Raise", Next::Loop)
+    }
+
+    fn per_shot(mut self, per_shot: i32) -> WeaponDef {
+        self.per_shot = per_shot;
+        self
+    }
+
+    fn states(
+        mut self,
+        label: &'static str,
+        sprite: &'static str,
+        frames: &'static str,
+        next: Next,
+    ) -> WeaponDef {
+        self.sequences.push(Sequence {
+            label,
+            sprite,
+            frames,
+            next,
+        });
+        self
+    }
+
+    // Muzzle flashes end by turning the extra light back off
+    fn light_done(self) -> WeaponDef {
+        self.states("lightdone", "SHTG", "E0 Light0", Next::Stop)
+    }
+}
+
+// Weapons that flash more than one way have the alternatives laid out right
+// after "flash", the firing code picks one by offset
+fn define_weapon(kind: WeaponType) -> WeaponDef {
+    use Next::*;
+
+    match kind {
+        WeaponType::Fist => WeaponDef::new(None, "PUNG").states(
+            "attack",
+            "PUNG",
+            "B4, C4 Punch, D5, C4, B5 ReFire",
+            Goto("ready", 0),
+        ),
+        WeaponType::Pistol => WeaponDef::new(Some(AmmoType::Clip), "PISG")
+            .states(
+                "attack",
+                "PISG",
+                "A4, B6 FirePistol, C4, B5 ReFire",
+                Goto("ready", 0),
+            )
+            .states("flash", "PISF", "A*7 Light1", Goto("lightdone", 0))
+            .light_done(),
+        WeaponType::Shotgun => WeaponDef::new(Some(AmmoType::Shell), "SHTG")
+            .states(
+                "attack",
+                "SHTG",
+                "A3, A7 FireShotgun, B5, C5, D4, C5, B5, A3, A7 ReFire",
+                Goto("ready", 0),
+            )
+            .states(
+                "flash",
+                "SHTF",
+                "A*4 Light1, B*3 Light2",
+                Goto("lightdone", 0),
+            )
+            .light_done(),
+        WeaponType::Chaingun => WeaponDef::new(Some(AmmoType::Clip), "CHGG")
+            .states(
+                "attack",
+                "CHGG",
+                "A4 FireCGun, B4 FireCGun, B0 ReFire",
+                Goto("ready", 0),
+            )
+            .states("flash", "CHGF", "A*5 Light1", Goto("lightdone", 0))
+            .states("flash2", "CHGF", "B*5 Light2", Goto("lightdone", 0))
+            .light_done(),
+        WeaponType::Missile => WeaponDef::new(Some(AmmoType::Missile), "MISG")
+            .states(
+                "attack",
+                "MISG",
+                "B8 GunFlash, B12 FireMissile, B0 ReFire",
+                Goto("ready", 0),
+            )
+            .states(
+                "flash",
+                "MISF",
+                "A*3 Light1, B*4, CD*4 Light2",
+                Goto("lightdone", 0),
+            )
+            .light_done(),
+        WeaponType::Plasma => WeaponDef::new(Some(AmmoType::Cell), "PLSG")
+            .states(
+                "attack",
+                "PLSG",
+                "A3 FirePlasma, B20 ReFire",
+                Goto("ready", 0),
+            )
+            .states("flash", "PLSF", "A*4 Light1", Goto("lightdone", 0))
+            .states("flash2", "PLSF", "B*4 Light1", Goto("lightdone", 0))
+            .light_done(),
+        WeaponType::Bfg => WeaponDef::new(Some(AmmoType::Cell), "BFGG")
+            .per_shot(40)
+            .states(
+                "attack",
+                "BFGG",
+                "A20 BFGsound, B10 GunFlash, B10 FireBFG, B20 ReFire",
+                Goto("ready", 0),
+            )
+            .states(
+                "flash",
+                "BFGF",
+                "A*11 Light1, B*6 Light2",
+                Goto("lightdone", 0),
+            )
+            .light_done(),
+        // The chainsaw idles with its own two frame loop
+        WeaponType::Chainsaw => WeaponDef {
+            ammo: None,
+            per_shot: 1,
+            sequences: Vec::new(),
+        }
+        .states("ready", "SAWG", "CD4 WeaponReady", Loop)
+        .states("down", "SAWG", "C1 Lower", Loop)
+        .states("up", "SAWG", "C1 Raise", Loop)
+        .states(
+            "attack",
+            "SAWG",
+            "A4 Saw, B4 Saw, B0 ReFire",
+            Goto("ready", 0),
+        ),
+        WeaponType::SuperShotgun => WeaponDef::new(Some(AmmoType::Shell), "SHT2")
+            .per_shot(2)
+            .states(
+                "attack",
+                "SHT2",
+                "A3, A7 FireShotgun2, B7, C7 CheckReload, D7 OpenShotgun2, E7, \
+                 F7 LoadShotgun2, G6, H6 CloseShotgun2, A5 ReFire",
+                Goto("ready", 0),
+            )
+            .states(
+                "flash",
+                "SHT2",
+                "I*4 Light1, J*3 Light2",
+                Goto("lightdone", 0),
+            )
+            .light_done(),
+    }
+}
+
+fn define(kind: MobjType) -> Def {
+    use Next::*;
+
+    match kind {
+        MobjType::Player => Def::new(
+            -1,
+            100,
+            16.0,
+            56.0,
+            MF_SOLID | MF_SHOOTABLE | MF_DROPOFF | MF_PICKUP | MF_NOTDMATCH,
+        )
+        .reaction_time(0)
+        .pain_chance(255)
+        .states("spawn", "PLAY", "A-1", Stop)
+        .states("see", "PLAY", "ABCD4", Loop)
+        .states("missile", "PLAY", "E12, F*6", Goto("spawn", 0))
+        .states("pain", "PLAY", "G4, G4 Pain", Goto("spawn", 0))
+        .states(
+            "death",
+            "PLAY",
+            "H10, I10 PlayerScream, J10 Fall, KLM10, N-1",
+            Stop,
+        )
+        .states("xdeath", "PLAY", "O5, P5 XScream, Q5 Fall, RSTUV5, W-1", Stop),
+
+        MobjType::Possessed => Def::monster(3004, 20, 20.0, 56.0, 8.0)
+            .pain_chance(200)
+            .states("spawn", "POSS", "AB10 Look", Loop)
+            .states("see", "POSS", "AABBCCDD4 Chase", Loop)
+            .states("missile", "POSS", "E10 FaceTarget, F8 PosAttack, E8", Goto("see", 0))
+            .states("pain", "POSS", "G3, G3 Pain", Goto("see", 0))
+            .states("death", "POSS", "H5, I5 Scream, J5 Fall, K5, L-1", Stop)
+            .states("xdeath", "POSS", "M5, N5 XScream, O5 Fall, PQRST5, U-1", Stop)
+            .states("raise", "POSS", "KJIH5", Goto("see", 0)),
+
+        MobjType::ShotGuy => Def::monster(9, 30, 20.0, 56.0, 8.0)
+            .pain_chance(170)
+            .states("spawn", "SPOS", "AB10 Look", Loop)
+            .states("see", "SPOS", "AABBCCDD3 Chase", Loop)
+            .states("missile", "SPOS", "E10 FaceTarget, F*10 SPosAttack, E10", Goto("see", 0))
+            .states("pain", "SPOS", "G3, G3 Pain", Goto("see", 0))
+            .states("death", "SPOS", "H5, I5 Scream, J5 Fall, K5, L-1", Stop)
+            .states("xdeath", "SPOS", "M5, N5 XScream, O5 Fall, PQRST5, U-1", Stop)
+            .states("raise", "SPOS", "LKJIH5", Goto("see", 0)),
+
+        MobjType::Vile => Def::monster(64, 700, 20.0, 56.0, 15.0)
+            .pain_chance(10)
+            .mass(500)
+            .states("spawn", "VILE", "AB10 Look", Loop)
+            .states("see", "VILE", "AABBCCDDEEFF2 VileChase", Loop)
+            .states(
+                "missile",
+                "VILE",
+                "G*0 VileStart, G*10 FaceTarget, H*8 VileTarget, IJKLMN*8 FaceTarget, O*8 VileAttack, P*20 FaceTarget",
+                Goto("see", 0),
+            )
+            .states("heal", "VILE", "[\\]*10", Goto("see", 0))
+            .states("pain", "VILE", "Q5, Q5 Pain", Goto("see", 0))
+            .states("death", "VILE", "Q7, R7 Scream, S7 Fall, TUVW7, XY5, Z-1", Stop),
+
+        MobjType::Fire => Def::new(-1, 1000, 20.0, 16.0, EFFECT).states(
+            "spawn",
+            "FIRE",
+            "A*2 StartFire, B*2 Fire, A*2 Fire, B*2 Fire, C*2 FireCrackle, B*2 Fire, \
+             C*2 Fire, B*2 Fire, C*2 Fire, D*2 Fire, C*2 Fire, D*2 Fire, C*2 Fire, \
+             D*2 Fire, E*2 FireCrackle, D*2 Fire, E*2 Fire, D*2 Fire, E*2 FireCrackle, \
+             F*2 Fire, E*2 Fire, F*2 Fire, E*2 Fire, F*2 Fire, G*2 FireCrackle, H*2 Fire, \
+             G*2 Fire, H*2 Fire, G*2 Fire, H*2 Fire",
+            Stop,
+        ),
+
+        MobjType::Undead => Def::monster(66, 300, 20.0, 56.0, 10.0)
+            .pain_chance(100)
+            .mass(500)
+            .states("spawn", "SKEL", "AB10 Look", Loop)
+            .states("see", "SKEL", "AABBCCDDEEFF2 Chase", Loop)
+            .states(
+                "melee",
+                "SKEL",
+                "G0 FaceTarget, G6 SkelWhoosh, H6 FaceTarget, I6 SkelFist",
+                Goto("see", 0),
+            )
+            .states(
+                "missile",
+                "SKEL",
+                "J*0 FaceTarget, J*10 FaceTarget, K10 SkelMissile, K10 FaceTarget",
+                Goto("see", 0),
+            )
+            .states("pain", "SKEL", "L5, L5 Pain", Goto("see", 0))
+            .states("death", "SKEL", "LM7, N7 Scream, O7 Fall, P7, Q-1", Stop)
+            .states("raise", "SKEL", "QPONML5", Goto("see", 0)),
+
+        MobjType::Tracer => Def::projectile(11.0, 8.0, 10.0, 10)
+            .states("spawn", "FATB", "AB*2 Tracer", Loop)
+            .states("death", "FBXP", "A*8, B*6, C*4", Stop),
+
+        MobjType::Smoke => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "PUFF", "BCBCD4", Stop),
+
+        MobjType::Fatso => Def::monster(67, 600, 48.0, 64.0, 8.0)
+            .pain_chance(80)
+            .mass(1000)
+            .states("spawn", "FATT", "AB15 Look", Loop)
+            .states("see", "FATT", "AABBCCDDEEFF4 Chase", Loop)
+            .states(
+                "missile",
+                "FATT",
+                "G20 FatRaise, H*10 FatAttack1, IG5 FaceTarget, H*10 FatAttack2, IG5 FaceTarget, H*10 FatAttack3, IG5 FaceTarget",
+                Goto("see", 0),
+            )
+            .states("pain", "FATT", "J3, J3 Pain", Goto("see", 0))
+            .states("death", "FATT", "K6, L6 Scream, M6 Fall, NOPQRS6, T-1", Stop)
+            .states("raise", "FATT", "RQPONMLK5", Goto("see", 0)),
+
+        MobjType::FatShot => Def::projectile(6.0, 8.0, 20.0, 8)
+            .states("spawn", "MANF", "AB*4", Loop)
+            .states("death", "MISL", "B*8, C*6, D*4", Stop),
+
+        MobjType::ChainGuy => Def::monster(65, 70, 20.0, 56.0, 8.0)
+            .pain_chance(170)
+            .states("spawn", "CPOS", "AB10 Look", Loop)
+            .states("see", "CPOS", "AABBCCDD3 Chase", Loop)
+            .states(
+                "missile",
+                "CPOS",
+                "E10 FaceTarget, FE*4 CPosAttack, F1 CPosRefire",
+                Goto("missile", 1),
+            )
+            .states("pain", "CPOS", "G3, G3 Pain", Goto("see", 0))
+            .states("death", "CPOS", "H5, I5 Scream, J5 Fall, KLM5, N-1", Stop)
+            .states("xdeath", "CPOS", "O5, P5 XScream, Q5 Fall, RS5, T-1", Stop)
+            .states("raise", "CPOS", "NMLKJIH5", Goto("see", 0)),
+
+        MobjType::Troop => Def::monster(3001, 60, 20.0, 56.0, 8.0)
+            .pain_chance(200)
+            .states("spawn", "TROO", "AB10 Look", Loop)
+            .states("see", "TROO", "AABBCCDD3 Chase", Loop)
+            .states("melee", "TROO", "EF8 FaceTarget, G6 TroopAttack", Goto("see", 0))
+            .states("missile", "TROO", "EF8 FaceTarget, G6 TroopAttack", Goto("see", 0))
+            .states("pain", "TROO", "H2, H2 Pain", Goto("see", 0))
+            .states("death", "TROO", "I8, J8 Scream, K6, L6 Fall, M-1", Stop)
+            .states("xdeath", "TROO", "N5, O5 XScream, P5, Q5 Fall, RST5, U-1", Stop)
+            .states("raise", "TROO", "ML8, KJI6", Goto("see", 0)),
+
+        MobjType::Sergeant => demon_states(Def::monster(3002, 150, 30.0, 56.0, 10.0)),
+        MobjType::Shadows => {
+            demon_states(Def::monster(58, 150, 30.0, 56.0, 10.0).flags(MF_SHADOW))
+        }
+
+        MobjType::Head => Def::new(3005, 400, 31.0, 56.0, FLYING_MONSTER)
+            .speed(8.0)
+            .pain_chance(128)
+            .mass(400)
+            .states("spawn", "HEAD", "A10 Look", Loop)
+            .states("see", "HEAD", "A3 Chase", Loop)
+            .states("missile", "HEAD", "BC5 FaceTarget, D*5 HeadAttack", Goto("see", 0))
+            .states("pain", "HEAD", "E3, E3 Pain, F6", Goto("see", 0))
+            .states("death", "HEAD", "G8, H8 Scream, IJ8, K8 Fall, L-1", Stop)
+            .states("raise", "HEAD", "LKJIHG8", Goto("see", 0)),
+
+        MobjType::Bruiser => baron_states(Def::monster(3003, 1000, 24.0, 64.0, 8.0), "BOSS"),
+        MobjType::Knight => baron_states(Def::monster(69, 500, 24.0, 64.0, 8.0), "BOS2"),
+
+        MobjType::BruiserShot => Def::projectile(6.0, 8.0, 15.0, 8)
+            .states("spawn", "BAL7", "AB*4", Loop)
+            .states("death", "BAL7", "CDE*6", Stop),
+
+        MobjType::Skull => Def::new(3006, 100, 16.0, 56.0, MF_SOLID | MF_SHOOTABLE | MF_FLOAT | MF_NOGRAVITY)
+            .speed(8.0)
+            .pain_chance(256)
+            .mass(50)
+            .damage(3)
+            .states("spawn", "SKUL", "AB*10 Look", Loop)
+            .states("see", "SKUL", "AB*6 Chase", Loop)
+            .states("missile", "SKUL", "C*10 FaceTarget, D*4 SkullAttack, CD*4", Goto("missile", 2))
+            .states("pain", "SKUL", "E*3, E*3 Pain", Goto("see", 0))
+            .states("death", "SKUL", "F*6, G*6 Scream, H*6, I*6 Fall, JK6", Stop),
+
+        MobjType::Spider => Def::monster(7, 3000, 128.0, 100.0, 12.0)
+            .pain_chance(40)
+            .mass(1000)
+            .states(
+                "spawn",
+                "SPID",
+                "AB10 Look",
+                Loop,
+            )
+            .states(
+                "see",
+                "SPID",
+                "A3 Metal, ABB3 Chase, C3 Metal, CDD3 Chase, E3 Metal, EFF3 Chase",
+                Loop,
+            )
+            .states(
+                "missile",
+                "SPID",
+                "A*20 FaceTarget, GH*4 SPosAttack, H*1 SpidRefire",
+                Goto("missile", 1),
+            )
+            .states("pain", "SPID", "I3, I3 Pain", Goto("see", 0))
+            .states(
+                "death",
+                "SPID",
+                "J20 Scream, K10 Fall, LMNOPQR10, S30, S-1 BossDeath",
+                Stop,
+            ),
+
+        MobjType::Baby => Def::monster(68, 500, 64.0, 64.0, 12.0)
+            .pain_chance(128)
+            .mass(600)
+            .states("spawn", "BSPI", "AB10 Look", Loop)
+            .states(
+                "see",
+                "BSPI",
+                "A20, A3 BabyMetal, ABBCC3 Chase, D3 BabyMetal, DEEFF3 Chase",
+                Goto("see", 1),
+            )
+            .states(
+                "missile",
+                "BSPI",
+                "A*20 FaceTarget, G*4 BspiAttack, H*4, H*1 SpidRefire",
+                Goto("missile", 1),
+            )
+            .states("pain", "BSPI", "I3, I3 Pain", Goto("see", 1))
+            .states("death", "BSPI", "J20 Scream, K7 Fall, LMNO7, P-1 BossDeath", Stop)
+            .states("raise", "BSPI", "PONMLKJ5", Goto("see", 1)),
+
+        MobjType::Cyborg => Def::monster(16, 4000, 40.0, 110.0, 16.0)
+            .pain_chance(20)
+            .mass(1000)
+            .states("spawn", "CYBR", "AB10 Look", Loop)
+            .states(
+                "see",
+                "CYBR",
+                "A3 Hoof, ABBCC3 Chase, D3 Metal, D3 Chase",
+                Loop,
+            )
+            .states(
+                "missile",
+                "CYBR",
+                "E6 FaceTarget, F12 CyberAttack, E12 FaceTarget, F12 CyberAttack, E12 FaceTarget, F12 CyberAttack",
+                Goto("see", 0),
+            )
+            .states("pain", "CYBR", "G10 Pain", Goto("see", 0))
+            .states(
+                "death",
+                "CYBR",
+                "H10, I10 Scream, JKL10, M10 Fall, NO10, P30, P-1 BossDeath",
+                Stop,
+            ),
+
+        MobjType::Pain => Def::new(71, 400, 31.0, 56.0, FLYING_MONSTER)
+            .speed(8.0)
+            .pain_chance(128)
+            .mass(400)
+            .states("spawn", "PAIN", "A10 Look", Loop)
+            .states("see", "PAIN", "AABBCC3 Chase", Loop)
+            .states(
+                "missile",
+                "PAIN",
+                "DE5 FaceTarget, F*5 FaceTarget, F*0 PainAttack",
+                Goto("see", 0),
+            )
+            .states("pain", "PAIN", "G6, G6 Pain", Goto("see", 0))
+            .states("death", "PAIN", "H*8, I*8 Scream, J*8, K*8 PainDie, LM*8", Stop)
+            .states("raise", "PAIN", "MLKJIH8", Goto("see", 0)),
+
+        MobjType::WolfSS => Def::monster(84, 50, 20.0, 56.0, 8.0)
+            .pain_chance(170)
+            .states("spawn", "SSWV", "AB10 Look", Loop)
+            .states("see", "SSWV", "AABBCCDD3 Chase", Loop)
+            .states(
+                "missile",
+                "SSWV",
+                "EF10 FaceTarget, G*4 CPosAttack, F6 FaceTarget, G*4 CPosAttack, F1 CPosRefire",
+                Goto("missile", 1),
+            )
+            .states("pain", "SSWV", "H3, H3 Pain", Goto("see", 0))
+            .states("death", "SSWV", "I5, J5 Scream, K5 Fall, L5, M-1", Stop)
+            .states("xdeath", "SSWV", "N5, O5 XScream, P5 Fall, QRSTU5, V-1", Stop)
+            .states("raise", "SSWV", "MLKJI5", Goto("see", 0)),
+
+        MobjType::Keen => Def::new(
+            72,
+            100,
+            16.0,
+            72.0,
+            MONSTER | MF_SPAWNCEILING | MF_NOGRAVITY,
+        )
+        .pain_chance(256)
+        .mass(10000000)
+        .states("spawn", "KEEN", "A-1", Stop)
+        .states("pain", "KEEN", "M4, M8 Pain", Goto("spawn", 0))
+        .states("death", "KEEN", "AB6, C6 Scream, DEFGHIJ6, K6 KeenDie, L-1", Stop),
+
+        MobjType::BossBrain => Def::new(88, 250, 16.0, 16.0, MF_SOLID | MF_SHOOTABLE)
+            .pain_chance(255)
+            .mass(10000000)
+            .states("spawn", "BBRN", "A-1", Stop)
+            .states("pain", "BBRN", "B36 BrainPain", Goto("spawn", 0))
+            .states("death", "BBRN", "A100 BrainScream, AA10, A-1 BrainDie", Stop)
+            .states("explode", "MISL", "BC*10, D*10 BrainExplode", Stop),
+
+        MobjType::BossSpit => Def::new(89, 1000, 20.0, 32.0, MF_NOBLOCKMAP | MF_NOSECTOR)
+            .states("spawn", "SSWV", "A10 Look", Loop)
+            .states("see", "SSWV", "A181 BrainAwake, A150 BrainSpit", Goto("see", 1)),
+
+        MobjType::BossTarget => Def::new(87, 1000, 20.0, 32.0, MF_NOBLOCKMAP | MF_NOSECTOR),
+
+        MobjType::SpawnShot => Def::new(-1, 1000, 6.0, 32.0, PROJECTILE | MF_NOCLIP)
+            .speed(10.0)
+            .damage(3)
+            .states("spawn", "BOSF", "ABCD*3 SpawnFly", Loop),
+
+        MobjType::SpawnFire => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "FIRE", "ABCDEFGH*4 Fire", Stop),
+
+        MobjType::Barrel => Def::new(2035, 20, 10.0, 42.0, MF_SOLID | MF_SHOOTABLE | MF_NOBLOOD)
+            .states("spawn", "BAR1", "AB6", Loop)
+            .states("death", "BEXP", "A*5, B*5 Scream, C*5, D*10 Explode, E*10", Stop),
+
+        MobjType::TroopShot => Def::projectile(6.0, 8.0, 10.0, 3)
+            .states("spawn", "BAL1", "AB*4", Loop)
+            .states("death", "BAL1", "CDE*6", Stop),
+
+        MobjType::HeadShot => Def::projectile(6.0, 8.0, 10.0, 5)
+            .states("spawn", "BAL2", "AB*4", Loop)
+            .states("death", "BAL2", "CDE*6", Stop),
+
+        MobjType::Rocket => Def::projectile(11.0, 8.0, 20.0, 20)
+            .states("spawn", "MISL", "A*1", Loop)
+            .states("death", "MISL", "B*8 Explode, C*6, D*4", Stop),
+
+        MobjType::Plasma => Def::projectile(13.0, 8.0, 25.0, 5)
+            .states("spawn", "PLSS", "AB*6", Loop)
+            .states("death", "PLSE", "ABCDE*4", Stop),
+
+        MobjType::Bfg => Def::projectile(13.0, 8.0, 25.0, 100)
+            .states("spawn", "BFS1", "AB*4", Loop)
+            .states("death", "BFE1", "AB*8, C*8 BFGSpray, DEF*8", Stop),
+
+        MobjType::ArachPlaz => Def::projectile(13.0, 8.0, 25.0, 5)
+            .states("spawn", "APLS", "AB*5", Loop)
+            .states("death", "APBX", "ABCDE*5", Stop),
+
+        MobjType::Puff => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "PUFF", "A*4, BCD4", Stop),
+
+        MobjType::Blood => Def::new(-1, 1000, 20.0, 16.0, MF_NOBLOCKMAP)
+            .states("spawn", "BLUD", "CBA8", Stop),
+
+        MobjType::TeleportFog => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "TFOG", "ABABCDEFGHIJ*6", Stop),
+
+        MobjType::ItemFog => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "IFOG", "ABABCDE*6", Stop),
+
+        MobjType::TeleportMan => Def::new(14, 1000, 20.0, 16.0, MF_NOBLOCKMAP | MF_NOSECTOR),
+
+        MobjType::ExtraBfg => Def::new(-1, 1000, 20.0, 16.0, EFFECT)
+            .states("spawn", "BFE2", "ABCD*8", Stop),
+
+        MobjType::GreenArmor => Def::pickup(2018, PICKUP, "ARM1", "A6, B*7"),
+        MobjType::BlueArmor => Def::pickup(2019, PICKUP, "ARM2", "A6, B*6"),
+        MobjType::HealthBonus => Def::pickup(2014, COUNTED_PICKUP, "BON1", "ABCDCB6"),
+        MobjType::ArmorBonus => Def::pickup(2015, COUNTED_PICKUP, "BON2", "ABCDCB6"),
+        MobjType::BlueCard => Def::pickup(5, PICKUP | MF_NOTDMATCH, "BKEY", "A10, B*10"),
+        MobjType::RedCard => Def::pickup(13, PICKUP | MF_NOTDMATCH, "RKEY", "A10, B*10"),
+        MobjType::YellowCard => Def::pickup(6, PICKUP | MF_NOTDMATCH, "YKEY", "A10, B*10"),
+        MobjType::YellowSkull => Def::pickup(39, PICKUP | MF_NOTDMATCH, "YSKU", "A10, B*10"),
+        MobjType::RedSkull => Def::pickup(38, PICKUP | MF_NOTDMATCH, "RSKU", "A10, B*10"),
+        MobjType::BlueSkull => Def::pickup(40, PICKUP | MF_NOTDMATCH, "BSKU", "A10, B*10"),
+        MobjType::Stimpack => Def::pickup(2011, PICKUP, "STIM", "A-1"),
+        MobjType::Medikit => Def::pickup(2012, PICKUP, "MEDI", "A-1"),
+        MobjType::SoulSphere => Def::pickup(2013, COUNTED_PICKUP, "SOUL", "ABCDCB*6"),
+        MobjType::Invulnerability => Def::pickup(2022, COUNTED_PICKUP, "PINV", "ABCD*6"),
+        MobjType::Berserk => Def::pickup(2023, COUNTED_PICKUP, "PSTR", "A*-1"),
+        MobjType::Invisibility => Def::pickup(2024, COUNTED_PICKUP, "PINS", "ABCD*6"),
+        MobjType::RadSuit => Def::pickup(2025, PICKUP, "SUIT", "A*-1"),
+        MobjType::AllMap => Def::pickup(2026, COUNTED_PICKUP, "PMAP", "ABCDCB*6"),
+        MobjType::LightAmp => Def::pickup(2045, COUNTED_PICKUP, "PVIS", "A*6, B6"),
+        MobjType::MegaSphere => Def::pickup(83, COUNTED_PICKUP, "MEGA", "ABCD*6"),
+        MobjType::Clip => Def::pickup(2007, PICKUP, "CLIP", "A-1"),
+        MobjType::ClipBox => Def::pickup(2048, PICKUP, "AMMO", "A-1"),
+        MobjType::RocketAmmo => Def::pickup(2010, PICKUP, "ROCK", "A-1"),
+        MobjType::RocketBox => Def::pickup(2046, PICKUP, "BROK", "A-1"),
+        MobjType::Cell => Def::pickup(2047, PICKUP, "CELL", "A-1"),
+        MobjType::CellPack => Def::pickup(17, PICKUP, "CELP", "A-1"),
+        MobjType::Shells => Def::pickup(2008, PICKUP, "SHEL", "A-1"),
+        MobjType::ShellBox => Def::pickup(2049, PICKUP, "SBOX", "A-1"),
+        MobjType::Backpack => Def::pickup(8, PICKUP, "BPAK", "A-1"),
+        MobjType::BfgWeapon => Def::pickup(2006, PICKUP, "BFUG", "A-1"),
+        MobjType::Chaingun => Def::pickup(2002, PICKUP, "MGUN", "A-1"),
+        MobjType::Chainsaw => Def::pickup(2005, PICKUP, "CSAW", "A-1"),
+        MobjType::Launcher => Def::pickup(2003, PICKUP, "LAUN", "A-1"),
+        MobjType::PlasmaGun => Def::pickup(2004, PICKUP, "PLAS", "A-1"),
+        MobjType::Shotgun => Def::pickup(2001, PICKUP, "SHOT", "A-1"),
+        MobjType::SuperShotgun => Def::pickup(82, PICKUP, "SGN2", "A-1"),
+
+        MobjType::TechLamp => Def::decoration(85, OBSTACLE, 16.0, "TLMP", "ABCD*4"),
+        MobjType::TechLamp2 => Def::decoration(86, OBSTACLE, 16.0, "TLP2", "ABCD*4"),
+        MobjType::Column => Def::decoration(2028, OBSTACLE, 16.0, "COLU", "A*-1"),
+        MobjType::TallGreenColumn => Def::decoration(30, OBSTACLE, 16.0, "COL1", "A-1"),
+        MobjType::ShortGreenColumn => Def::decoration(31, OBSTACLE, 16.0, "COL2", "A-1"),
+        MobjType::TallRedColumn => Def::decoration(32, OBSTACLE, 16.0, "COL3", "A-1"),
+        MobjType::ShortRedColumn => Def::decoration(33, OBSTACLE, 16.0, "COL4", "A-1"),
+        MobjType::SkullColumn => Def::decoration(37, OBSTACLE, 16.0, "COL6", "A-1"),
+        MobjType::HeartColumn => Def::decoration(36, OBSTACLE, 16.0, "COL5", "AB14"),
+        MobjType::EvilEye => Def::decoration(41, OBSTACLE, 16.0, "CEYE", "ABCB*6"),
+        MobjType::FloatingSkull => Def::decoration(42, OBSTACLE, 16.0, "FSKU", "ABC*6"),
+        MobjType::TornTree => Def::decoration(43, OBSTACLE, 16.0, "TRE1", "A-1"),
+        MobjType::BlueTorch => Def::decoration(44, OBSTACLE, 16.0, "TBLU", "ABCD*4"),
+        MobjType::GreenTorch => Def::decoration(45, OBSTACLE, 16.0, "TGRN", "ABCD*4"),
+        MobjType::RedTorch => Def::decoration(46, OBSTACLE, 16.0, "TRED", "ABCD*4"),
+        MobjType::ShortBlueTorch => Def::decoration(55, OBSTACLE, 16.0, "SMBT", "ABCD*4"),
+        MobjType::ShortGreenTorch => Def::decoration(56, OBSTACLE, 16.0, "SMGT", "ABCD*4"),
+        MobjType::ShortRedTorch => Def::decoration(57, OBSTACLE, 16.0, "SMRT", "ABCD*4"),
+        MobjType::Stalagtite => Def::decoration(47, OBSTACLE, 16.0, "SMIT", "A-1"),
+        MobjType::TechPillar => Def::decoration(48, OBSTACLE, 16.0, "ELEC", "A-1"),
+        MobjType::Candlestick => Def::decoration(34, DECORATION, 16.0, "CAND", "A*-1"),
+        MobjType::Candelabra => Def::decoration(35, OBSTACLE, 16.0, "CBRA", "A*-1"),
+        MobjType::BloodyTwitch => {
+            Def::decoration(49, HANGING, 68.0, "GOR1", "A10, B15, C8, B6")
+        }
+        MobjType::Meat2 => Def::decoration(50, HANGING, 84.0, "GOR2", "A-1"),
+        MobjType::Meat3 => Def::decoration(51, HANGING, 84.0, "GOR3", "A-1"),
+        MobjType::Meat4 => Def::decoration(52, HANGING, 68.0, "GOR4", "A-1"),
+        MobjType::Meat5 => Def::decoration(53, HANGING, 52.0, "GOR5", "A-1"),
+        MobjType::NoBlockTwitch => {
+            Def::decoration(63, HANGING_DECORATION, 68.0, "GOR1", "A10, B15, C8, B6")
+        }
+        MobjType::NoBlockMeat2 => Def::decoration(59, HANGING_DECORATION, 84.0, "GOR2", "A-1"),
+        MobjType::NoBlockMeat3 => Def::decoration(61, HANGING_DECORATION, 52.0, "GOR3", "A-1"),
+        MobjType::NoBlockMeat4 => Def::decoration(60, HANGING_DECORATION, 68.0, "GOR4", "A-1"),
+        MobjType::NoBlockMeat5 => Def::decoration(62, HANGING_DECORATION, 52.0, "GOR5", "A-1"),
+        MobjType::DeadPlayer => Def::decoration(15, DECORATION, 16.0, "PLAY", "N-1"),
+        MobjType::DeadPossessed => Def::decoration(18, DECORATION, 16.0, "POSS", "L-1"),
+        MobjType::DeadShotGuy => Def::decoration(19, DECORATION, 16.0, "SPOS", "L-1"),
+        MobjType::DeadTroop => Def::decoration(20, DECORATION, 16.0, "TROO", "M-1"),
+        MobjType::DeadSergeant => Def::decoration(21, DECORATION, 16.0, "SARG", "N-1"),
+        MobjType::DeadHead => Def::decoration(22, DECORATION, 16.0, "HEAD", "L-1"),
+        // Like the original, the dead lost soul vanishes right away
+        MobjType::DeadSkull => Def::new(23, 1000, 16.0, 16.0, DECORATION)
+            .states("spawn", "SKUL", "K6", Stop),
+        MobjType::GibbedPlayer => Def::decoration(10, DECORATION, 16.0, "PLAY", "W-1"),
+        MobjType::GibbedPlayer2 => Def::decoration(12, DECORATION, 16.0, "PLAY", "W-1"),
+        MobjType::HeadsOnStick => Def::decoration(28, OBSTACLE, 16.0, "POL2", "A-1"),
+        MobjType::Gibs => Def::decoration(24, DECORATION, 16.0, "POL5", "A-1"),
+        MobjType::HeadOnStick => Def::decoration(27, OBSTACLE, 16.0, "POL4", "A-1"),
+        MobjType::HeadCandles => Def::decoration(29, OBSTACLE, 16.0, "POL3", "AB*6"),
+        MobjType::DeadStick => Def::decoration(25, OBSTACLE, 16.0, "POL1", "A-1"),
+        MobjType::LiveStick => Def::decoration(26, OBSTACLE, 16.0, "POL6", "A6, B8"),
+        MobjType::BigTree => Def::new(54, 1000, 32.0, 16.0, OBSTACLE)
+            .states("spawn", "TRE2", "A-1", Stop),
+        MobjType::BurningBarrel => Def::decoration(70, OBSTACLE, 16.0, "FCAN", "ABC*4"),
+        MobjType::HangNoGuts => Def::decoration(73, HANGING, 88.0, "HDB1", "A-1"),
+        MobjType::HangBNoBrain => Def::decoration(74, HANGING, 88.0, "HDB2", "A-1"),
+        MobjType::HangTLookDown => Def::decoration(75, HANGING, 64.0, "HDB3", "A-1"),
+        MobjType::HangTSkull => Def::decoration(76, HANGING, 64.0, "HDB4", "A-1"),
+        MobjType::HangTLookUp => Def::decoration(77, HANGING, 64.0, "HDB5", "A-1"),
+        MobjType::HangTNoBrain => Def::decoration(78, HANGING, 64.0, "HDB6", "A-1"),
+        MobjType::ColonGibs => Def::decoration(79, DECORATION, 16.0, "POB1", "A-1"),
+        MobjType::SmallPool => Def::decoration(80, DECORATION, 16.0, "POB2", "A-1"),
+        MobjType::BrainStem => Def::decoration(81, DECORATION, 16.0, "BRS1", "A-1"),
+    }
+}
+
+// The demon and its invisible spectre share their states
+fn demon_states(def: Def) -> Def {
+    use Next::*;
+
+    def.pain_chance(180)
+        .mass(400)
+        .states("spawn", "SARG", "AB10 Look", Loop)
+        .states("see", "SARG", "AABBCCDD2 Chase", Loop)
+        .states(
+            "melee",
+            "SARG",
+            "EF8 FaceTarget, G8 SargAttack",
+            Goto("see", 0),
+        )
+        .states("pain", "SARG", "H2, H2 Pain", Goto("see", 0))
+        .states("death", "SARG", "I8, J8 Scream, K4, L4 Fall, M4, N-1", Stop)
+        .states("raise", "SARG", "NMLKJI5", Goto("see", 0))
+}
+
+// The baron of hell and the hell knight only differ in their sprite
+fn baron_states(def: Def, sprite: &'static str) -> Def {
+    use Next::*;
+
+    def.pain_chance(50)
+        .mass(1000)
+        .states("spawn", sprite, "AB10 Look", Loop)
+        .states("see", sprite, "AABBCCDD3 Chase", Loop)
+        .states(
+            "melee",
+            sprite,
+            "EF8 FaceTarget, G8 BruisAttack",
+            Goto("see", 0),
+        )
+        .states(
+            "missile",
+            sprite,
+            "EF8 FaceTarget, G8 BruisAttack",
+            Goto("see", 0),
+        )
+        .states("pain", sprite, "H2, H2 Pain", Goto("see", 0))
+        .states(
+            "death",
+            sprite,
+            "I8, J8 Scream, K8, L8 Fall, MN8, O-1 BossDeath",
+            Stop,
+        )
+        .states("raise", sprite, "ONMLKJI8", Goto("see", 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_only_point_at_real_states() {
+        let info = info();
+        let states = info.states.len();
+
+        for (i, state) in info.states.iter().enumerate() {
+            assert!(state.next < states, "state {} goes to {}", i, state.next);
+            assert_eq!(state.sprite.len(), 4, "state {} sprite", i);
+        }
+
+        for kind in MobjType::ALL {
+            let mobj = info.mobj(kind);
+            for state in [
+                mobj.spawn_state,
+                mobj.see_state,
+                mobj.pain_state,
+                mobj.melee_state,
+                mobj.missile_state,
+                mobj.death_state,
+                mobj.xdeath_state,
+                mobj.raise_state,
+                mobj.heal_state,
+                mobj.explode_state,
+            ] {
+                assert!(state < states, "{:?} state {}", kind, state);
+            }
+        }
+
+        assert!(info.weapon_states.end <= states);
+        for kind in WeaponType::ALL {
+            let weapon = info.weapon(kind);
+            for state in [
+                weapon.up_state,
+                weapon.down_state,
+                weapon.ready_state,
+                weapon.attack_state,
+            ] {
+                assert!(
+                    info.weapon_states.contains(&state),
+                    "{:?} state {}",
+                    kind,
+                    state
+                );
+            }
+            assert!(weapon.flash_state < states, "{:?} flash", kind);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use crate::font::Font;
+use crate::level::{par_time, parse_map_name, LevelStats};
+use crate::render::Graphics;
+
+const TICRATE: u32 = 35;
+
+// Layout of the single player stats, on the 320x200 screen
+const TITLE_Y: i32 = 2;
+const STATS_X: i32 = 50;
+const STATS_Y: i32 = 50;
+const TIME_X: i32 = 16;
+const TIME_Y: i32 = 200 - 32;
+
+// How long the "you are here" map shows before moving on
+const SHOW_NEXT_LOC_TICS: u32 = 4 * TICRATE;
+
+// Where each level sits on the WIMAP0-2 episode maps
+const LEVEL_NODES: [[(i32, i32); 9]; 3] = [
+    [
+        (185, 164),
+        (148, 143),
+        (69, 122),
+        (209, 102),
+        (116, 89),
+        (166, 55),
+        (71, 56),
+        (135, 29),
+        (71, 24),
+    ],
+    [
+        (254, 25),
+        (97, 50),
+        (188, 64),
+        (128, 78),
+        (214, 92),
+        (133, 130),
+        (208, 136),
+        (148, 140),
+        (235, 158),
+    ],
+    [
+        (156, 168),
+        (48, 154),
+        (174, 95),
+        (265, 75),
+        (130, 48),
+        (279, 23),
+        (198, 48),
+        (140, 25),
+        (281, 136),
+    ],
+];
+
+// Every intermission picture we might draw, loaded up front for the render thread
+pub fn patch_names() -> Vec<String> {
+    let mut names: Vec<String> = [
+        "INTERPIC", "WIMAP0", "WIMAP1", "WIMAP2", "WIF", "WIENTER", "WIOSTK", "WIOSTI", "WISCRT2",
+        "WITIME", "WIPAR", "WIPCNT", "WICOLON", "WIURH0", "WISPLAT",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect();
+
+    names.extend((0..10).map(|i| format!("WINUM{}", i)));
+    for episode in 0..4 {
+        names.extend((0..9).map(|map| format!("WILV{}{}", episode, map)));
+    }
+    names.extend((0..32).map(|map| format!("CWILV{:02}", map)));
+    names
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Stage {
+    // Counting up the kill, item, secret and time figures
+    Stats,
+    // The episode map with a "you are here" marker on the next level
+    NextLoc,
+    // A short pause before the next level loads
+    NoState,
+}
+
+pub struct Intermission {
+    stage: Stage,
+    // Even steps count a figure up, odd steps pause between figures
+    stat_step: usize,
+    stage_tics: u32,
+    pause_tics: u32,
+    // Total tics shown, drives the blinking marker
+    tics: u32,
+    accelerate: bool,
+    pub finished: bool,
+
+    last_map: String,
+    next_map: Option<String>,
+    // Zero for Doom II style MAPxx levels
+    episode: usize,
+    last: usize,
+    next: Option<usize>,
+
+    // Targets and the values currently shown, percentages and seconds
+    kills: i32,
+    items: i32,
+    secrets: i32,
+    time: i32,
+    par: Option<i32>,
+    shown: [i32; 5],
+}
+
+impl Intermission {
+    pub fn new(last_map: &str, next_map: Option<String>, stats: &LevelStats) -> Self {
+        let percent = |count: usize, total: usize| (count * 100 / total.max(1)) as i32;
+        let (episode, last) = parse_map_name(last_map).unwrap_or((0, 1));
+        let next = next_map
+            .as_deref()
+            .and_then(parse_map_name)
+            .map(|(_, map)| map);
+
+        Intermission {
+            stage: Stage::Stats,
+            stat_step: 1,
+            stage_tics: 0,
+            pause_tics: TICRATE,
+            tics: 0,
+            accelerate: false,
+            finished: false,
+            last_map: last_map.to_string(),
+            next_map,
+            episode,
+            last,
+            next,
+            kills: percent(stats.kills, stats.total_kills),
+            items: percent(stats.items, stats.total_items),
+            secrets: percent(stats.secrets, stats.total_secrets),
+            time: (stats.time / TICRATE) as i32,
+            par: par_time(last_map).map(|par| par as i32),
+            shown: [-1; 5],
+        }
+    }
+
+    // The player pressed use or fire: skip ahead
+    pub fn accelerate(&mut self) {
+        self.accelerate = true;
+    }
+
+    pub fn tick(&mut self) {
+        self.tics += 1;
+        self.stage_tics += 1;
+
+        match self.stage {
+            Stage::Stats => self.update_stats(),
+            Stage::NextLoc => {
+                if self.stage_tics >= SHOW_NEXT_LOC_TICS || self.accelerate {
+                    self.set_stage(Stage::NoState);
+                }
+            }
+            Stage::NoState => {
+                if self.stage_tics >= 10 {
+                    self.finished = true;
+                }
+            }
+        }
+
+        self.accelerate = false;
+    }
+
+    fn set_stage(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.stage_tics = 0;
+    }
+
+    fn targets(&self) -> [i32; 5] {
+        [
+            self.kills,
+            self.items,
+            self.secrets,
+            self.time,
+            self.par.unwrap_or(0),
+        ]
+    }
+
+    fn update_stats(&mut self) {
+        let targets = self.targets();
+
+        if self.accelerate && self.stat_step != 10 {
+            self.shown = targets;
+            self.stat_step = 10;
+            return;
+        }
+
+        match self.stat_step {
+            // Percentages count up by two each tic
+            2 | 4 | 6 => {
+                let i = self.stat_step / 2 - 1;
+                self.shown[i] = (self.shown[i] + 2).min(targets[i]);
+                if self.shown[i] == targets[i] {
+                    self.stat_step += 1;
+                }
+            }
+            // Time and par count up together, three seconds per tic
+            8 => {
+                self.shown[3] = (self.shown[3].max(0) + 3).min(targets[3]);
+                self.shown[4] = (self.shown[4].max(0) + 3).min(targets[4]);
+                if self.shown[3] == targets[3] && self.shown[4] == targets[4] {
+                    self.stat_step += 1;
+                }
+            }
+            10 => {
+                if self.accelerate {
+                    // Doom II has no episode map to show
+                    if self.episode == 0 || self.episode > 3 {
+                        self.set_stage(Stage::NoState);
+                    } else {
+                        self.set_stage(Stage::NextLoc);
+                    }
+                }
+            }
+            // Odd steps pause for a moment before the next figure
+            _ => {
+                self.pause_tics -= 1;
+                if self.pause_tics == 0 {
+                    self.stat_step += 1;
+                    self.pause_tics = TICRATE;
+                    if self.stat_step <= 6 {
+                        self.shown[self.stat_step / 2 - 1] = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], graphics: &Graphics, font: &Font) {
+        let background = if self.episode == 0 || self.episode > 3 {
+            "INTERPIC".to_string()
+        } else {
+            format!("WIMAP{}", self.episode - 1)
+        };
+        graphics.draw(buffer, font, &background, "", 0, 0);
+
+        match self.stage {
+            Stage::Stats => self.draw_stats(buffer, graphics, font),
+            Stage::NextLoc | Stage::NoState => self.draw_next_loc(buffer, graphics, font),
+        }
+    }
+
+    fn level_patch(&self, map: usize) -> String {
+        if self.episode == 0 {
+            format!("CWILV{:02}", map - 1)
+        } else {
+            format!("WILV{}{}", self.episode - 1, map - 1)
+        }
+    }
+
+    // Draw a picture centered horizontally, returns the y below it
+    fn draw_centered(
+        &self,
+        buffer: &mut [u32],
+        graphics: &Graphics,
+        font: &Font,
+        name: &str,
+        label: &str,
+        y: i32,
+    ) -> i32 {
+        match graphics.get(name) {
+            Some(patch) => {
+                let x = (320 - patch.width as i32) / 2;
+                graphics.draw(buffer, font, name, label, x, y);
+                y + patch.height as i32 * 5 / 4
+            }
+            None => {
+                let x = (320 - font.text_width(label)) / 2;
+                graphics.draw(buffer, font, name, label, x, y);
+                y + font.height * 2
+            }
+        }
+    }
+
+    fn draw_stats(&self, buffer: &mut [u32], graphics: &Graphics, font: &Font) {
+        // "<level> finished"
+        let y = self.draw_centered(
+            buffer,
+            graphics,
+            font,
+            &self.level_patch(self.last),
+            &self.last_map,
+            TITLE_Y,
+        );
+        self.draw_centered(buffer, graphics, font, "WIF", "finished", y);
+
+        let line_height = graphics
+            .get("WINUM0")
+            .map(|p| p.height as i32 * 3 / 2)
+            .unwrap_or(font.height * 2 + 4);
+        let rows = [
+            ("WIOSTK", "Kills"),
+            ("WIOSTI", "Items"),
+            ("WISCRT2", "Secret"),
+        ];
+
+        for (i, (patch, label)) in rows.iter().enumerate() {
+            let y = STATS_Y + i as i32 * line_height;
+            graphics.draw(buffer, font, patch, label, STATS_X, y);
+            self.draw_percent(buffer, graphics, font, 320 - STATS_X, y, self.shown[i]);
+        }
+
+        graphics.draw(buffer, font, "WITIME", "Time", TIME_X, TIME_Y);
+        self.draw_time(buffer, graphics, font, 160 - TIME_X, TIME_Y, self.shown[3]);
+
+        if self.par.is_some() {
+            graphics.draw(buffer, font, "WIPAR", "Par", 160 + TIME_X, TIME_Y);
+            self.draw_time(buffer, graphics, font, 320 - TIME_X, TIME_Y, self.shown[4]);
+        }
+    }
+
+    fn draw_next_loc(&self, buffer: &mut [u32], graphics: &Graphics, font: &Font) {
+        if (1..=3).contains(&self.episode) {
+            let nodes = &LEVEL_NODES[self.episode - 1];
+
+            // Splat every level finished so far, coming back from the secret
+            // level everything before the next one is done
+            let completed = match (self.last, self.next) {
+                (9, Some(next)) => next - 1,
+                (last, _) => last,
+            };
+            for &(x, y) in nodes.iter().take(completed) {
+                graphics.draw(buffer, font, "WISPLAT", "X", x, y);
+            }
+
+            // Blink the "you are here" pointer on the next level
+            if let Some(next) = self.next {
+                if self.tics & 31 < 20 {
+                    let (x, y) = nodes[next - 1];
+                    graphics.draw(buffer, font, "WIURH0", "*", x, y);
+                }
+            }
+        }
+
+        // "entering <level>"
+        if let (Some(next_map), Some(next)) = (&self.next_map, self.next) {
+            let y = self.draw_centered(buffer, graphics, font, "WIENTER", "entering", TITLE_Y);
+            self.draw_centered(buffer, graphics, font, &self.level_patch(next), next_map, y);
+        }
+    }
+
+    // Numbers are drawn right-aligned so that they end at x
+    fn draw_number(
+        &self,
+        buffer: &mut [u32],
+        graphics: &Graphics,
+        font: &Font,
+        x: i32,
+        y: i32,
+        text: &str,
+    ) -> i32 {
+        let mut x = x;
+        for c in text.chars().rev() {
+            let name = match c {
+                '%' => "WIPCNT".to_string(),
+                ':' => "WICOLON".to_string(),
+                digit => format!("WINUM{}", digit),
+            };
+            let width = match graphics.get(&name) {
+                Some(patch) => patch.width as i32,
+                None => font.text_width(&c.to_string()),
+            };
+            x -= width;
+            graphics.draw(buffer, font, &name, &c.to_string(), x, y);
+        }
+        x
+    }
+
+    fn draw_percent(
+        &self,
+        buffer: &mut [u32],
+        graphics: &Graphics,
+        font: &Font,
+        x: i32,
+        y: i32,
+        percent: i32,
+    ) {
+        if percent >= 0 {
+            let text = format!("{}%", percent);
+            self.draw_number(buffer, graphics, font, x, y, &text);
+        }
+    }
+
+    fn draw_time(
+        &self,
+        buffer: &mut [u32],
+        graphics: &Graphics,
+        font: &Font,
+        x: i32,
+        y: i32,
+        seconds: i32,
+    ) {
+        if seconds >= 0 {
+            let text = format!("{}:{:02}", seconds / 60, seconds % 60);
+            self.draw_number(buffer, graphics, font, x, y, &text);
+        }
+    }
+}
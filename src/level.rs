@@ -0,0 +1,466 @@
+use crate::animations::Animations;
+use crate::collision::{check_position, PLAYER_RADIUS};
+use crate::combat::{Armor, PlayerHit};
+use crate::info::{
+    info, MobjType, WeaponType, MF_AMBUSH, MF_COUNTITEM, MF_COUNTKILL, MF_NOTDMATCH,
+};
+use crate::lights::LightThinker;
+use crate::mobj::{Body, Mobj, Target};
+use crate::random::Random;
+use crate::specials::SectorThinker;
+use crate::switches::{load_switches, Button};
+use crate::wad_reader::{
+    Blockmap, LineDef, Node, Sector, Seg, SideDef, SubSector, Thing, WadData, NF_SUBSECTOR,
+};
+use std::collections::HashMap;
+use std::io;
+
+// Thing flags: which skill levels a thing appears on, and multiplayer-only things
+pub const MTF_EASY: i16 = 1;
+pub const MTF_NORMAL: i16 = 2;
+pub const MTF_HARD: i16 = 4;
+// Monsters that wait until they see the player, whatever they hear
+pub const MTF_AMBUSH: i16 = 8;
+pub const MTF_NOT_SINGLE: i16 = 16;
+
+pub const SECRET_SECTOR: i16 = 9;
+
+// Skill levels, from I'm too young to die to Nightmare!
+pub const BABY: usize = 0;
+pub const EASY: usize = 1;
+pub const NIGHTMARE: usize = 4;
+
+// Thing types marking where players 1-4 start, and deathmatch spawn spots
+const PLAYER_STARTS: [i16; 4] = [1, 2, 3, 4];
+const DEATHMATCH_START: i16 = 11;
+// Random picks among the deathmatch spots before giving up on finding a free one
+const DEATHMATCH_TRIES: usize = 20;
+
+// Where each episode carries on after its secret level
+const SECRET_RETURNS: [usize; 4] = [4, 6, 7, 3];
+
+// Par times in seconds for Doom episodes 1-3 and Doom II
+const PARS: [[u32; 9]; 3] = [
+    [30, 75, 120, 90, 165, 180, 180, 30, 165],
+    [90, 90, 90, 120, 90, 360, 240, 30, 170],
+    [90, 45, 90, 150, 90, 90, 165, 30, 135],
+];
+const CPARS: [u32; 32] = [
+    30, 90, 120, 120, 90, 150, 120, 120, 270, 90, 210, 150, 150, 150, 210, 150, 420, 150, 210, 150,
+    240, 150, 180, 150, 150, 300, 330, 420, 300, 180, 120, 30,
+];
+
+pub struct LevelStats {
+    pub kills: usize,
+    pub total_kills: usize,
+    pub items: usize,
+    pub total_items: usize,
+    pub secrets: usize,
+    pub total_secrets: usize,
+    // Time spent in the level, in 35Hz tics
+    pub time: u32,
+}
+
+// The skill and options a level is played with: -nomonsters leaves the
+// monsters out, -respawn brings dead ones back and -fast speeds them up, as
+// Nightmare does anyway
+#[derive(Clone, Copy)]
+pub struct LevelOptions {
+    pub skill: usize,
+    pub deathmatch: bool,
+    pub no_monsters: bool,
+    pub respawn: bool,
+    pub fast: bool,
+}
+
+// Where a player appears, with the angle in Player::angle terms
+#[derive(Clone, Copy)]
+pub struct MapStart {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+}
+
+impl MapStart {
+    pub fn from_thing(thing: &Thing) -> MapStart {
+        MapStart {
+            x: thing.x as f32,
+            y: thing.y as f32,
+            // THINGS angles count anticlockwise from east, ours clockwise from north
+            angle: (90.0 - thing.angle as f32).rem_euclid(360.0),
+        }
+    }
+}
+
+pub struct Level {
+    pub vertexes: Vec<(f32, f32)>,
+    pub linedefs: Vec<LineDef>,
+    pub sectors: Vec<Sector>,
+    pub sidedefs: Vec<SideDef>,
+    pub segs: Vec<Seg>,
+    pub subsectors: Vec<SubSector>,
+    pub nodes: Vec<Node>,
+    pub blockmap: Blockmap,
+    // One bit per pair of sectors that can't possibly see each other
+    pub reject: Vec<u8>,
+    // The lines around each sector
+    pub sector_lines: Vec<Vec<usize>>,
+    // Who last made a noise each sector heard
+    pub sound_targets: Vec<Option<Target>>,
+    pub skill: usize,
+    // Dead monsters come back, and some monsters and missiles are faster
+    pub respawn_monsters: bool,
+    pub fast_monsters: bool,
+    // Everything in the level that moves or animates, None once it's gone
+    pub mobjs: Vec<Option<Mobj>>,
+    // Where the player is, updated every tic for monsters to find and bump into
+    pub player_body: Option<Body>,
+    // The player's weapon in hand, since a chainsaw doesn't knock back
+    pub player_weapon: WeaponType,
+    // The player's armor and invulnerability, which decide how much of a
+    // hit gets through
+    pub player_armor: Armor,
+    pub player_invulnerable: bool,
+    // iddqd, which the end of E1M8 takes away
+    pub player_god: bool,
+    // Harm done to the player this tic, for the game to apply
+    pub player_hits: Vec<PlayerHit>,
+    pub player_starts: [Option<MapStart>; 4],
+    pub deathmatch_starts: Vec<MapStart>,
+    // Gameplay randomness, restarted with every level so replays match
+    pub rng: Random,
+    pub stats: LevelStats,
+    // Which map this is, for the bosses whose deaths open things up
+    pub map: String,
+    // Doors, lifts, floors and crushers on the move, None once they're done
+    pub thinkers: Vec<Option<SectorThinker>>,
+    // Flickering, flashing and glowing sector lights
+    pub lights: Vec<LightThinker>,
+    // Each sector's entry in `thinkers`, while it's busy
+    pub sector_thinkers: Vec<Option<usize>>,
+    // How tall each wall texture is, for floors that rise by one
+    pub texture_heights: HashMap<String, f32>,
+    // Which frame of each animated wall and floor shows this tic
+    pub animations: Animations,
+    // The off and on textures of every switch, and pressed buttons waiting
+    // to pop back out
+    pub switches: Vec<(String, String)>,
+    pub buttons: Vec<Button>,
+    // The player's keys, for locked doors
+    pub player_cards: [bool; 6],
+    // Something for the game to tell the player, like a door needing a key
+    pub player_message: Option<&'static str>,
+    // Where a teleporter sent the player, for the game to move them
+    pub player_teleport: Option<MapStart>,
+    // An exit line or switch was set off, and whether it was a secret exit
+    pub exit_requested: bool,
+    pub secret_exit: bool,
+    // The Icon of Sin's spawn shooter: the target it fires at next, and
+    // whether the last shot was skipped on the easy skills
+    pub brain_target_on: usize,
+    pub brain_easy: bool,
+}
+
+impl Level {
+    pub fn load(wad: &WadData, name: &str, options: LevelOptions) -> io::Result<Level> {
+        let vertexes = wad.read_vertexes(name)?;
+        let linedefs = wad.read_linedefs(name)?;
+        let things = wad.read_things(name)?;
+        let sectors = wad.read_sectors(name)?;
+        let sidedefs = wad.read_sidedefs(name)?;
+        let segs = wad.read_segs(name)?;
+        let subsectors = wad.read_subsectors(name)?;
+        let nodes = wad.read_nodes(name)?;
+        let blockmap = wad.read_blockmap(name)?;
+        // Some maps come without a REJECT table, then nothing is ruled out
+        let reject = wad.read_map_lump(name, "REJECT").unwrap_or_default();
+
+        // Starts appear whatever the skill, a later duplicate replaces an earlier one
+        let mut player_starts = [None; 4];
+        for thing in &things {
+            if let Some(number) = PLAYER_STARTS.iter().position(|&t| t == thing.thing_type) {
+                player_starts[number] = Some(MapStart::from_thing(thing));
+            }
+        }
+        let deathmatch_starts = things
+            .iter()
+            .filter(|t| t.thing_type == DEATHMATCH_START)
+            .map(MapStart::from_thing)
+            .collect();
+
+        let stats = LevelStats {
+            kills: 0,
+            total_kills: 0,
+            items: 0,
+            total_items: 0,
+            secrets: 0,
+            total_secrets: sectors
+                .iter()
+                .filter(|s| s.special == SECRET_SECTOR)
+                .count(),
+            time: 0,
+        };
+        let mut level = Level {
+            vertexes,
+            linedefs,
+            sectors,
+            sidedefs,
+            segs,
+            subsectors,
+            nodes,
+            blockmap,
+            reject,
+            sector_lines: Vec::new(),
+            sound_targets: Vec::new(),
+            skill: options.skill,
+            respawn_monsters: options.respawn || options.skill == NIGHTMARE,
+            fast_monsters: options.fast || options.skill == NIGHTMARE,
+            mobjs: Vec::new(),
+            player_body: None,
+            player_weapon: WeaponType::Pistol,
+            player_armor: Armor::default(),
+            player_invulnerable: false,
+            player_god: false,
+            player_hits: Vec::new(),
+            player_starts,
+            deathmatch_starts,
+            rng: Random::new(),
+            stats,
+            map: name.to_string(),
+            thinkers: Vec::new(),
+            lights: Vec::new(),
+            sector_thinkers: Vec::new(),
+            texture_heights: wad.read_textures().into_iter().collect(),
+            animations: Animations::load(wad),
+            switches: load_switches(wad),
+            buttons: Vec::new(),
+            player_cards: [false; 6],
+            player_message: None,
+            player_teleport: None,
+            exit_requested: false,
+            secret_exit: false,
+            brain_target_on: 0,
+            brain_easy: false,
+        };
+
+        level.sector_lines = vec![Vec::new(); level.sectors.len()];
+        for (index, line) in level.linedefs.iter().enumerate() {
+            let front = level.side_sector_index(line.front_sidedef);
+            let back = level.side_sector_index(line.back_sidedef);
+            for sector in [front, back].into_iter().flatten() {
+                if let Some(lines) = level.sector_lines.get_mut(sector) {
+                    if lines.last() != Some(&index) {
+                        lines.push(index);
+                    }
+                }
+            }
+        }
+        level.sound_targets = vec![None; level.sectors.len()];
+        level.sector_thinkers = vec![None; level.sectors.len()];
+
+        for thing in &things {
+            level.spawn_map_thing(thing, options);
+        }
+        level.spawn_specials();
+        Ok(level)
+    }
+
+    // Spawn the mobj for a THINGS entry, if it belongs on this skill level
+    // and in this kind of game
+    fn spawn_map_thing(&mut self, thing: &Thing, options: LevelOptions) {
+        let deathmatch = options.deathmatch;
+        if !spawns_on_skill(thing, options.skill, deathmatch) {
+            return;
+        }
+        // Player starts and deathmatch spots aren't things
+        let Some(kind) = info().by_doomednum(thing.thing_type) else {
+            if !PLAYER_STARTS.contains(&thing.thing_type) && thing.thing_type != DEATHMATCH_START {
+                println!("Unknown thing type {}", thing.thing_type);
+            }
+            return;
+        };
+        let flags = info().mobj(kind).flags;
+        if deathmatch && flags & MF_NOTDMATCH != 0 {
+            return;
+        }
+        if options.no_monsters && (kind == MobjType::Skull || flags & MF_COUNTKILL != 0) {
+            return;
+        }
+
+        let start = MapStart::from_thing(thing);
+        let index = self.spawn_mobj(kind, start.x, start.y, start.angle);
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.spawn_point = Some(thing.clone());
+        // Start animations at different points so things don't move in step
+        if mobj.tics > 0 {
+            mobj.tics = 1 + self.rng.next() % mobj.tics;
+        }
+        if mobj.flags & MF_COUNTKILL != 0 {
+            self.stats.total_kills += 1;
+        }
+        if mobj.flags & MF_COUNTITEM != 0 {
+            self.stats.total_items += 1;
+        }
+        if thing.flags & MTF_AMBUSH != 0 {
+            mobj.flags |= MF_AMBUSH;
+        }
+    }
+
+    // Where player `number` (0-3) should appear. Deathmatch picks a random
+    // free deathmatch spot, falling back to the player's own start.
+    pub fn player_start(&mut self, number: usize, deathmatch: bool) -> Option<MapStart> {
+        if deathmatch && !self.deathmatch_starts.is_empty() {
+            for _ in 0..DEATHMATCH_TRIES {
+                let pick = self.rng.next() as usize % self.deathmatch_starts.len();
+                let start = self.deathmatch_starts[pick];
+                if check_position(self, start.x, start.y, PLAYER_RADIUS, Target::Player).is_ok() {
+                    return Some(start);
+                }
+            }
+        }
+        self.player_starts.get(number).copied().flatten()
+    }
+
+    // Walk the BSP tree down to the subsector containing (x, y)
+    fn subsector_at(&self, x: f32, y: f32) -> Option<&SubSector> {
+        // A map with a single subsector has no nodes
+        if self.nodes.is_empty() {
+            return self.subsectors.first();
+        }
+
+        let mut index = self.nodes.len() - 1;
+        loop {
+            let node = self.nodes.get(index)?;
+            let child = node.children[node_side(node, x, y)];
+            if child & NF_SUBSECTOR != 0 {
+                return self.subsectors.get((child & !NF_SUBSECTOR) as usize);
+            }
+            index = child as usize;
+        }
+    }
+
+    // The sector containing (x, y)
+    pub fn sector_at(&self, x: f32, y: f32) -> Option<&Sector> {
+        self.sectors.get(self.sector_index_at(x, y)?)
+    }
+
+    pub fn sector_index_at(&self, x: f32, y: f32) -> Option<usize> {
+        let subsector = self.subsector_at(x, y)?;
+        if subsector.seg_count == 0 {
+            return None;
+        }
+        let seg = self.segs.get(subsector.first_seg)?;
+        let line = self.linedefs.get(seg.linedef)?;
+        let side = if seg.direction == 0 {
+            line.front_sidedef
+        } else {
+            line.back_sidedef
+        };
+        self.side_sector_index(side)
+    }
+
+    // The sector a sidedef faces, None for the missing back of one-sided lines
+    pub fn side_sector_index(&self, side: i16) -> Option<usize> {
+        let sidedef = self.sidedefs.get(usize::try_from(side).ok()?)?;
+        Some(sidedef.sector).filter(|&sector| sector < self.sectors.len())
+    }
+
+    fn side_sector(&self, side: i16) -> Option<&Sector> {
+        self.sectors.get(self.side_sector_index(side)?)
+    }
+
+    // The sectors in front of and behind a linedef, the back one is None for
+    // one-sided lines
+    pub fn line_sectors(&self, line: &LineDef) -> (Option<&Sector>, Option<&Sector>) {
+        (
+            self.side_sector(line.front_sidedef),
+            self.side_sector(line.back_sidedef),
+        )
+    }
+
+    pub fn line_points(&self, line: &LineDef) -> Option<((f32, f32), (f32, f32))> {
+        let start = self.vertexes.get(line.start_vertex[0] as usize)?;
+        let end = self.vertexes.get(line.end_vertex[0] as usize)?;
+        Some((*start, *end))
+    }
+}
+
+// Which child of a BSP node (x, y) is under: right of the partition line is
+// the front, child 0
+pub fn node_side(node: &Node, x: f32, y: f32) -> usize {
+    let (dx, dy) = (x - node.x, y - node.y);
+    if dy * node.dx < node.dy * dx {
+        0
+    } else {
+        1
+    }
+}
+
+// Multiplayer games also get the things flagged as not for single player
+pub fn spawns_on_skill(thing: &Thing, skill: usize, multiplayer: bool) -> bool {
+    let skill_bit = match skill {
+        0 | 1 => MTF_EASY,
+        2 => MTF_NORMAL,
+        _ => MTF_HARD,
+    };
+    thing.flags & skill_bit != 0 && (multiplayer || thing.flags & MTF_NOT_SINGLE == 0)
+}
+
+// Split "E1M3" into (1, 3) and "MAP07" into (0, 7)
+pub fn parse_map_name(map: &str) -> Option<(usize, usize)> {
+    let bytes = map.as_bytes();
+    if let Some(number) = map.strip_prefix("MAP") {
+        return Some((0, number.parse().ok()?));
+    }
+    if bytes.len() == 4 && bytes[0] == b'E' && bytes[2] == b'M' {
+        let episode = (bytes[1] as char).to_digit(10)? as usize;
+        let mission = (bytes[3] as char).to_digit(10)? as usize;
+        return Some((episode, mission));
+    }
+    None
+}
+
+pub fn par_time(map: &str) -> Option<u32> {
+    match parse_map_name(map)? {
+        (0, number) => CPARS.get(number.checked_sub(1)?).copied(),
+        (episode, mission) => PARS
+            .get(episode.checked_sub(1)?)?
+            .get(mission.checked_sub(1)?)
+            .copied(),
+    }
+}
+
+// The map after `map`, taking the secret exit or coming back from the
+// secret levels. None at the end of an episode or of Doom II.
+pub fn next_map(map: &str, secret: bool) -> Option<String> {
+    match parse_map_name(map)? {
+        (0, 15) if secret => Some("MAP31".to_string()),
+        (0, 31) if secret => Some("MAP32".to_string()),
+        (0, 31 | 32) => Some("MAP16".to_string()),
+        (0, number) if number < 30 => Some(format!("MAP{:02}", number + 1)),
+        (0, _) => None,
+        (episode, mission) if secret && mission < 8 => Some(format!("E{}M9", episode)),
+        // Back from the secret level to the map after the one with the exit
+        (episode, 9) => {
+            let next = SECRET_RETURNS.get(episode.checked_sub(1)?)?;
+            Some(format!("E{}M{}", episode, next))
+        }
+        (episode, mission) if mission < 8 => Some(format!("E{}M{}", episode, mission + 1)),
+        _ => None,
+    }
+}
+
+fn cross(o: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+// True if segment a1-a2 properly crosses segment b1-b2
+pub fn segments_cross(a1: (f32, f32), a2: (f32, f32), b1: (f32, f32), b2: (f32, f32)) -> bool {
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+    (d1 > 0.0) != (d2 > 0.0) && (d3 > 0.0) != (d4 > 0.0)
+}
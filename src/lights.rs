@@ -0,0 +1,234 @@
+// Sector lighting, like the original's p_lights.c: flickering, flashing,
+// strobing and glowing sectors, and lines and switches that turn the lights
+// of tagged sectors on and off
+use crate::level::Level;
+
+// Tics a strobe stays dark, fast or slow, and bright
+pub const FAST_DARK: i32 = 15;
+pub const SLOW_DARK: i32 = 35;
+const STROBE_BRIGHT: i32 = 5;
+// Light levels a glowing sector changes by each tic
+const GLOW_SPEED: i16 = 8;
+
+// A sector's light changing every so often, for as long as the level lasts
+pub enum LightThinker {
+    FireFlicker(FireFlicker),
+    Flash(LightFlash),
+    Strobe(Strobe),
+    Glow(Glow),
+}
+
+// Dims by random amounts
+pub struct FireFlicker {
+    pub sector: usize,
+    pub count: i32,
+    pub max_light: i16,
+    pub min_light: i16,
+}
+
+// Goes dark at random for a moment
+pub struct LightFlash {
+    pub sector: usize,
+    pub count: i32,
+    pub max_light: i16,
+    pub min_light: i16,
+    pub max_time: i32,
+    pub min_time: i32,
+}
+
+// Blinks between bright and dark
+pub struct Strobe {
+    pub sector: usize,
+    pub count: i32,
+    pub min_light: i16,
+    pub max_light: i16,
+    pub dark_time: i32,
+    pub bright_time: i32,
+}
+
+// Fades down and up
+pub struct Glow {
+    pub sector: usize,
+    pub min_light: i16,
+    pub max_light: i16,
+    pub direction: i16,
+}
+
+impl Level {
+    // The darkest neighbouring light level below `max`
+    fn min_surrounding_light(&self, sector: usize, max: i16) -> i16 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].light_level)
+            .fold(max, i16::min)
+    }
+
+    pub fn spawn_fire_flicker(&mut self, sector: usize) {
+        self.sectors[sector].special = 0;
+        let light = self.sectors[sector].light_level;
+        let flicker = FireFlicker {
+            sector,
+            count: 4,
+            max_light: light,
+            min_light: self.min_surrounding_light(sector, light) + 16,
+        };
+        self.lights.push(LightThinker::FireFlicker(flicker));
+    }
+
+    pub fn spawn_light_flash(&mut self, sector: usize) {
+        self.sectors[sector].special = 0;
+        let light = self.sectors[sector].light_level;
+        let (max_time, min_time) = (64, 7);
+        let flash = LightFlash {
+            sector,
+            count: (self.rng.next() & max_time) + 1,
+            max_light: light,
+            min_light: self.min_surrounding_light(sector, light),
+            max_time,
+            min_time,
+        };
+        self.lights.push(LightThinker::Flash(flash));
+    }
+
+    // Strobes `in_sync` all start dark together, the rest at random
+    pub fn spawn_strobe_flash(&mut self, sector: usize, dark_time: i32, in_sync: bool) {
+        let light = self.sectors[sector].light_level;
+        let mut min_light = self.min_surrounding_light(sector, light);
+        if min_light == light {
+            min_light = 0;
+        }
+        self.sectors[sector].special = 0;
+        let count = if in_sync {
+            1
+        } else {
+            (self.rng.next() & 7) + 1
+        };
+        let strobe = Strobe {
+            sector,
+            count,
+            min_light,
+            max_light: light,
+            dark_time,
+            bright_time: STROBE_BRIGHT,
+        };
+        self.lights.push(LightThinker::Strobe(strobe));
+    }
+
+    pub fn spawn_glowing_light(&mut self, sector: usize) {
+        let light = self.sectors[sector].light_level;
+        let glow = Glow {
+            sector,
+            min_light: self.min_surrounding_light(sector, light),
+            max_light: light,
+            direction: -1,
+        };
+        self.sectors[sector].special = 0;
+        self.lights.push(LightThinker::Glow(glow));
+    }
+
+    // Start the tagged sectors strobing slowly
+    pub fn start_light_strobing(&mut self, tag: i16) -> bool {
+        let mut search = None;
+        while let Some(sector) = self.find_sector_from_tag(tag, search) {
+            search = Some(sector);
+            if self.sector_thinkers[sector].is_some() {
+                continue;
+            }
+            self.spawn_strobe_flash(sector, SLOW_DARK, false);
+        }
+        true
+    }
+
+    pub fn tick_lights(&mut self) {
+        for light in &mut self.lights {
+            match light {
+                LightThinker::FireFlicker(flicker) => {
+                    flicker.count -= 1;
+                    if flicker.count > 0 {
+                        continue;
+                    }
+                    flicker.count = 4;
+                    let amount = ((self.rng.next() & 3) * 16) as i16;
+                    let light = &mut self.sectors[flicker.sector].light_level;
+                    *light = if *light - amount < flicker.min_light {
+                        flicker.min_light
+                    } else {
+                        flicker.max_light - amount
+                    };
+                }
+                LightThinker::Flash(flash) => {
+                    flash.count -= 1;
+                    if flash.count > 0 {
+                        continue;
+                    }
+                    let light = &mut self.sectors[flash.sector].light_level;
+                    if *light == flash.max_light {
+                        *light = flash.min_light;
+                        flash.count = (self.rng.next() & flash.min_time) + 1;
+                    } else {
+                        *light = flash.max_light;
+                        flash.count = (self.rng.next() & flash.max_time) + 1;
+                    }
+                }
+                LightThinker::Strobe(strobe) => {
+                    strobe.count -= 1;
+                    if strobe.count > 0 {
+                        continue;
+                    }
+                    let light = &mut self.sectors[strobe.sector].light_level;
+                    if *light == strobe.min_light {
+                        *light = strobe.max_light;
+                        strobe.count = strobe.bright_time;
+                    } else {
+                        *light = strobe.min_light;
+                        strobe.count = strobe.dark_time;
+                    }
+                }
+                LightThinker::Glow(glow) => {
+                    let light = &mut self.sectors[glow.sector].light_level;
+                    *light += GLOW_SPEED * glow.direction;
+                    let turn = if glow.direction < 0 {
+                        *light <= glow.min_light
+                    } else {
+                        *light >= glow.max_light
+                    };
+                    if turn {
+                        *light -= GLOW_SPEED * glow.direction;
+                        glow.direction = -glow.direction;
+                    }
+                }
+            }
+        }
+    }
+
+    // Light the sectors tagged `tag` to `bright`, or with 0 to their
+    // brightest neighbour
+    pub fn light_turn_on(&mut self, tag: i16, mut bright: i16) -> bool {
+        for sector in 0..self.sectors.len() {
+            if self.sectors[sector].tag != tag {
+                continue;
+            }
+            // The original keeps the level found here for the rest of the
+            // tagged sectors
+            if bright == 0 {
+                bright = self
+                    .neighbours(sector)
+                    .map(|other| self.sectors[other].light_level)
+                    .fold(bright, i16::max);
+            }
+            self.sectors[sector].light_level = bright;
+        }
+        true
+    }
+
+    // Darken the sectors tagged `tag` to their darkest neighbour
+    pub fn turn_tag_lights_off(&mut self, tag: i16) -> bool {
+        for sector in 0..self.sectors.len() {
+            if self.sectors[sector].tag != tag {
+                continue;
+            }
+            let darkest = self.min_surrounding_light(sector, self.sectors[sector].light_level);
+            self.sectors[sector].light_level = darkest;
+        }
+        true
+    }
+}
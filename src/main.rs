@@ -1,35 +1,120 @@
+mod animations;
+mod ceilings;
+mod cheats;
+mod collision;
+mod combat;
+mod config;
+mod demo;
+mod doors;
+mod endoom;
+mod enemy;
+mod finale;
+mod floors;
+mod font;
 mod game;
+mod info;
+mod intermission;
+mod level;
+mod lights;
+mod menu;
+mod mobj;
+mod pickup;
+mod plats;
+mod random;
 mod render;
+mod savegame;
+mod sight;
+mod specials;
+mod strings;
+mod switches;
+mod teleport;
+#[cfg(test)]
+mod testwad;
+mod ticcmd;
 mod wad_reader;
+mod weapon;
+mod wipe;
+use config::CONFIG_PATH;
+use font::Font;
 use game::Game;
+use game::Options;
 use game::Player;
-use minifb::{Key, Window, WindowOptions};
-use render::{perspective_render, render_linedef, HEIGHT, WIDTH};
-use std::sync::{mpsc, Arc, Mutex};
+use game::Scene;
+use menu::MENU_PATCHES;
+use minifb::{InputCallback, Key, MouseMode, Window, WindowOptions};
+use random::Random;
+use render::{
+    draw_floor, draw_psprites, draw_view_border, perspective_render, render_linedef, tint_screen,
+    Flats, Graphics, HEIGHT, SCALE, WIDTH,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use wad_reader::DoomEngine;
 use wad_reader::WadData;
+use wipe::Wipe;
 
 // Structure to hold game state
 struct GameState {
     buffer: Vec<u32>,
     should_exit: bool,
-    world_objects: Vec<(f32, f32)>,
+}
+
+// Keys as they go down, in order, for the menu and cheat codes. Held keys
+// repeating don't count again.
+struct KeyPresses {
+    down: Vec<Key>,
+    pressed: Rc<RefCell<Vec<Key>>>,
+}
+
+impl InputCallback for KeyPresses {
+    fn add_char(&mut self, _uni_char: u32) {}
+
+    fn set_key_state(&mut self, key: Key, state: bool) {
+        if !state {
+            self.down.retain(|&k| k != key);
+        } else if !self.down.contains(&key) {
+            self.down.push(key);
+            self.pressed.borrow_mut().push(key);
+        }
+    }
+}
+
+// The value following a command line option like -record <name>
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
 }
 
 fn main() {
     let mut doomengine = DoomEngine::new("wad/doom1.wad");
     println!("Loading WAD file: {}", doomengine.wad_path);
-    let wad = doomengine.load_wad().unwrap();
+    doomengine.load_wad().unwrap();
     let wad_data = WadData::new(doomengine);
-    let world_objects = wad_data.read_vertexes().unwrap();
+
+    // -timedemo <name> plays a demo through without a window and reports
+    // how it ended
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(name) = arg_value(&args, "-timedemo") {
+        Game::new(&wad_data).time_demo(name);
+        return;
+    }
+
+    let font = Font::load(&wad_data);
+    let menu_graphics = Graphics::load(&wad_data, &MENU_PATCHES);
+    let intermission_graphics = Graphics::load(&wad_data, &intermission::patch_names());
+    let page_graphics = Graphics::load(&wad_data, &["TITLEPIC", "CREDIT", "HELP1", "HELP2"]);
+    let weapon_graphics = weapon::psprite_graphics(&wad_data);
+    let flats = Flats::load(&wad_data);
 
     // Shared game state
     let game_state = Arc::new(Mutex::new(GameState {
         buffer: vec![0; WIDTH * HEIGHT],
         should_exit: false,
-        world_objects: world_objects.clone(),
     }));
 
     let mut window = Window::new(
@@ -40,13 +125,71 @@ fn main() {
     )
     .unwrap_or_else(|e| panic!("{}", e));
     window.set_target_fps(60);
+    let pressed = Rc::new(RefCell::new(Vec::new()));
+    window.set_input_callback(Box::new(KeyPresses {
+        down: Vec::new(),
+        pressed: Rc::clone(&pressed),
+    }));
 
     // Shared game objects
-    let game = Arc::new(Mutex::new(Game::new()));
-    let player = Arc::new(Mutex::new(Player::new()));
+    let mut game = Game::new(&wad_data);
+    game.options = Options::load(CONFIG_PATH);
+
+    // -deathmatch spawns at the deathmatch spots, -player <1-4> at that player's start
+    game.deathmatch = args.iter().any(|arg| arg == "-deathmatch");
+    // -nomonsters leaves monsters out, -respawn brings them back after dying
+    // and -fast speeds them up, like Nightmare
+    game.no_monsters = args.iter().any(|arg| arg == "-nomonsters");
+    game.respawn = args.iter().any(|arg| arg == "-respawn");
+    game.fast = args.iter().any(|arg| arg == "-fast");
+    if let Some(number) = args
+        .iter()
+        .position(|arg| arg == "-player")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|number| (1..=4).contains(number))
+    {
+        game.player_number = number - 1;
+    }
+
+    // -playdemo <name> plays a demo and quits. -warp <episode> <map>, or
+    // just <map> for Doom II, and -skill <1-5> start a game straight away,
+    // which -record <name> records.
+    let mut player = Player::new();
+    let warp: Vec<usize> = args
+        .iter()
+        .skip_while(|arg| *arg != "-warp")
+        .skip(1)
+        .map_while(|arg| arg.parse().ok())
+        .take(2)
+        .collect();
+    let skill = arg_value(&args, "-skill")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|skill| (1..=5).contains(skill));
+    let record = arg_value(&args, "-record");
+    if let Some(name) = arg_value(&args, "-playdemo") {
+        game.play_demo_file(name, &mut player);
+    } else if !warp.is_empty() || skill.is_some() || record.is_some() {
+        let (episode, map) = match warp[..] {
+            [map] => (1, map),
+            [episode, map] => (episode, map),
+            _ => (1, 1),
+        };
+        game.autostart(
+            episode,
+            map,
+            skill.map_or(2, |skill| skill - 1),
+            &mut player,
+        );
+        if let Some(name) = record {
+            game.record_demo(name);
+        }
+    }
+    let game = Arc::new(Mutex::new(game));
+    let player = Arc::new(Mutex::new(player));
 
     // Channels for communication
-    let (input_tx, input_rx) = mpsc::channel::<Vec<Key>>();
+    let (input_tx, input_rx) = mpsc::channel::<(Vec<Key>, Vec<Key>, f32)>();
     let (render_tx, render_rx) = mpsc::channel::<()>();
 
     // Clone references for threads
@@ -57,10 +200,10 @@ fn main() {
     // Input handling thread
     let input_thread = thread::spawn(move || {
         while !game_state_clone.lock().unwrap().should_exit {
-            if let Ok(keys) = input_rx.try_recv() {
+            if let Ok((keys, pressed, mouse_dx)) = input_rx.try_recv() {
                 let mut game = game_clone.lock().unwrap();
                 let mut player = player_clone.lock().unwrap();
-                game.handle_input(&keys, &mut player);
+                game.handle_input(&keys, &pressed, mouse_dx, &mut player);
             }
             thread::sleep(Duration::from_millis(1));
         }
@@ -73,44 +216,155 @@ fn main() {
         let game = Arc::clone(&game);
 
         thread::spawn(move || {
+            let mut frames = 0;
+            let mut fps = 0;
+            let mut fps_timer = Instant::now();
+            let mut melt: Option<Wipe> = None;
+            let mut melt_rng = Random::new();
+            let mut wipe_started = Instant::now();
+            let mut wipe_tics = 0;
+
             while !game_state.lock().unwrap().should_exit {
                 if let Ok(()) = render_rx.try_recv() {
                     let mut state = game_state.lock().unwrap();
-                    let game = game.lock().unwrap();
+                    let mut game = game.lock().unwrap();
                     let player = player.lock().unwrap();
 
-                    // Clear buffer
-                    for i in state.buffer.iter_mut() {
-                        *i = 0x000000;
-                    }
+                    if let Some(wipe) = &mut melt {
+                        // Run the melt at 35 tics per second until it's done
+                        let target = wipe_started.elapsed().as_millis() * 35 / 1000;
+                        let mut done = false;
+                        while wipe_tics < target && !done {
+                            done = wipe.tick();
+                            wipe_tics += 1;
+                        }
+                        wipe.draw(&mut state.buffer);
+                        if done {
+                            melt = None;
+                        }
+                    } else {
+                        // The last frame shown is what melts away
+                        let wipe_start = game.wipe_pending.then(|| state.buffer.clone());
+
+                        // Clear buffer
+                        for i in state.buffer.iter_mut() {
+                            *i = 0x000000;
+                        }
 
-                    if game.render_map {
-                        // Render WAD vertices when map rendering is enabled
-                        let mut world_objects_copy = state.world_objects.clone();
+                        // Draw the player partway between tics unless that's turned off
+                        let fraction = if game.options.interpolate {
+                            game.tic_fraction()
+                        } else {
+                            1.0
+                        };
+                        let (view_x, view_y, view_angle) = player.view(fraction);
 
-                        // Draw the linedefs from the WAD data
-                        match wad_data.read_linedefs() {
-                            Ok(linedefs) => {
-                                render_linedef(
+                        match &game.scene {
+                            Scene::DemoScreen { page, .. } => {
+                                page_graphics.draw(&mut state.buffer, &font, page, page, 0, 0)
+                            }
+                            Scene::Intermission(intermission) => {
+                                intermission.draw(&mut state.buffer, &intermission_graphics, &font)
+                            }
+                            Scene::Finale(finale) => finale.draw(&mut state.buffer, &font),
+                            Scene::Level if game.render_map => {
+                                // Draw the vertices and linedefs of the current level
+                                if let Some(level) = &game.level {
+                                    // iddt's second step shows where everything is
+                                    let things: Vec<(f32, f32)> = if game.automap_cheat == 2 {
+                                        level.bodies().map(|(_, body)| (body.x, body.y)).collect()
+                                    } else {
+                                        Vec::new()
+                                    };
+                                    render_linedef(
+                                        &mut state.buffer,
+                                        &level.vertexes,
+                                        &level.linedefs,
+                                        game.automap_cheat > 0,
+                                        &things,
+                                    );
+                                }
+                            }
+                            Scene::Level => {
+                                // The view is lit by the sector the player stands in,
+                                // and brighter while the weapon's firing
+                                let light = game
+                                    .level
+                                    .as_ref()
+                                    .and_then(|level| level.sector_at(view_x, view_y))
+                                    .map_or(255, |sector| sector.light_level as i32)
+                                    + player.extra_light * 16;
+                                perspective_render(
                                     &mut state.buffer,
-                                    &mut world_objects_copy,
-                                    linedefs,
+                                    view_x,
+                                    view_y,
+                                    view_angle,
+                                    light,
                                 );
-                                //
-                            }
-                            Err(e) => {
-                                println!("Failed to read linedefs for rendering: {}", e);
+                                // The floor shows this tic's frame of an animated flat
+                                if let Some(level) = &game.level {
+                                    if let Some(sector) = level.sector_at(view_x, view_y) {
+                                        draw_floor(
+                                            &mut state.buffer,
+                                            &flats,
+                                            level.animations.flat(&sector.floor_texture),
+                                            (view_x, view_y, view_angle),
+                                            player.view_z - sector.floor_height,
+                                            light,
+                                        );
+                                    }
+                                }
+                                draw_psprites(
+                                    &mut state.buffer,
+                                    &weapon_graphics,
+                                    &player.psprites,
+                                    game.options.screen_size,
+                                    light,
+                                );
+                                draw_view_border(&mut state.buffer, game.options.screen_size);
                             }
                         }
-                    } else {
-                        let mut world_objects_copy = state.world_objects.clone();
-                        perspective_render(
-                            &mut state.buffer,
-                            player.x.get_value(),
-                            player.y.get_value(),
-                            player.angle,
-                            &mut world_objects_copy,
-                        );
+
+                        // Count frames drawn over the last second
+                        frames += 1;
+                        if fps_timer.elapsed() >= Duration::from_secs(1) {
+                            fps = frames;
+                            frames = 0;
+                            fps_timer = Instant::now();
+                        }
+
+                        if game.show_stats {
+                            let fps_text = format!("FPS: {}", fps);
+                            let fps_x = (WIDTH / SCALE) as i32 - font.text_width(&fps_text) - 2;
+                            font.draw_text(&mut state.buffer, fps_x, 2, &fps_text);
+
+                            let position = format!(
+                                "X: {:.1} Y: {:.1} Z: {:.1} ANGLE: {:.1}",
+                                view_x, view_y, player.view_z, view_angle
+                            );
+                            font.draw_text(&mut state.buffer, 2, 12, &position);
+                        }
+
+                        if let Some(message) = game.current_message() {
+                            font.draw_text(&mut state.buffer, 2, 2, message);
+                        }
+
+                        game.menu
+                            .draw(&mut state.buffer, &menu_graphics, &font, &game.options);
+
+                        // Flash the screen for pain, pickups and powerups
+                        if let Scene::Level = game.scene {
+                            tint_screen(&mut state.buffer, player.palette_index());
+                        }
+
+                        if let Some(start) = wipe_start {
+                            let end = state.buffer.clone();
+                            state.buffer.copy_from_slice(&start);
+                            melt = Some(Wipe::new(start, end, &mut melt_rng));
+                            wipe_started = Instant::now();
+                            wipe_tics = 0;
+                            game.wipe_pending = false;
+                        }
                     }
                 }
                 thread::sleep(Duration::from_millis(1));
@@ -119,10 +373,20 @@ fn main() {
     };
 
     // Main game loop
-    while window.is_open() && !window.is_key_down(Key::Escape) {
+    let mut last_mouse_x = None;
+    while window.is_open() && !game.lock().unwrap().quit_requested {
         let keys = window.get_keys();
 
-        input_tx.send(keys).unwrap();
+        // Horizontal mouse movement since the last frame turns the player
+        let mouse_x = window.get_mouse_pos(MouseMode::Pass).map(|(x, _)| x);
+        let mouse_dx = match (mouse_x, last_mouse_x) {
+            (Some(x), Some(last)) => x - last,
+            _ => 0.0,
+        };
+        last_mouse_x = mouse_x;
+
+        let pressed = std::mem::take(&mut *pressed.borrow_mut());
+        input_tx.send((keys, pressed, mouse_dx)).unwrap();
 
         // Trigger render
         render_tx.send(()).unwrap();
@@ -145,6 +409,20 @@ fn main() {
     }
 
     // Wait for threads to finish
-    input_thread.join().unwrap();
-    render_thread.join().unwrap();
+    for (name, thread) in [("Input", input_thread), ("Render", render_thread)] {
+        if thread.join().is_err() {
+            println!("{} thread stopped with an error", name);
+        }
+    }
+
+    // Write out the demo being recorded and keep the settings for next time,
+    // even if a thread left the game locked mid-update
+    let mut game = game.lock().unwrap_or_else(PoisonError::into_inner);
+    game.end_recording();
+    if let Err(e) = game.options.save(CONFIG_PATH) {
+        println!("Couldn't save {}: {}", CONFIG_PATH, e);
+    }
+
+    endoom::print_endoom(&wad_data);
+    std::process::exit(0);
 }
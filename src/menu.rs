@@ -0,0 +1,605 @@
+use crate::cheats::key_char;
+use crate::font::Font;
+use crate::game::Options;
+use crate::render::{Graphics, SCREEN_Y_OFFSET};
+use crate::savegame::read_save_names;
+use crate::wad_reader::WadData;
+use minifb::Key;
+use std::time::Instant;
+
+const LINE_HEIGHT: i32 = 16;
+const SKULL_X_OFFSET: i32 = -32;
+const SKULL_FRAME_MS: u128 = 228; // 8 tics, like the original
+pub const SAVE_SLOTS: usize = 6;
+pub const SAVE_STRING_SIZE: usize = 24;
+
+pub const SCREEN_SIZE_MIN: usize = 3;
+pub const SCREEN_SIZE_MAX: usize = 11;
+pub const MOUSE_SENSITIVITY_MAX: usize = 9;
+
+const QUIT_PROMPT: &str = "are you sure you want to\nquit this great game?\n\n(press y to quit.)";
+const NIGHTMARE_PROMPT: &str =
+    "are you sure? this skill level\nisn't even remotely fair.\n\npress y or n.";
+const SHAREWARE_PROMPT: &str =
+    "this is the shareware version of doom.\n\nyou need to order the entire trilogy.\n\npress a key.";
+const SAVEDEAD_PROMPT: &str = "you can't save if you aren't playing!\n\npress a key.";
+
+// Every menu graphic we might draw, loaded up front for the render thread
+pub const MENU_PATCHES: [&str; 33] = [
+    "M_DOOM", "M_NGAME", "M_OPTION", "M_LOADG", "M_SAVEG", "M_QUITG", "M_EPISOD", "M_EPI1",
+    "M_EPI2", "M_EPI3", "M_EPI4", "M_NEWG", "M_SKILL", "M_JKILL", "M_ROUGH", "M_HURT", "M_ULTRA",
+    "M_NMARE", "M_OPTTTL", "M_MESSG", "M_SCRNSZ", "M_MSENS", "M_MSGON", "M_MSGOFF", "M_THERML",
+    "M_THERMM", "M_THERMR", "M_THERMO", "M_LSLEFT", "M_LSCNTR", "M_LSRGHT", "M_SKULL1", "M_SKULL2",
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Page {
+    Main,
+    Episode,
+    Skill,
+    Options,
+    Load,
+    Save,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Prompt {
+    Quit,
+    Nightmare,
+    Shareware,
+    SaveDead,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ItemKind {
+    Button,
+    Slider,
+    Spacer,
+}
+
+struct MenuItem {
+    kind: ItemKind,
+    patch: &'static str,
+    label: &'static str,
+    hotkey: Option<Key>,
+}
+
+const fn button(patch: &'static str, label: &'static str, hotkey: Key) -> MenuItem {
+    MenuItem {
+        kind: ItemKind::Button,
+        patch,
+        label,
+        hotkey: Some(hotkey),
+    }
+}
+
+const fn slider(patch: &'static str, label: &'static str, hotkey: Key) -> MenuItem {
+    MenuItem {
+        kind: ItemKind::Slider,
+        patch,
+        label,
+        hotkey: Some(hotkey),
+    }
+}
+
+const SPACER: MenuItem = MenuItem {
+    kind: ItemKind::Spacer,
+    patch: "",
+    label: "",
+    hotkey: None,
+};
+
+const MAIN_ITEMS: [MenuItem; 5] = [
+    button("M_NGAME", "New Game", Key::N),
+    button("M_OPTION", "Options", Key::O),
+    button("M_LOADG", "Load Game", Key::L),
+    button("M_SAVEG", "Save Game", Key::S),
+    button("M_QUITG", "Quit Game", Key::Q),
+];
+
+const EPISODE_ITEMS: [MenuItem; 4] = [
+    button("M_EPI1", "Knee-Deep in the Dead", Key::K),
+    button("M_EPI2", "The Shores of Hell", Key::T),
+    button("M_EPI3", "Inferno", Key::I),
+    button("M_EPI4", "Thy Flesh Consumed", Key::T),
+];
+
+const SKILL_ITEMS: [MenuItem; 5] = [
+    button("M_JKILL", "I'm too young to die.", Key::I),
+    button("M_ROUGH", "Hey, not too rough.", Key::H),
+    button("M_HURT", "Hurt me plenty.", Key::H),
+    button("M_ULTRA", "Ultra-Violence.", Key::U),
+    button("M_NMARE", "Nightmare!", Key::N),
+];
+
+// Sliders are followed by a spacer row where their thermometer is drawn
+const OPTIONS_ITEMS: [MenuItem; 5] = [
+    button("M_MESSG", "Messages:", Key::M),
+    slider("M_SCRNSZ", "Screen Size", Key::S),
+    SPACER,
+    slider("M_MSENS", "Mouse Sensitivity", Key::M),
+    SPACER,
+];
+const OPTIONS_MESSAGES: usize = 0;
+const OPTIONS_SCREEN_SIZE: usize = 1;
+const OPTIONS_MOUSE: usize = 3;
+
+const SLOT_ITEMS: [MenuItem; SAVE_SLOTS] = [
+    button("", "", Key::Key1),
+    button("", "", Key::Key2),
+    button("", "", Key::Key3),
+    button("", "", Key::Key4),
+    button("", "", Key::Key5),
+    button("", "", Key::Key6),
+];
+
+struct PageLayout {
+    titles: &'static [(&'static str, &'static str, i32, i32)],
+    x: i32,
+    y: i32,
+    items: &'static [MenuItem],
+    parent: Option<Page>,
+}
+
+fn layout(page: Page) -> PageLayout {
+    match page {
+        Page::Main => PageLayout {
+            titles: &[("M_DOOM", "DOOM", 94, 2)],
+            x: 97,
+            y: 64,
+            items: &MAIN_ITEMS,
+            parent: None,
+        },
+        Page::Episode => PageLayout {
+            titles: &[("M_EPISOD", "Which Episode?", 54, 38)],
+            x: 48,
+            y: 63,
+            items: &EPISODE_ITEMS,
+            parent: Some(Page::Main),
+        },
+        Page::Skill => PageLayout {
+            titles: &[
+                ("M_NEWG", "NEW GAME", 96, 14),
+                ("M_SKILL", "Choose Skill Level:", 54, 38),
+            ],
+            x: 48,
+            y: 63,
+            items: &SKILL_ITEMS,
+            parent: Some(Page::Episode),
+        },
+        Page::Options => PageLayout {
+            titles: &[("M_OPTTTL", "OPTIONS", 108, 15)],
+            x: 60,
+            y: 37,
+            items: &OPTIONS_ITEMS,
+            parent: Some(Page::Main),
+        },
+        Page::Load => PageLayout {
+            titles: &[("M_LOADG", "Load Game", 72, 28)],
+            x: 80,
+            y: 54,
+            items: &SLOT_ITEMS,
+            parent: Some(Page::Main),
+        },
+        Page::Save => PageLayout {
+            titles: &[("M_SAVEG", "Save Game", 72, 28)],
+            x: 80,
+            y: 54,
+            items: &SLOT_ITEMS,
+            parent: Some(Page::Main),
+        },
+    }
+}
+
+// What the game has to do in response to a menu selection
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuAction {
+    None,
+    NewGame { episode: usize, skill: usize },
+    LoadGame(usize),
+    SaveGame(usize),
+    ToggleMessages,
+    Quit,
+}
+
+pub struct Menu {
+    pub active: bool,
+    page: Page,
+    item_on: usize,
+    prompt: Option<Prompt>,
+    episode: usize,
+    // Episodes listed in the menu (3, or 4 for The Ultimate Doom)
+    menu_episodes: usize,
+    // Episodes actually present in the WAD, fewer than listed for shareware
+    available_episodes: usize,
+    // Doom II has no episodes, New Game goes straight to the skill page
+    commercial: bool,
+    pub save_names: Vec<String>,
+    // The slot whose description is being typed, and what it said before
+    save_entry: Option<usize>,
+    save_old: String,
+    // Set by the game while there's a level being played to save
+    pub can_save: bool,
+    opened_at: Instant,
+}
+
+impl Menu {
+    pub fn new(wad: &WadData) -> Self {
+        let available_episodes = (1..=4)
+            .filter(|e| wad.has_lump(&format!("E{}M1", e)))
+            .count();
+
+        Menu {
+            active: false,
+            page: Page::Main,
+            item_on: 0,
+            prompt: None,
+            episode: 0,
+            menu_episodes: if available_episodes == 4 { 4 } else { 3 },
+            available_episodes,
+            commercial: wad.has_lump("MAP01"),
+            save_names: read_save_names(),
+            save_entry: None,
+            save_old: String::new(),
+            can_save: false,
+            opened_at: Instant::now(),
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.active = true;
+        self.set_page(Page::Main);
+        self.opened_at = Instant::now();
+    }
+
+    pub fn close(&mut self) {
+        self.active = false;
+        self.prompt = None;
+    }
+
+    fn set_page(&mut self, page: Page) {
+        self.page = page;
+        self.item_on = 0;
+        // Pick up savegames written since the menu was last up
+        if matches!(page, Page::Load | Page::Save) {
+            self.save_names = read_save_names();
+        }
+    }
+
+    fn item_count(&self) -> usize {
+        match self.page {
+            Page::Episode => self.menu_episodes,
+            _ => layout(self.page).items.len(),
+        }
+    }
+
+    // Handle a single key press, returning what the game should do about it
+    pub fn responder(&mut self, key: Key, options: &mut Options) -> MenuAction {
+        if let Some(prompt) = self.prompt {
+            return self.prompt_responder(prompt, key);
+        }
+        if let Some(slot) = self.save_entry {
+            return self.save_entry_responder(slot, key);
+        }
+
+        if !self.active {
+            if key == Key::Escape {
+                self.open();
+            }
+            return MenuAction::None;
+        }
+
+        let items = layout(self.page).items;
+        let count = self.item_count();
+
+        match key {
+            Key::Escape => self.close(),
+            Key::Backspace => match layout(self.page).parent {
+                // Doom II has no episode page to go back to
+                Some(Page::Episode) if self.commercial => self.set_page(Page::Main),
+                Some(parent) => self.set_page(parent),
+                None => self.close(),
+            },
+            Key::Down => loop {
+                self.item_on = (self.item_on + 1) % count;
+                if items[self.item_on].kind != ItemKind::Spacer {
+                    break;
+                }
+            },
+            Key::Up => loop {
+                self.item_on = (self.item_on + count - 1) % count;
+                if items[self.item_on].kind != ItemKind::Spacer {
+                    break;
+                }
+            },
+            Key::Left if items[self.item_on].kind == ItemKind::Slider => {
+                self.change_slider(options, false)
+            }
+            Key::Right if items[self.item_on].kind == ItemKind::Slider => {
+                self.change_slider(options, true)
+            }
+            Key::Enter => {
+                if items[self.item_on].kind == ItemKind::Slider {
+                    self.change_slider(options, true);
+                } else {
+                    return self.activate();
+                }
+            }
+            _ => {
+                // Jump to the next item whose hotkey matches
+                for i in 1..=count {
+                    let index = (self.item_on + i) % count;
+                    if items[index].hotkey == Some(key) {
+                        self.item_on = index;
+                        break;
+                    }
+                }
+            }
+        }
+
+        MenuAction::None
+    }
+
+    fn prompt_responder(&mut self, prompt: Prompt, key: Key) -> MenuAction {
+        let confirmed = key == Key::Y;
+        let any_key = matches!(prompt, Prompt::Shareware | Prompt::SaveDead);
+        if !any_key && !confirmed && key != Key::N && key != Key::Escape {
+            return MenuAction::None;
+        }
+
+        self.prompt = None;
+        match (prompt, confirmed) {
+            (Prompt::Quit, true) => MenuAction::Quit,
+            (Prompt::Nightmare, true) => {
+                self.close();
+                MenuAction::NewGame {
+                    episode: self.episode,
+                    skill: 4,
+                }
+            }
+            _ => MenuAction::None,
+        }
+    }
+
+    // Typing a savegame's description: Enter saves, Escape puts back the old one
+    fn save_entry_responder(&mut self, slot: usize, key: Key) -> MenuAction {
+        let name = &mut self.save_names[slot];
+        match key {
+            Key::Escape => {
+                *name = std::mem::take(&mut self.save_old);
+                self.save_entry = None;
+            }
+            Key::Enter if !name.is_empty() => {
+                self.save_entry = None;
+                self.close();
+                return MenuAction::SaveGame(slot);
+            }
+            Key::Backspace => {
+                name.pop();
+            }
+            _ => {
+                let c = if key == Key::Space {
+                    Some(' ')
+                } else {
+                    key_char(key)
+                };
+                if let Some(c) = c.filter(|_| name.len() < SAVE_STRING_SIZE - 1) {
+                    name.push(c.to_ascii_uppercase());
+                }
+            }
+        }
+        MenuAction::None
+    }
+
+    fn activate(&mut self) -> MenuAction {
+        match (self.page, self.item_on) {
+            (Page::Main, 0) => {
+                if self.commercial {
+                    self.episode = 0;
+                    self.set_page(Page::Skill);
+                    self.item_on = 2; // default to "Hurt me plenty"
+                } else {
+                    self.set_page(Page::Episode);
+                }
+            }
+            (Page::Main, 1) => self.set_page(Page::Options),
+            (Page::Main, 2) => self.set_page(Page::Load),
+            (Page::Main, 3) if !self.can_save => self.prompt = Some(Prompt::SaveDead),
+            (Page::Main, 3) => self.set_page(Page::Save),
+            (Page::Main, _) => self.prompt = Some(Prompt::Quit),
+            (Page::Episode, episode) => {
+                if episode >= self.available_episodes {
+                    self.prompt = Some(Prompt::Shareware);
+                } else {
+                    self.episode = episode;
+                    self.set_page(Page::Skill);
+                    self.item_on = 2;
+                }
+            }
+            (Page::Skill, 4) => self.prompt = Some(Prompt::Nightmare),
+            (Page::Skill, skill) => {
+                self.close();
+                return MenuAction::NewGame {
+                    episode: self.episode,
+                    skill,
+                };
+            }
+            (Page::Options, OPTIONS_MESSAGES) => return MenuAction::ToggleMessages,
+            (Page::Options, _) => {}
+            (Page::Load, slot) => {
+                if self.save_names[slot].is_empty() {
+                    return MenuAction::None;
+                }
+                self.close();
+                return MenuAction::LoadGame(slot);
+            }
+            // Type a description first, starting from the old one
+            (Page::Save, slot) => {
+                self.save_old = self.save_names[slot].clone();
+                self.save_entry = Some(slot);
+            }
+        }
+        MenuAction::None
+    }
+
+    fn change_slider(&mut self, options: &mut Options, increase: bool) {
+        let (value, min, max) = match self.item_on {
+            OPTIONS_SCREEN_SIZE => (&mut options.screen_size, SCREEN_SIZE_MIN, SCREEN_SIZE_MAX),
+            OPTIONS_MOUSE => (&mut options.mouse_sensitivity, 0, MOUSE_SENSITIVITY_MAX),
+            _ => return,
+        };
+
+        if increase && *value < max {
+            *value += 1;
+        } else if !increase && *value > min {
+            *value -= 1;
+        }
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], graphics: &Graphics, font: &Font, options: &Options) {
+        if let Some(prompt) = self.prompt {
+            let text = match prompt {
+                Prompt::Quit => QUIT_PROMPT,
+                Prompt::Nightmare => NIGHTMARE_PROMPT,
+                Prompt::Shareware => SHAREWARE_PROMPT,
+                Prompt::SaveDead => SAVEDEAD_PROMPT,
+            };
+            draw_centered_text(buffer, font, text);
+            return;
+        }
+
+        if !self.active {
+            return;
+        }
+
+        let page = layout(self.page);
+        for &(patch, label, x, y) in page.titles {
+            graphics.draw(buffer, font, patch, label, x, y);
+        }
+
+        for (i, item) in page.items.iter().take(self.item_count()).enumerate() {
+            let y = page.y + i as i32 * LINE_HEIGHT;
+            match self.page {
+                Page::Load | Page::Save => {
+                    self.draw_save_slot(buffer, graphics, font, i, page.x, y)
+                }
+                _ if item.kind != ItemKind::Spacer => {
+                    graphics.draw(buffer, font, item.patch, item.label, page.x, y)
+                }
+                _ => {}
+            }
+        }
+
+        if self.page == Page::Options {
+            let (patch, label) = if options.show_messages {
+                ("M_MSGON", "on")
+            } else {
+                ("M_MSGOFF", "off")
+            };
+            let y = page.y + OPTIONS_MESSAGES as i32 * LINE_HEIGHT;
+            graphics.draw(buffer, font, patch, label, page.x + 120, y);
+
+            let thermo_y = |item: usize| page.y + (item as i32 + 1) * LINE_HEIGHT;
+            draw_thermo(
+                buffer,
+                graphics,
+                font,
+                page.x,
+                thermo_y(OPTIONS_SCREEN_SIZE),
+                SCREEN_SIZE_MAX - SCREEN_SIZE_MIN + 1,
+                options.screen_size - SCREEN_SIZE_MIN,
+            );
+            draw_thermo(
+                buffer,
+                graphics,
+                font,
+                page.x,
+                thermo_y(OPTIONS_MOUSE),
+                MOUSE_SENSITIVITY_MAX + 1,
+                options.mouse_sensitivity,
+            );
+        }
+
+        // The skull cursor blinks between its two frames
+        let frame = self.opened_at.elapsed().as_millis() / SKULL_FRAME_MS % 2;
+        let skull = if frame == 0 { "M_SKULL1" } else { "M_SKULL2" };
+        graphics.draw(
+            buffer,
+            font,
+            skull,
+            ">",
+            page.x + SKULL_X_OFFSET,
+            page.y - 5 + self.item_on as i32 * LINE_HEIGHT,
+        );
+    }
+
+    fn draw_save_slot(
+        &self,
+        buffer: &mut [u32],
+        graphics: &Graphics,
+        font: &Font,
+        slot: usize,
+        x: i32,
+        y: i32,
+    ) {
+        graphics.draw(buffer, font, "M_LSLEFT", "", x - 8, y + 7);
+        for i in 0..SAVE_STRING_SIZE as i32 {
+            graphics.draw(buffer, font, "M_LSCNTR", "", x + i * 8, y + 7);
+        }
+        graphics.draw(
+            buffer,
+            font,
+            "M_LSRGHT",
+            "",
+            x + SAVE_STRING_SIZE as i32 * 8,
+            y + 7,
+        );
+
+        let name = &self.save_names[slot];
+        if self.save_entry == Some(slot) {
+            let text = format!("{}_", name);
+            font.draw_text(buffer, x, y + SCREEN_Y_OFFSET, &text);
+        } else if name.is_empty() {
+            font.draw_text(buffer, x, y + SCREEN_Y_OFFSET, "empty slot");
+        } else {
+            font.draw_text(buffer, x, y + SCREEN_Y_OFFSET, name);
+        }
+    }
+}
+
+// Prompts are drawn line by line, centered on the 320x200 screen
+fn draw_centered_text(buffer: &mut [u32], font: &Font, text: &str) {
+    let line_height = font.height + 1;
+    let total_height = text.lines().count() as i32 * line_height;
+    let mut y = 100 - total_height / 2 + SCREEN_Y_OFFSET;
+
+    for line in text.lines() {
+        font.draw_text(buffer, 160 - font.text_width(line) / 2, y, line);
+        y += line_height;
+    }
+}
+
+fn draw_thermo(
+    buffer: &mut [u32],
+    graphics: &Graphics,
+    font: &Font,
+    x: i32,
+    y: i32,
+    width: usize,
+    value: usize,
+) {
+    if graphics.get("M_THERMM").is_none() {
+        let bar: String = (0..width)
+            .map(|i| if i == value { '|' } else { '-' })
+            .collect();
+        font.draw_text(buffer, x, y + 4 + SCREEN_Y_OFFSET, &format!("[{}]", bar));
+        return;
+    }
+
+    graphics.draw(buffer, font, "M_THERML", "", x, y);
+    for i in 0..width as i32 {
+        graphics.draw(buffer, font, "M_THERMM", "", x + 8 + i * 8, y);
+    }
+    graphics.draw(buffer, font, "M_THERMR", "", x + 8 + width as i32 * 8, y);
+    graphics.draw(buffer, font, "M_THERMO", "", x + 8 + value as i32 * 8, y);
+}
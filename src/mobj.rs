@@ -0,0 +1,616 @@
+// Things in a level, like the original's mobjs: monsters, items, decorations
+// and projectiles, each stepping through the states from the info tables
+use crate::collision::{check_position, try_move, xy_move, Blocked, Mover, MAX_STEP};
+use crate::combat::MISSILE_RANGE;
+use crate::enemy::{approx_distance, point_to_angle, FLOAT_SPEED, NO_DIR};
+use crate::info::{
+    info, MobjType, StateId, MF_AMBUSH, MF_CORPSE, MF_COUNTKILL, MF_FLOAT, MF_INFLOAT, MF_MISSILE,
+    MF_NOCLIP, MF_NOGRAVITY, MF_SHADOW, MF_SHOOTABLE, MF_SKULLFLY, MF_SPAWNCEILING, S_NULL,
+};
+use crate::level::{Level, MapStart, MTF_AMBUSH, NIGHTMARE};
+use crate::wad_reader::Thing;
+
+// Tics a dead monster lies still before it may come back
+const RESPAWN_WAIT: i32 = 12 * 35;
+
+// Movement numbers shared with the player, in map units per tic
+const GRAVITY: f32 = 1.0;
+const FRICTION: f32 = 0xE800 as f32 / 65536.0;
+const STOP_SPEED: f32 = 0x1000 as f32 / 65536.0;
+const MAX_MOVE: f32 = 30.0;
+
+// What a monster is after: the player, or another thing it's fighting
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Player,
+    Mobj(usize),
+}
+
+// Where something is and how big, all that sight and range checks need
+#[derive(Clone, Copy)]
+pub struct Body {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub angle: f32,
+    pub flags: u32,
+    pub health: i32,
+}
+
+// The direction an angle faces, as (x, y)
+pub fn heading(angle: f32) -> (f32, f32) {
+    let radians = angle.to_radians();
+    (radians.sin(), radians.cos())
+}
+
+pub struct Mobj {
+    pub kind: MobjType,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    // Degrees, like Player::angle
+    pub angle: f32,
+    pub mom_x: f32,
+    pub mom_y: f32,
+    pub mom_z: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub flags: u32,
+    pub health: i32,
+    pub state: StateId,
+    // Tics left in the current state, -1 for ever
+    pub tics: i32,
+    // The floor and ceiling of the space it is in
+    pub floor_z: f32,
+    pub ceiling_z: f32,
+
+    // Monster thinking: who it's after, how long before it reacts or
+    // switches targets, and which way it's walking for how many steps
+    pub target: Option<Target>,
+    pub reaction_time: i32,
+    pub threshold: i32,
+    pub move_dir: usize,
+    pub move_count: i32,
+    // What a homing missile is homing in on, or an arch-vile's flame burns
+    pub tracer: Option<Target>,
+    // The map thing it was placed by, where it comes back to on Nightmare
+    pub spawn_point: Option<Thing>,
+}
+
+impl Mobj {
+    fn new(kind: MobjType, x: f32, y: f32, angle: f32) -> Mobj {
+        let tables = info();
+        let info = tables.mobj(kind);
+        let state = &tables.states[info.spawn_state];
+        Mobj {
+            kind,
+            x,
+            y,
+            z: 0.0,
+            angle,
+            mom_x: 0.0,
+            mom_y: 0.0,
+            mom_z: 0.0,
+            radius: info.radius,
+            height: info.height,
+            flags: info.flags,
+            health: info.spawn_health,
+            state: info.spawn_state,
+            tics: state.tics,
+            floor_z: 0.0,
+            ceiling_z: 0.0,
+            target: None,
+            reaction_time: info.reaction_time,
+            threshold: 0,
+            move_dir: NO_DIR,
+            move_count: 0,
+            tracer: None,
+            spawn_point: None,
+        }
+    }
+
+    fn on_ground(&self) -> bool {
+        self.z <= self.floor_z
+    }
+
+    pub fn body(&self) -> Body {
+        Body {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            radius: self.radius,
+            height: self.height,
+            angle: self.angle,
+            flags: self.flags,
+            health: self.health,
+        }
+    }
+}
+
+impl Level {
+    // Add a new thing standing on the floor, or hanging from the ceiling if
+    // that's where it belongs. Returns its index in Level::mobjs.
+    pub fn spawn_mobj(&mut self, kind: MobjType, x: f32, y: f32, angle: f32) -> usize {
+        let mut mobj = Mobj::new(kind, x, y, angle);
+        if let Some(sector) = self.sector_at(x, y) {
+            mobj.floor_z = sector.floor_height;
+            mobj.ceiling_z = sector.ceiling_height;
+        }
+        mobj.z = if mobj.flags & MF_SPAWNCEILING != 0 {
+            mobj.ceiling_z - mobj.height
+        } else {
+            mobj.floor_z
+        };
+        // Nightmare monsters don't hesitate
+        if self.skill == NIGHTMARE {
+            mobj.reaction_time = 0;
+        }
+        self.mobjs.push(Some(mobj));
+        self.mobjs.len() - 1
+    }
+
+    // Move a thing to a new state, running the state's action. States that
+    // last no time at all are passed straight through. Returns false if the
+    // thing went away.
+    pub fn set_mobj_state(&mut self, index: usize, mut state: StateId) -> bool {
+        loop {
+            if state == S_NULL {
+                self.mobjs[index] = None;
+                return false;
+            }
+            let next = &info().states[state];
+            let Some(mobj) = self.mobjs[index].as_mut() else {
+                return false;
+            };
+            mobj.state = state;
+            mobj.tics = next.tics;
+            if self.fast_monsters && is_fast_state(mobj.kind, state) {
+                mobj.tics >>= 1;
+            }
+            self.run_action(index, next.action);
+
+            match &self.mobjs[index] {
+                Some(mobj) if mobj.tics == 0 => state = next.next,
+                Some(_) => return true,
+                None => return false,
+            }
+        }
+    }
+
+    // Where a target is, if it's still around
+    pub fn body(&self, target: Target) -> Option<Body> {
+        match target {
+            Target::Player => self.player_body,
+            Target::Mobj(index) => self.mobjs.get(index)?.as_ref().map(Mobj::body),
+        }
+    }
+
+    // Everything that can be bumped into, shot or seen, the player included
+    pub fn bodies(&self) -> impl Iterator<Item = (Target, Body)> + '_ {
+        self.mobjs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, mobj)| Some((Target::Mobj(index), mobj.as_ref()?.body())))
+            .chain(self.player_body.map(|body| (Target::Player, body)))
+    }
+
+    // Run one tic for every thing in the level
+    pub fn tick_mobjs(&mut self) {
+        for index in 0..self.mobjs.len() {
+            let (moving, flying) = match &self.mobjs[index] {
+                Some(mobj) => (
+                    mobj.mom_x != 0.0 || mobj.mom_y != 0.0 || mobj.flags & MF_SKULLFLY != 0,
+                    mobj.flags & (MF_MISSILE | MF_SKULLFLY) != 0,
+                ),
+                None => continue,
+            };
+            if moving && flying {
+                self.missile_xy_movement(index);
+            } else if moving {
+                self.mobj_xy_movement(index);
+            }
+            self.mobj_z_movement(index);
+
+            let Some(mobj) = self.mobjs[index].as_mut() else {
+                continue;
+            };
+            // Things in a state that lasts for ever never change by themselves,
+            // though dead monsters can come back
+            if mobj.tics == -1 {
+                if self.respawn_monsters && mobj.flags & MF_COUNTKILL != 0 {
+                    mobj.move_count += 1;
+                    if mobj.move_count >= RESPAWN_WAIT
+                        && self.stats.time & 31 == 0
+                        && self.rng.next() <= 4
+                    {
+                        self.nightmare_respawn(index);
+                    }
+                }
+                continue;
+            }
+            mobj.tics -= 1;
+            if mobj.tics <= 0 {
+                let next = info().states[mobj.state].next;
+                self.set_mobj_state(index, next);
+            }
+        }
+    }
+
+    fn mobj_xy_movement(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let mover = Mover {
+            x: mobj.x,
+            y: mobj.y,
+            z: mobj.z,
+            radius: mobj.radius,
+            height: mobj.height,
+            who: Target::Mobj(index),
+        };
+        let mom = (
+            mobj.mom_x.clamp(-MAX_MOVE, MAX_MOVE),
+            mobj.mom_y.clamp(-MAX_MOVE, MAX_MOVE),
+        );
+        let noclip = mobj.flags & MF_NOCLIP != 0;
+        let ((x, y), (mom_x, mom_y), opening) = xy_move(self, &mover, mom);
+
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.x = x;
+        mobj.y = y;
+        mobj.mom_x = mom_x;
+        mobj.mom_y = mom_y;
+        if let Some(opening) = opening {
+            mobj.floor_z = opening.floor_z;
+            mobj.ceiling_z = opening.ceiling_z;
+        }
+        // Lines crossed can send it somewhere else entirely
+        if !noclip {
+            self.cross_special_lines((mover.x, mover.y), (x, y), mover.who);
+        }
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+
+        // Things in the air keep going, corpses sliding off a ledge keep
+        // sliding
+        if !mobj.on_ground() {
+            return;
+        }
+        if mobj.flags & MF_CORPSE != 0 && mobj.floor_z < mobj.z {
+            return;
+        }
+        if mobj.mom_x.abs() < STOP_SPEED && mobj.mom_y.abs() < STOP_SPEED {
+            mobj.mom_x = 0.0;
+            mobj.mom_y = 0.0;
+        } else {
+            mobj.mom_x *= FRICTION;
+            mobj.mom_y *= FRICTION;
+        }
+    }
+
+    // Fall under gravity and stay between the floor and ceiling. Floating
+    // monsters rise and sink towards their target's height.
+    fn mobj_z_movement(&mut self, index: usize) {
+        let target = self.mobjs[index]
+            .as_ref()
+            .and_then(|mobj| mobj.target)
+            .and_then(|target| self.body(target));
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.z += mobj.mom_z;
+
+        if let Some(target) = target {
+            if mobj.flags & MF_FLOAT != 0 && mobj.flags & MF_INFLOAT == 0 {
+                let distance = approx_distance(target.x - mobj.x, target.y - mobj.y);
+                let delta = target.z + mobj.height / 2.0 - mobj.z;
+                if delta < 0.0 && distance < -delta * 3.0 {
+                    mobj.z -= FLOAT_SPEED;
+                } else if delta > 0.0 && distance < delta * 3.0 {
+                    mobj.z += FLOAT_SPEED;
+                }
+            }
+        }
+
+        // Missiles blow up on the floor and ceiling, charging lost souls
+        // bounce off them
+        let missile = mobj.flags & MF_MISSILE != 0 && mobj.flags & MF_NOCLIP == 0;
+        let mut explode = false;
+        if mobj.z <= mobj.floor_z {
+            if mobj.flags & MF_SKULLFLY != 0 {
+                mobj.mom_z = -mobj.mom_z;
+            }
+            mobj.z = mobj.floor_z;
+            mobj.mom_z = mobj.mom_z.max(0.0);
+            explode = missile;
+        } else if mobj.flags & MF_NOGRAVITY == 0 {
+            mobj.mom_z -= if mobj.mom_z == 0.0 {
+                GRAVITY * 2.0
+            } else {
+                GRAVITY
+            };
+        }
+
+        if mobj.z + mobj.height > mobj.ceiling_z {
+            mobj.mom_z = mobj.mom_z.min(0.0);
+            mobj.z = mobj.ceiling_z - mobj.height;
+            if mobj.flags & MF_SKULLFLY != 0 {
+                mobj.mom_z = -mobj.mom_z;
+            }
+            explode = missile;
+        }
+        if explode {
+            self.explode_missile(index);
+        }
+    }
+
+    // Fly straight on without sliding, in steps short enough not to skip
+    // through anything. Hitting something hurts it and ends the flight.
+    fn missile_xy_movement(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (mut x, mut y) = (mobj.x, mobj.y);
+        let (z, radius, height) = (mobj.z, mobj.radius, mobj.height);
+        let mom_x = mobj.mom_x.clamp(-MAX_MOVE, MAX_MOVE);
+        let mom_y = mobj.mom_y.clamp(-MAX_MOVE, MAX_MOVE);
+        let noclip = mobj.flags & MF_NOCLIP != 0;
+        let steps = (mom_x.abs().max(mom_y.abs()) / MAX_STEP).ceil().max(1.0);
+        let (step_x, step_y) = (mom_x / steps, mom_y / steps);
+
+        let mut blocked = None;
+        for _ in 0..steps as usize {
+            // Spawn cubes fly straight through walls and things
+            if noclip {
+                x += step_x;
+                y += step_y;
+                continue;
+            }
+            match try_move(
+                self,
+                x + step_x,
+                y + step_y,
+                z,
+                radius,
+                height,
+                Target::Mobj(index),
+            ) {
+                Ok(opening) => {
+                    x += step_x;
+                    y += step_y;
+                    if let Some(mobj) = self.mobjs[index].as_mut() {
+                        mobj.floor_z = opening.floor_z;
+                        mobj.ceiling_z = opening.ceiling_z;
+                    }
+                }
+                Err(why) => {
+                    blocked = Some(why);
+                    break;
+                }
+            }
+        }
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.x = x;
+            mobj.y = y;
+        }
+        if let Some(blocked) = blocked {
+            self.missile_hit(index, blocked);
+        }
+    }
+
+    // A missile or charging lost soul ran into something
+    fn missile_hit(&mut self, index: usize, blocked: Blocked) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (kind, shooter, body) = (mobj.kind, mobj.target, mobj.body());
+        let damage = info().mobj(kind).damage;
+        let charging = mobj.flags & MF_SKULLFLY != 0;
+
+        if let Blocked::Thing(victim) = blocked {
+            let victim_body = self.body(victim);
+            // Monsters' missiles don't hurt their own kind, hell knights and
+            // barons counting as one
+            let same_kind = match (
+                shooter.and_then(|shooter| self.kind_of(shooter)),
+                self.kind_of(victim),
+            ) {
+                (_, Some(MobjType::Player)) => false,
+                (Some(MobjType::Knight), Some(MobjType::Bruiser))
+                | (Some(MobjType::Bruiser), Some(MobjType::Knight)) => true,
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            };
+            let shootable = victim_body.is_some_and(|body| body.flags & MF_SHOOTABLE != 0);
+            if shootable && (charging || !same_kind) {
+                let damage = (self.rng.next() % 8 + 1) * damage;
+                let source = if charging {
+                    Some(Target::Mobj(index))
+                } else {
+                    shooter
+                };
+                self.damage_mobj(victim, Some(body), source, damage);
+            }
+        }
+
+        if charging {
+            // The lost soul stops dead and gets its bearings
+            let Some(mobj) = self.mobjs[index].as_mut() else {
+                return;
+            };
+            mobj.flags &= !MF_SKULLFLY;
+            mobj.mom_x = 0.0;
+            mobj.mom_y = 0.0;
+            mobj.mom_z = 0.0;
+            self.set_mobj_state(index, info().mobj(kind).spawn_state);
+        } else {
+            self.explode_missile(index);
+        }
+    }
+
+    // What kind of thing a target is, the player counting as MobjType::Player
+    pub fn kind_of(&self, target: Target) -> Option<MobjType> {
+        match target {
+            Target::Player => Some(MobjType::Player),
+            Target::Mobj(index) => self.mobjs.get(index)?.as_ref().map(|mobj| mobj.kind),
+        }
+    }
+
+    // Stop a missile and play its explosion
+    pub fn explode_missile(&mut self, index: usize) {
+        let Some(mobj) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        mobj.mom_x = 0.0;
+        mobj.mom_y = 0.0;
+        mobj.mom_z = 0.0;
+        mobj.flags &= !MF_MISSILE;
+        let death_state = info().mobj(mobj.kind).death_state;
+        if self.set_mobj_state(index, death_state) {
+            self.shorten_tics(index);
+        }
+    }
+
+    // Knock up to 3 tics off the current state so things don't all animate
+    // in step
+    pub fn shorten_tics(&mut self, index: usize) {
+        let cut = self.rng.next() & 3;
+        if let Some(mobj) = self.mobjs[index].as_mut() {
+            mobj.tics = (mobj.tics - cut).max(1);
+        }
+    }
+
+    // Fire a monster's missile from its chest at `dest`. Returns the
+    // missile's index.
+    pub fn spawn_missile(&mut self, source: usize, dest: Target, kind: MobjType) -> Option<usize> {
+        let (Some(mobj), Some(dest)) = (&self.mobjs[source], self.body(dest)) else {
+            return None;
+        };
+        let (x, y, z, source_z) = (mobj.x, mobj.y, mobj.z + 32.0, mobj.z);
+        let mut angle = point_to_angle((x, y), (dest.x, dest.y));
+        // Partly invisible targets are hard to aim at
+        if dest.flags & MF_SHADOW != 0 {
+            let wobble = self.rng.next() - self.rng.next();
+            angle = (angle + wobble as f32 * 360.0 / 2048.0).rem_euclid(360.0);
+        }
+
+        let speed = self.missile_speed(kind);
+        let time = (approx_distance(dest.x - x, dest.y - y) / speed).max(1.0);
+        let mom_z = (dest.z - source_z) / time;
+        let missile = self.launch(kind, (x, y, z), angle, mom_z, Target::Mobj(source));
+        self.check_missile_spawn(missile);
+        Some(missile)
+    }
+
+    // Fire the player's missile, aimed up or down at whatever's roughly in
+    // front of them
+    pub fn spawn_player_missile(&mut self, kind: MobjType, angle: f32) {
+        let Some(player) = self.player_body else {
+            return;
+        };
+        let mut aim = (angle, 0.0);
+        for delta in [0.0, -5.625, 5.625] {
+            let try_angle = (angle + delta).rem_euclid(360.0);
+            let (slope, target) =
+                self.aim_line_attack(Target::Player, try_angle, MISSILE_RANGE / 2.0);
+            if target.is_some() {
+                aim = (try_angle, slope);
+                break;
+            }
+        }
+
+        let (x, y, z) = (player.x, player.y, player.z + 32.0);
+        let mom_z = info().mobj(kind).speed * aim.1;
+        let missile = self.launch(kind, (x, y, z), aim.0, mom_z, Target::Player);
+        self.check_missile_spawn(missile);
+    }
+
+    fn launch(
+        &mut self,
+        kind: MobjType,
+        (x, y, z): (f32, f32, f32),
+        angle: f32,
+        mom_z: f32,
+        shooter: Target,
+    ) -> usize {
+        let index = self.spawn_mobj(kind, x, y, angle);
+        let speed = self.missile_speed(kind);
+        let (dx, dy) = heading(angle);
+        if let Some(missile) = self.mobjs[index].as_mut() {
+            missile.z = z;
+            missile.target = Some(shooter);
+            missile.mom_x = speed * dx;
+            missile.mom_y = speed * dy;
+            missile.mom_z = mom_z;
+        }
+        index
+    }
+
+    // Missiles start half a step out, so one fired point blank at a wall
+    // blows up at once
+    fn check_missile_spawn(&mut self, index: usize) {
+        self.shorten_tics(index);
+        let Some(missile) = self.mobjs[index].as_mut() else {
+            return;
+        };
+        missile.x += missile.mom_x / 2.0;
+        missile.y += missile.mom_y / 2.0;
+        missile.z += missile.mom_z / 2.0;
+        let (x, y, radius) = (missile.x, missile.y, missile.radius);
+        if check_position(self, x, y, radius, Target::Mobj(index)).is_err() {
+            self.explode_missile(index);
+        }
+    }
+
+    // Imp, cacodemon and baron fireballs fly faster on Nightmare and -fast
+    fn missile_speed(&self, kind: MobjType) -> f32 {
+        match kind {
+            MobjType::TroopShot | MobjType::HeadShot | MobjType::BruiserShot
+                if self.fast_monsters =>
+            {
+                20.0
+            }
+            _ => info().mobj(kind).speed,
+        }
+    }
+
+    // A dead monster comes back where it was first placed, in a flash of
+    // teleport fog, unless something's standing there
+    fn nightmare_respawn(&mut self, index: usize) {
+        let Some(mobj) = &self.mobjs[index] else {
+            return;
+        };
+        let (kind, old_x, old_y) = (mobj.kind, mobj.x, mobj.y);
+        let Some(thing) = mobj.spawn_point.clone() else {
+            return;
+        };
+        let start = MapStart::from_thing(&thing);
+        if check_position(self, start.x, start.y, mobj.radius, Target::Mobj(index)).is_err() {
+            return;
+        }
+
+        self.spawn_mobj(MobjType::TeleportFog, old_x, old_y, 0.0);
+        self.spawn_mobj(MobjType::TeleportFog, start.x, start.y, 0.0);
+        let respawned = self.spawn_mobj(kind, start.x, start.y, start.angle);
+        if let Some(mobj) = self.mobjs[respawned].as_mut() {
+            if thing.flags & MTF_AMBUSH != 0 {
+                mobj.flags |= MF_AMBUSH;
+            }
+            mobj.reaction_time = 18;
+            mobj.spawn_point = Some(thing);
+        }
+        self.mobjs[index] = None;
+    }
+}
+
+// Demons run, bite and flinch twice as fast on Nightmare and -fast
+fn is_fast_state(kind: MobjType, state: StateId) -> bool {
+    let info = info().mobj(kind);
+    matches!(kind, MobjType::Sergeant | MobjType::Shadows)
+        && (info.see_state..info.death_state).contains(&state)
+}
@@ -0,0 +1,415 @@
+// Picking things up, like the original's P_TouchSpecialThing: health,
+// armor, ammo, weapons, keys and powerups, each with its message
+use crate::game::Player;
+use crate::info::{
+    info, AmmoType, MobjType, WeaponType, MF_COUNTITEM, MF_DROPPED, MF_SHADOW, MF_SPECIAL,
+};
+use crate::level::{Level, BABY, NIGHTMARE};
+use crate::strings::*;
+use crate::weapon::WeaponSet;
+
+pub const MAX_HEALTH: i32 = 100;
+// Bonuses and spheres go beyond MAX_HEALTH up to this
+const MAX_SOUL_HEALTH: i32 = 200;
+// The most ammo that can be carried without a backpack, indexed by AmmoType
+pub const MAX_AMMO: [i32; 4] = [200, 50, 300, 50];
+// Ammo in a clip, a few shells, a rocket and a cell
+const CLIP_AMMO: [i32; 4] = [10, 4, 20, 1];
+// How much each pickup adds to the gold flash
+const BONUS_ADD: i32 = 6;
+
+// How long the timed powerups last
+const INVULN_TICS: i32 = 30 * 35;
+const INVIS_TICS: i32 = 60 * 35;
+const INFRA_TICS: i32 = 120 * 35;
+const IRON_TICS: i32 = 60 * 35;
+
+// The screen flash palettes in PLAYPAL
+const START_RED_PALS: usize = 1;
+const NUM_RED_PALS: usize = 8;
+const START_BONUS_PALS: usize = 9;
+const NUM_BONUS_PALS: usize = 4;
+const RADIATION_PAL: usize = 13;
+
+// Keys, in the original's order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Card {
+    Blue,
+    Yellow,
+    Red,
+    BlueSkull,
+    YellowSkull,
+    RedSkull,
+}
+
+// Powerups, in the original's order. Player::powers holds the tics left,
+// except berserk, which counts up from pickup for the fading red flash, and
+// the computer map, which is just had or not.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Power {
+    Invulnerability,
+    Strength,
+    Invisibility,
+    IronFeet,
+    AllMap,
+    Infrared,
+}
+
+impl Player {
+    // Heal up to MAX_HEALTH. False if already there.
+    fn give_body(&mut self, amount: i32) -> bool {
+        if self.health >= MAX_HEALTH {
+            return false;
+        }
+        self.health = (self.health + amount).min(MAX_HEALTH);
+        true
+    }
+
+    // Green armor is worth 100 points, blue 200. False if what's worn is
+    // already as good.
+    fn give_armor(&mut self, kind: i32) -> bool {
+        let points = kind * 100;
+        if self.armor.points >= points {
+            return false;
+        }
+        self.armor.kind = kind;
+        self.armor.points = points;
+        true
+    }
+
+    fn give_card(&mut self, card: Card) {
+        if self.cards[card as usize] {
+            return;
+        }
+        self.bonus_count = BONUS_ADD;
+        self.cards[card as usize] = true;
+    }
+
+    pub fn has_power(&self, power: Power) -> bool {
+        self.powers[power as usize] > 0
+    }
+
+    pub fn give_power(&mut self, power: Power) -> bool {
+        let tics = match power {
+            Power::Invulnerability => INVULN_TICS,
+            Power::Invisibility => INVIS_TICS,
+            Power::Infrared => INFRA_TICS,
+            Power::IronFeet => IRON_TICS,
+            Power::Strength => {
+                self.give_body(100);
+                1
+            }
+            Power::AllMap if self.has_power(power) => return false,
+            Power::AllMap => 1,
+        };
+        self.powers[power as usize] = tics;
+        true
+    }
+
+    // Add `clips` clips' worth of ammo, or half a clip for none, as dropped
+    // by monsters. Running dry and then finding ammo switches to a weapon
+    // that uses it.
+    fn give_ammo(&mut self, ammo: AmmoType, clips: i32, skill: usize) -> bool {
+        let index = ammo as usize;
+        if self.ammo[index] == self.max_ammo[index] {
+            return false;
+        }
+        let mut amount = if clips > 0 {
+            clips * CLIP_AMMO[index]
+        } else {
+            CLIP_AMMO[index] / 2
+        };
+        // The easiest and hardest skills give double
+        if skill == BABY || skill == NIGHTMARE {
+            amount *= 2;
+        }
+        let old = self.ammo[index];
+        self.ammo[index] = (old + amount).min(self.max_ammo[index]);
+        if old != 0 {
+            return true;
+        }
+
+        let ready = self.ready_weapon;
+        let owns = |weapon: WeaponType| self.weapon_owned[weapon as usize];
+        let better = match ammo {
+            AmmoType::Clip if ready == WeaponType::Fist => Some(if owns(WeaponType::Chaingun) {
+                WeaponType::Chaingun
+            } else {
+                WeaponType::Pistol
+            }),
+            AmmoType::Shell
+                if matches!(ready, WeaponType::Fist | WeaponType::Pistol)
+                    && owns(WeaponType::Shotgun) =>
+            {
+                Some(WeaponType::Shotgun)
+            }
+            AmmoType::Cell
+                if matches!(ready, WeaponType::Fist | WeaponType::Pistol)
+                    && owns(WeaponType::Plasma) =>
+            {
+                Some(WeaponType::Plasma)
+            }
+            AmmoType::Missile if ready == WeaponType::Fist && owns(WeaponType::Missile) => {
+                Some(WeaponType::Missile)
+            }
+            _ => None,
+        };
+        if better.is_some() {
+            self.pending_weapon = better;
+        }
+        true
+    }
+
+    // A new weapon is switched to straight away. Weapons come loaded with
+    // two clips, or one when dropped by a monster.
+    fn give_weapon(&mut self, weapon: WeaponType, dropped: bool, skill: usize) -> bool {
+        let gave_ammo = match info().weapon(weapon).ammo {
+            Some(ammo) => self.give_ammo(ammo, if dropped { 1 } else { 2 }, skill),
+            None => false,
+        };
+        if self.weapon_owned[weapon as usize] {
+            return gave_ammo;
+        }
+        self.weapon_owned[weapon as usize] = true;
+        self.pending_weapon = Some(weapon);
+        true
+    }
+
+    // Pick up whatever the player is touching. Returns the messages to show.
+    pub fn touch_specials(&mut self, level: &mut Level, set: WeaponSet) -> Vec<&'static str> {
+        let mut messages = Vec::new();
+        if self.health <= 0 {
+            return messages;
+        }
+        let body = self.body();
+        for index in 0..level.mobjs.len() {
+            let Some(mobj) = &level.mobjs[index] else {
+                continue;
+            };
+            let reach = mobj.radius + body.radius;
+            if mobj.flags & MF_SPECIAL == 0
+                || (mobj.x - body.x).abs() >= reach
+                || (mobj.y - body.y).abs() >= reach
+            {
+                continue;
+            }
+            // Out of reach above or below
+            let delta = mobj.z - body.z;
+            if delta > body.height || delta < -8.0 {
+                continue;
+            }
+
+            let (kind, flags) = (mobj.kind, mobj.flags);
+            let dropped = flags & MF_DROPPED != 0;
+            let Some(message) = self.touch_special(kind, dropped, level.skill, set) else {
+                continue;
+            };
+            if flags & MF_COUNTITEM != 0 {
+                level.stats.items += 1;
+            }
+            level.mobjs[index] = None;
+            self.bonus_count += BONUS_ADD;
+            messages.extend(message);
+        }
+        messages
+    }
+
+    // Take one thing if it's any use. None if it's left where it is,
+    // otherwise the message to show, if there is one.
+    fn touch_special(
+        &mut self,
+        kind: MobjType,
+        dropped: bool,
+        skill: usize,
+        set: WeaponSet,
+    ) -> Option<Option<&'static str>> {
+        let taken = |took: bool, message: &'static str| took.then_some(Some(message));
+        match kind {
+            MobjType::GreenArmor => taken(self.give_armor(1), GOTARMOR),
+            MobjType::BlueArmor => taken(self.give_armor(2), GOTMEGA),
+
+            // Bonuses go past the usual limits
+            MobjType::HealthBonus => {
+                self.health = (self.health + 1).min(MAX_SOUL_HEALTH);
+                Some(Some(GOTHTHBONUS))
+            }
+            MobjType::ArmorBonus => {
+                self.armor.points = (self.armor.points + 1).min(MAX_SOUL_HEALTH);
+                if self.armor.kind == 0 {
+                    self.armor.kind = 1;
+                }
+                Some(Some(GOTARMBONUS))
+            }
+            MobjType::SoulSphere => {
+                self.health = (self.health + 100).min(MAX_SOUL_HEALTH);
+                Some(Some(GOTSUPER))
+            }
+            MobjType::MegaSphere => {
+                if !set.commercial {
+                    return None;
+                }
+                self.health = MAX_SOUL_HEALTH;
+                self.give_armor(2);
+                Some(Some(GOTMSPHERE))
+            }
+
+            // Keys are always taken, but only news the first time
+            MobjType::BlueCard
+            | MobjType::YellowCard
+            | MobjType::RedCard
+            | MobjType::BlueSkull
+            | MobjType::YellowSkull
+            | MobjType::RedSkull => {
+                let (card, message) = match kind {
+                    MobjType::BlueCard => (Card::Blue, GOTBLUECARD),
+                    MobjType::YellowCard => (Card::Yellow, GOTYELWCARD),
+                    MobjType::RedCard => (Card::Red, GOTREDCARD),
+                    MobjType::BlueSkull => (Card::BlueSkull, GOTBLUESKUL),
+                    MobjType::YellowSkull => (Card::YellowSkull, GOTYELWSKUL),
+                    _ => (Card::RedSkull, GOTREDSKULL),
+                };
+                let news = !self.cards[card as usize];
+                self.give_card(card);
+                Some(news.then_some(message))
+            }
+
+            MobjType::Stimpack => taken(self.give_body(10), GOTSTIM),
+            MobjType::Medikit => {
+                if !self.give_body(25) {
+                    return None;
+                }
+                // Checked after healing, as the original does
+                Some(Some(if self.health < 25 {
+                    GOTMEDINEED
+                } else {
+                    GOTMEDIKIT
+                }))
+            }
+
+            MobjType::Invulnerability => taken(self.give_power(Power::Invulnerability), GOTINVUL),
+            MobjType::Berserk => {
+                self.give_power(Power::Strength);
+                if self.ready_weapon != WeaponType::Fist {
+                    self.pending_weapon = Some(WeaponType::Fist);
+                }
+                Some(Some(GOTBERSERK))
+            }
+            MobjType::Invisibility => taken(self.give_power(Power::Invisibility), GOTINVIS),
+            MobjType::RadSuit => taken(self.give_power(Power::IronFeet), GOTSUIT),
+            MobjType::AllMap => taken(self.give_power(Power::AllMap), GOTMAP),
+            MobjType::LightAmp => taken(self.give_power(Power::Infrared), GOTVISOR),
+
+            MobjType::Clip => {
+                let clips = if dropped { 0 } else { 1 };
+                taken(self.give_ammo(AmmoType::Clip, clips, skill), GOTCLIP)
+            }
+            MobjType::ClipBox => taken(self.give_ammo(AmmoType::Clip, 5, skill), GOTCLIPBOX),
+            MobjType::RocketAmmo => taken(self.give_ammo(AmmoType::Missile, 1, skill), GOTROCKET),
+            MobjType::RocketBox => taken(self.give_ammo(AmmoType::Missile, 5, skill), GOTROCKBOX),
+            MobjType::Cell => taken(self.give_ammo(AmmoType::Cell, 1, skill), GOTCELL),
+            MobjType::CellPack => taken(self.give_ammo(AmmoType::Cell, 5, skill), GOTCELLBOX),
+            MobjType::Shells => taken(self.give_ammo(AmmoType::Shell, 1, skill), GOTSHELLS),
+            MobjType::ShellBox => taken(self.give_ammo(AmmoType::Shell, 5, skill), GOTSHELLBOX),
+            MobjType::Backpack => {
+                if !self.backpack {
+                    self.max_ammo = MAX_AMMO.map(|max| max * 2);
+                    self.backpack = true;
+                }
+                for ammo in [
+                    AmmoType::Clip,
+                    AmmoType::Shell,
+                    AmmoType::Cell,
+                    AmmoType::Missile,
+                ] {
+                    self.give_ammo(ammo, 1, skill);
+                }
+                Some(Some(GOTBACKPACK))
+            }
+
+            MobjType::BfgWeapon => {
+                taken(self.give_weapon(WeaponType::Bfg, false, skill), GOTBFG9000)
+            }
+            MobjType::Chaingun => taken(
+                self.give_weapon(WeaponType::Chaingun, dropped, skill),
+                GOTCHAINGUN,
+            ),
+            MobjType::Chainsaw => taken(
+                self.give_weapon(WeaponType::Chainsaw, false, skill),
+                GOTCHAINSAW,
+            ),
+            MobjType::Launcher => taken(
+                self.give_weapon(WeaponType::Missile, false, skill),
+                GOTLAUNCHER,
+            ),
+            MobjType::PlasmaGun => taken(
+                self.give_weapon(WeaponType::Plasma, false, skill),
+                GOTPLASMA,
+            ),
+            MobjType::Shotgun => taken(
+                self.give_weapon(WeaponType::Shotgun, dropped, skill),
+                GOTSHOTGUN,
+            ),
+            MobjType::SuperShotgun => taken(
+                self.give_weapon(WeaponType::SuperShotgun, dropped, skill),
+                GOTSHOTGUN2,
+            ),
+            // Every MF_SPECIAL thing is handled above, nothing else is touched
+            _ => None,
+        }
+    }
+
+    // Count down the timed powerups and the screen flashes
+    pub fn tick_powers(&mut self) {
+        for power in [
+            Power::Invulnerability,
+            Power::Invisibility,
+            Power::Infrared,
+            Power::IronFeet,
+        ] {
+            let tics = &mut self.powers[power as usize];
+            *tics = (*tics - 1).max(0);
+        }
+        if self.has_power(Power::Strength) {
+            self.powers[Power::Strength as usize] += 1;
+        }
+        self.damage_count = (self.damage_count - 1).max(0);
+        self.bonus_count = (self.bonus_count - 1).max(0);
+    }
+
+    // Which of the PLAYPAL palettes to show, like the original's status bar:
+    // red for pain and berserk, gold for pickups, green for the radiation suit
+    pub fn palette_index(&self) -> usize {
+        let mut red = self.damage_count;
+        if self.has_power(Power::Strength) {
+            // Berserk's red fades over the first few seconds
+            red = red.max(12 - (self.powers[Power::Strength as usize] >> 6));
+        }
+        let iron = self.powers[Power::IronFeet as usize];
+        if red > 0 {
+            (((red + 7) >> 3) as usize).min(NUM_RED_PALS - 1) + START_RED_PALS
+        } else if self.bonus_count > 0 {
+            (((self.bonus_count + 7) >> 3) as usize).min(NUM_BONUS_PALS - 1) + START_BONUS_PALS
+        } else if iron > 4 * 32 || iron & 8 != 0 {
+            RADIATION_PAL
+        } else {
+            0
+        }
+    }
+
+    // Flags the player's body gets from powerups
+    pub fn power_flags(&self) -> u32 {
+        if self.has_power(Power::Invisibility) {
+            MF_SHADOW
+        } else {
+            0
+        }
+    }
+
+    // Leaving a level: powerups and keys stay behind, the rest is carried on
+    pub fn finish_level(&mut self) {
+        self.powers = [0; 6];
+        self.cards = [false; 6];
+        self.damage_count = 0;
+        self.bonus_count = 0;
+    }
+}
@@ -0,0 +1,189 @@
+// Lifts and moving platforms, like the original's p_plats.c: floors that go
+// down, wait and come back up, or go up and down forever
+use crate::floors::{MoveResult, Plane};
+use crate::level::Level;
+use crate::specials::SectorThinker;
+
+const PLAT_SPEED: f32 = 1.0;
+// Tics a lift waits at the bottom
+const PLAT_WAIT: i32 = 35 * 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlatKind {
+    // Up and down until stopped
+    PerpetualRaise,
+    // The usual lift
+    DownWaitUpStay,
+    BlazeDownWaitUpStay,
+    // Up by a set amount, taking the floor of the line's sector
+    RaiseAndChange,
+    // Up to the next floor, taking the floor of the line's sector
+    RaiseToNearestAndChange,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlatStatus {
+    Up,
+    Down,
+    Waiting,
+    // Stopped by a switch or line until set going again
+    InStasis,
+}
+
+pub struct Plat {
+    pub kind: PlatKind,
+    pub sector: usize,
+    pub speed: f32,
+    pub low: f32,
+    pub high: f32,
+    pub wait: i32,
+    pub count: i32,
+    pub status: PlatStatus,
+    pub old_status: PlatStatus,
+    pub crush: bool,
+    pub tag: i16,
+}
+
+impl Level {
+    // Start lifts in every sector tagged `tag`. `amount` is how far the
+    // RaiseAndChange kind goes up, `front` the sector it takes its floor
+    // from.
+    pub fn do_plat(&mut self, tag: i16, kind: PlatKind, amount: f32, front: Option<usize>) -> bool {
+        // Stopped perpetual lifts start again
+        if kind == PlatKind::PerpetualRaise {
+            self.activate_plats_in_stasis(tag);
+        }
+
+        let mut started = false;
+        let mut search = None;
+        while let Some(sector) = self.find_sector_from_tag(tag, search) {
+            search = Some(sector);
+            if self.sector_thinkers[sector].is_some() {
+                continue;
+            }
+            started = true;
+
+            let floor_height = self.sectors[sector].floor_height;
+            let mut plat = Plat {
+                kind,
+                sector,
+                speed: PLAT_SPEED,
+                low: floor_height,
+                high: floor_height,
+                wait: 0,
+                count: 0,
+                status: PlatStatus::Up,
+                old_status: PlatStatus::Up,
+                crush: false,
+                tag,
+            };
+            match kind {
+                PlatKind::RaiseToNearestAndChange | PlatKind::RaiseAndChange => {
+                    plat.speed = PLAT_SPEED / 2.0;
+                    if let Some(front) = front {
+                        let texture = self.sectors[front].floor_texture.clone();
+                        self.sectors[sector].floor_texture = texture;
+                    }
+                    if kind == PlatKind::RaiseToNearestAndChange {
+                        plat.high = self.next_highest_floor(sector, floor_height);
+                        self.sectors[sector].special = 0;
+                    } else {
+                        plat.high = floor_height + amount;
+                    }
+                }
+                PlatKind::DownWaitUpStay | PlatKind::BlazeDownWaitUpStay => {
+                    plat.speed = match kind {
+                        PlatKind::DownWaitUpStay => PLAT_SPEED * 4.0,
+                        _ => PLAT_SPEED * 8.0,
+                    };
+                    plat.low = self.lowest_floor_surrounding(sector).min(floor_height);
+                    plat.wait = PLAT_WAIT;
+                    plat.status = PlatStatus::Down;
+                }
+                PlatKind::PerpetualRaise => {
+                    plat.speed = PLAT_SPEED;
+                    plat.low = self.lowest_floor_surrounding(sector).min(floor_height);
+                    plat.high = self.highest_floor_surrounding(sector).max(floor_height);
+                    plat.wait = PLAT_WAIT;
+                    plat.status = if self.rng.next() & 1 == 0 {
+                        PlatStatus::Up
+                    } else {
+                        PlatStatus::Down
+                    };
+                }
+            }
+            self.add_thinker(sector, SectorThinker::Plat(plat));
+        }
+        started
+    }
+
+    fn activate_plats_in_stasis(&mut self, tag: i16) {
+        for thinker in self.thinkers.iter_mut().flatten() {
+            if let SectorThinker::Plat(plat) = thinker {
+                if plat.tag == tag && plat.status == PlatStatus::InStasis {
+                    plat.status = plat.old_status;
+                }
+            }
+        }
+    }
+
+    // Stop the moving lifts tagged `tag` where they are
+    pub fn stop_plat(&mut self, tag: i16) -> bool {
+        for thinker in self.thinkers.iter_mut().flatten() {
+            if let SectorThinker::Plat(plat) = thinker {
+                if plat.tag == tag && plat.status != PlatStatus::InStasis {
+                    plat.old_status = plat.status;
+                    plat.status = PlatStatus::InStasis;
+                }
+            }
+        }
+        true
+    }
+
+    // Returns false once the lift is back up for good
+    pub fn tick_plat(&mut self, plat: &mut Plat) -> bool {
+        match plat.status {
+            PlatStatus::Up => {
+                let result = self.move_plane(
+                    plat.sector,
+                    plat.speed,
+                    plat.high,
+                    plat.crush,
+                    Plane::Floor,
+                    1,
+                );
+                if result == MoveResult::Crushed && !plat.crush {
+                    // Something's in the way, go back down
+                    plat.count = plat.wait;
+                    plat.status = PlatStatus::Down;
+                } else if result == MoveResult::PastDest {
+                    plat.count = plat.wait;
+                    plat.status = PlatStatus::Waiting;
+                    if plat.kind != PlatKind::PerpetualRaise {
+                        return false;
+                    }
+                }
+            }
+            PlatStatus::Down => {
+                let result =
+                    self.move_plane(plat.sector, plat.speed, plat.low, false, Plane::Floor, -1);
+                if result == MoveResult::PastDest {
+                    plat.count = plat.wait;
+                    plat.status = PlatStatus::Waiting;
+                }
+            }
+            PlatStatus::Waiting => {
+                plat.count -= 1;
+                if plat.count == 0 {
+                    plat.status = if self.sectors[plat.sector].floor_height == plat.low {
+                        PlatStatus::Up
+                    } else {
+                        PlatStatus::Down
+                    };
+                }
+            }
+            PlatStatus::InStasis => {}
+        }
+        true
+    }
+}
@@ -1,16 +1,24 @@
 pub const WIDTH: usize = 640;
 pub const HEIGHT: usize = 640;
+// Doom's 2D graphics are laid out on a 320x200 screen, we draw them doubled
+pub const SCALE: usize = 2;
+// Vertical offset that centers a 320x200 layout on our taller screen
+pub const SCREEN_Y_OFFSET: i32 = ((HEIGHT / SCALE) as i32 - 200) / 2;
+use std::collections::HashMap;
 use std::f32::consts::PI;
 
-use crate::wad_reader::LineDef;
+use crate::font::Font;
+use crate::info::{info, S_NULL};
+use crate::specials::ML_DONTDRAW;
+use crate::wad_reader::{LineDef, Patch, WadData, FLAT_SIZE};
+use crate::weapon::{psprite_name, Psprite};
 
-fn clear_buffer(buffer: &mut Vec<u32>) {
-    for i in 0..WIDTH * HEIGHT {
-        buffer[i] = 0xff000000;
-    }
+#[allow(dead_code)]
+fn clear_buffer(buffer: &mut [u32]) {
+    buffer[..WIDTH * HEIGHT].fill(0xff000000);
 }
 
-pub fn draw_line(buffer: &mut Vec<u32>, x1: i32, y1: i32, x2: i32, y2: i32, color: u32) {
+pub fn draw_line(buffer: &mut [u32], x1: i32, y1: i32, x2: i32, y2: i32, color: u32) {
     // clear_buffer(buffer);
     let dx = (x2 - x1).abs();
     let dy = (y2 - y1).abs(); // delta x and y
@@ -42,12 +50,213 @@ pub fn draw_line(buffer: &mut Vec<u32>, x1: i32, y1: i32, x2: i32, y2: i32, colo
     }
 }
 
+// Draw a WAD picture at (x, y) in 320x200 screen coordinates, honoring its offsets
+pub fn draw_patch(buffer: &mut [u32], patch: &Patch, palette: &[u32], x: i32, y: i32) {
+    let left = x - patch.left_offset as i32;
+    let top = y - patch.top_offset as i32;
+
+    for py in 0..patch.height {
+        for px in 0..patch.width {
+            if let Some(index) = patch.pixels[py * patch.width + px] {
+                fill_block(
+                    buffer,
+                    (left + px as i32) * SCALE as i32,
+                    (top + py as i32) * SCALE as i32,
+                    SCALE,
+                    palette[index as usize],
+                );
+            }
+        }
+    }
+}
+
+// Tile a flat over the whole screen, like the finale and help backgrounds
+pub fn draw_flat(buffer: &mut [u32], flat: &[u8], palette: &[u32]) {
+    if flat.len() < FLAT_SIZE * FLAT_SIZE || palette.is_empty() {
+        return;
+    }
+    for y in 0..HEIGHT / SCALE {
+        for x in 0..WIDTH / SCALE {
+            let index = flat[(y % FLAT_SIZE) * FLAT_SIZE + x % FLAT_SIZE];
+            fill_block(
+                buffer,
+                (x * SCALE) as i32,
+                (y * SCALE) as i32,
+                SCALE,
+                palette[index as usize],
+            );
+        }
+    }
+}
+
+// WAD pictures for a 320x200 screen layout (menus, intermission...), loaded
+// up front so the render thread doesn't have to touch the WAD file
+pub struct Graphics {
+    patches: HashMap<String, Patch>,
+    palette: Vec<u32>,
+}
+
+impl Graphics {
+    // Missing lumps are skipped, callers fall back to drawing text
+    pub fn load<S: AsRef<str>>(wad: &WadData, names: &[S]) -> Self {
+        let mut patches = HashMap::new();
+        for name in names {
+            if let Ok(patch) = wad.read_patch(name.as_ref()) {
+                patches.insert(name.as_ref().to_string(), patch);
+            }
+        }
+
+        Graphics {
+            patches,
+            palette: wad.read_palette(0).unwrap_or_default(),
+        }
+    }
+
+    // Add a picture that didn't come straight from a lump, like a mirrored sprite
+    pub fn insert(&mut self, name: &str, patch: Patch) {
+        self.patches.insert(name.to_string(), patch);
+    }
+
+    pub fn palette(&self) -> &[u32] {
+        &self.palette
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Patch> {
+        match self.patches.get(name) {
+            Some(patch) if !self.palette.is_empty() => Some(patch),
+            _ => None,
+        }
+    }
+
+    // Draw a picture at (x, y) on the 320x200 layout, or the label if it's missing
+    pub fn draw(&self, buffer: &mut [u32], font: &Font, name: &str, label: &str, x: i32, y: i32) {
+        match self.get(name) {
+            Some(patch) => draw_patch(buffer, patch, &self.palette, x, y + SCREEN_Y_OFFSET),
+            None => font.draw_text(buffer, x, y + 4 + SCREEN_Y_OFFSET, label),
+        }
+    }
+}
+
+// Every flat in the WAD, loaded up front like Graphics so the render thread
+// can pick whichever the animations are showing
+pub struct Flats {
+    flats: HashMap<String, Vec<u8>>,
+    palette: Vec<u32>,
+}
+
+impl Flats {
+    pub fn load(wad: &WadData) -> Self {
+        let flats = wad
+            .read_flat_names()
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), wad.read_flat(&name).ok()?)))
+            .collect();
+        Flats {
+            flats,
+            palette: wad.read_palette(0).unwrap_or_default(),
+        }
+    }
+}
+
+// Fill a size x size square of pixels, clipped to the screen
+pub fn fill_block(buffer: &mut [u32], x: i32, y: i32, size: usize, color: u32) {
+    for dy in 0..size as i32 {
+        for dx in 0..size as i32 {
+            let (sx, sy) = (x + dx, y + dy);
+            if sx >= 0 && sx < WIDTH as i32 && sy >= 0 && sy < HEIGHT as i32 {
+                buffer[sy as usize * WIDTH + sx as usize] = color;
+            }
+        }
+    }
+}
+
+// Below screen size 10 the 3D view shrinks and is framed by a border
+pub fn view_window(screen_size: usize) -> (usize, usize, usize, usize) {
+    if screen_size >= 10 {
+        return (0, 0, WIDTH, HEIGHT);
+    }
+    let width = WIDTH * screen_size / 10;
+    let height = HEIGHT * screen_size / 10;
+    ((WIDTH - width) / 2, (HEIGHT - height) / 2, width, height)
+}
+
+// Darken a colour for a light level, 255 being full brightness
+pub fn shade(color: u32, light: i32) -> u32 {
+    let light = light.clamp(0, 255) as u32;
+    let scale = |channel: u32| (channel & 0xff) * light / 255;
+    (scale(color >> 16) << 16) | (scale(color >> 8) << 8) | scale(color)
+}
+
+// Draw the player's weapon and its muzzle flash over the 3D view, lit by
+// `light` unless the frame is fullbright. They're placed on a 320x200 view,
+// which sits at the bottom middle of ours.
+pub fn draw_psprites(
+    buffer: &mut [u32],
+    graphics: &Graphics,
+    psprites: &[Psprite],
+    screen_size: usize,
+    light: i32,
+) {
+    let shaded: Vec<u32> = graphics
+        .palette()
+        .iter()
+        .map(|&c| shade(c, light))
+        .collect();
+    let (x, y, width, height) = view_window(screen_size);
+    let left = ((x + width / 2) / SCALE) as i32 - 160;
+    let top = ((y + height) / SCALE) as i32 - 200;
+    for psprite in psprites {
+        if psprite.state == S_NULL {
+            continue;
+        }
+        let state = &info().states[psprite.state];
+        let name = psprite_name(state);
+        if let Some(patch) = graphics.get(&name) {
+            let palette = if state.bright {
+                graphics.palette()
+            } else {
+                &shaded
+            };
+            let (sx, sy) = (psprite.sx as i32, psprite.sy as i32);
+            draw_patch(buffer, patch, palette, left + sx, top + sy);
+        }
+    }
+}
+
+// Blend the whole screen towards a colour, the way PLAYPAL's palettes 1 to
+// 13 shift the game's colours for the red, gold and green screen flashes
+pub fn tint_screen(buffer: &mut [u32], palette: usize) {
+    let ((red, green, blue), amount) = match palette {
+        1..=8 => ((255, 0, 0), palette as u32 * 256 / 9),
+        9..=12 => ((215, 186, 69), (palette as u32 - 8) * 256 / 8),
+        13 => ((0, 256, 0), 256 / 8),
+        _ => return,
+    };
+    let blend = |channel: u32, to: u32| (channel * (256 - amount) + to * amount) >> 8;
+    for pixel in buffer.iter_mut() {
+        let (r, g, b) = ((*pixel >> 16) & 0xff, (*pixel >> 8) & 0xff, *pixel & 0xff);
+        *pixel = (blend(r, red).min(255) << 16) | (blend(g, green).min(255) << 8) | blend(b, blue);
+    }
+}
+
+pub fn draw_view_border(buffer: &mut [u32], screen_size: usize) {
+    let (x, y, width, height) = view_window(screen_size);
+    for sy in 0..HEIGHT {
+        for sx in 0..WIDTH {
+            if sx < x || sx >= x + width || sy < y || sy >= y + height {
+                buffer[sy * WIDTH + sx] = 0x303030;
+            }
+        }
+    }
+}
+
+// `light` is the light level of the sector the player is standing in
 pub fn perspective_render(
-    buffer: &mut Vec<u32>,
+    buffer: &mut [u32],
     player_x: f32,
     player_y: f32,
     player_angle: f32,
-    world_objects: &Vec<(f32, f32)>,
+    light: i32,
 ) {
     for i in buffer.iter_mut() {
         *i = 0x000000;
@@ -69,6 +278,8 @@ pub fn perspective_render(
 
     let screen_center_x = WIDTH as f32 / 2.0;
     let screen_center_y = HEIGHT as f32 / 2.0;
+    // white (object color), dimmed by the sector's light
+    let color = shade(0xFFFFFF, light);
 
     // sine and cosine for rotation
     let angle_rad = player_angle * (PI / 180.0);
@@ -92,18 +303,16 @@ pub fn perspective_render(
     let scale_y = HEIGHT as f32 / (max_y - min_y).max(1.0);
     let scale = scale_x.min(scale_y) * 0.9; // 90% of screen to avoid clipping
 
-    let screen_objects: Vec<(usize, usize)> = world_objects
-        .iter()
-        .map(|(object_x, object_y)| {
-            // Normalize and scale coordinates
-            let normalized_x = ((object_x - min_x) * scale) as usize
-                + ((WIDTH as f32 - scale * (max_x - min_x)) / 2.0) as usize;
-            let normalized_y = ((object_y - min_y) * scale) as usize
-                + ((HEIGHT as f32 - scale * (max_y - min_y)) / 2.0) as usize;
-
-            (normalized_x, normalized_y)
-        })
-        .collect();
+    let to_screen = |&(object_x, object_y): &(f32, f32)| {
+        // Normalize and scale coordinates
+        let normalized_x = ((object_x - min_x) * scale) as usize
+            + ((WIDTH as f32 - scale * (max_x - min_x)) / 2.0) as usize;
+        let normalized_y = ((object_y - min_y) * scale) as usize
+            + ((HEIGHT as f32 - scale * (max_y - min_y)) / 2.0) as usize;
+
+        (normalized_x, normalized_y)
+    };
+    let screen_objects: Vec<(usize, usize)> = world_objects.iter().map(to_screen).collect();
 
     for (object_x, object_y) in &screen_objects {
         // calculate world coordinates relative to the player
@@ -143,7 +352,7 @@ pub fn perspective_render(
                     let y = screen_y + dy.saturating_sub(size / 2);
 
                     if x < WIDTH && y < HEIGHT {
-                        buffer[y * WIDTH + x] = 0xFFFFFF; // white (object color)
+                        buffer[y * WIDTH + x] = color;
                     }
                 }
             }
@@ -151,10 +360,56 @@ pub fn perspective_render(
     }
 }
 
+// Cover the bottom half of the view with the floor under the player, seen
+// from `height` above it at `view`, the player's x, y and angle. `flat` is
+// the one to draw, after the animations.
+pub fn draw_floor(
+    buffer: &mut [u32],
+    flats: &Flats,
+    flat: &str,
+    view: (f32, f32, f32),
+    height: f32,
+    light: i32,
+) {
+    let (player_x, player_y, player_angle) = view;
+    let Some(flat) = flats.flats.get(flat) else {
+        return;
+    };
+    if flats.palette.is_empty() || height <= 0.0 {
+        return;
+    }
+
+    // A 90 degree field of view across the screen
+    let center_x = WIDTH as f32 / 2.0;
+    let center_y = HEIGHT as f32 / 2.0;
+    let focal = center_x;
+    let angle_rad = player_angle * (PI / 180.0);
+    let (sin_angle, cos_angle) = angle_rad.sin_cos();
+
+    for sy in HEIGHT / 2 + 1..HEIGHT {
+        // How far ahead this row of the floor is
+        let distance = height * focal / (sy as f32 - center_y);
+        for sx in 0..WIDTH {
+            let side = (sx as f32 - center_x) * distance / focal;
+            let x = player_x + cos_angle * distance + sin_angle * side;
+            let y = player_y + sin_angle * distance - cos_angle * side;
+            // Flats repeat every 64 units, with y going down the flat
+            let u = (x.floor() as i32).rem_euclid(FLAT_SIZE as i32) as usize;
+            let v = (-y.floor() as i32).rem_euclid(FLAT_SIZE as i32) as usize;
+            let index = flat[v * FLAT_SIZE + u];
+            buffer[sy * WIDTH + sx] = shade(flats.palette[index as usize], light);
+        }
+    }
+}
+
+// The automap. `reveal` draws the lines hidden from it too, and `things`
+// are marked where they stand.
 pub fn render_linedef(
-    buffer: &mut Vec<u32>,
-    world_objects: &Vec<(f32, f32)>,
-    linedefs: Vec<LineDef>,
+    buffer: &mut [u32],
+    world_objects: &[(f32, f32)],
+    linedefs: &[LineDef],
+    reveal: bool,
+    things: &[(f32, f32)],
 ) {
     // Clear the buffer first
     for i in buffer.iter_mut() {
@@ -179,18 +434,16 @@ pub fn render_linedef(
     let scale_y = HEIGHT as f32 / (max_y - min_y).max(1.0);
     let scale = scale_x.min(scale_y) * 0.9; // 90% of screen to avoid clipping
 
-    let screen_objects: Vec<(usize, usize)> = world_objects
-        .iter()
-        .map(|(object_x, object_y)| {
-            // Normalize and scale coordinates
-            let normalized_x = ((object_x - min_x) * scale) as usize
-                + ((WIDTH as f32 - scale * (max_x - min_x)) / 2.0) as usize;
-            let normalized_y = ((object_y - min_y) * scale) as usize
-                + ((HEIGHT as f32 - scale * (max_y - min_y)) / 2.0) as usize;
-
-            (normalized_x, normalized_y)
-        })
-        .collect();
+    let to_screen = |&(object_x, object_y): &(f32, f32)| {
+        // Normalize and scale coordinates
+        let normalized_x = ((object_x - min_x) * scale) as usize
+            + ((WIDTH as f32 - scale * (max_x - min_x)) / 2.0) as usize;
+        let normalized_y = ((object_y - min_y) * scale) as usize
+            + ((HEIGHT as f32 - scale * (max_y - min_y)) / 2.0) as usize;
+
+        (normalized_x, normalized_y)
+    };
+    let screen_objects: Vec<(usize, usize)> = world_objects.iter().map(to_screen).collect();
 
     // Render vertices
     for (screen_x, screen_y) in &screen_objects {
@@ -201,6 +454,9 @@ pub fn render_linedef(
     // println!("Starting to render linedefs, total linedefs: {}", linedefs.len());
 
     for linedef in linedefs {
+        if linedef.flags & ML_DONTDRAW != 0 && !reveal {
+            continue;
+        }
         if let (Some(&(x1_screen, y1_screen)), Some(&(x2_screen, y2_screen))) = (
             screen_objects.get(linedef.start_vertex[0] as usize),
             screen_objects.get(linedef.end_vertex[0] as usize),
@@ -216,4 +472,12 @@ pub fn render_linedef(
             );
         }
     }
+
+    // Things as small green crosses
+    for thing in things {
+        let (x, y) = to_screen(thing);
+        let (x, y) = (x as i32, y as i32);
+        draw_line(buffer, x - 2, y, x + 2, y, 0x00FF00);
+        draw_line(buffer, x, y - 2, x, y + 2, 0x00FF00);
+    }
 }
@@ -0,0 +1,151 @@
+// Line of sight between things, like the original's P_CheckSight: the REJECT
+// table rules out whole pairs of sectors, then the sight line is traced
+// through the BSP tree, narrowing the visible window at every opening.
+use crate::level::{node_side, Level};
+use crate::mobj::Body;
+use crate::wad_reader::NF_SUBSECTOR;
+
+struct SightTrace {
+    from: (f32, f32),
+    delta: (f32, f32),
+    // Height of the looker's eyes
+    eye_z: f32,
+    // Slopes from the eyes to the top and bottom of what's still visible, as
+    // height change over the full length of the trace
+    top_slope: f32,
+    bottom_slope: f32,
+}
+
+impl SightTrace {
+    fn end(&self) -> (f32, f32) {
+        (self.from.0 + self.delta.0, self.from.1 + self.delta.1)
+    }
+}
+
+fn cross(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+// Which side of the line through `origin` along `delta` a point is on
+fn point_side(point: (f32, f32), origin: (f32, f32), delta: (f32, f32)) -> bool {
+    cross((point.0 - origin.0, point.1 - origin.1), delta) > 0.0
+}
+
+impl Level {
+    // Could `from` see `to`, looking from three quarters of its height?
+    pub fn check_sight(&self, from: &Body, to: &Body) -> bool {
+        let sectors = (
+            self.sector_index_at(from.x, from.y),
+            self.sector_index_at(to.x, to.y),
+        );
+        if let (Some(first), Some(second)) = sectors {
+            let pair = first * self.sectors.len() + second;
+            let rejected = self
+                .reject
+                .get(pair / 8)
+                .is_some_and(|byte| byte & (1 << (pair % 8)) != 0);
+            if rejected {
+                return false;
+            }
+        }
+
+        let eye_z = from.z + from.height - from.height / 4.0;
+        let mut trace = SightTrace {
+            from: (from.x, from.y),
+            delta: (to.x - from.x, to.y - from.y),
+            eye_z,
+            top_slope: to.z + to.height - eye_z,
+            bottom_slope: to.z - eye_z,
+        };
+        // A map with a single subsector has no nodes
+        match self.nodes.len() {
+            0 => self.cross_subsector(0, &mut trace),
+            count => self.cross_bsp_node((count - 1) as u16, &mut trace),
+        }
+    }
+
+    // Check the side of the partition the trace starts on, then the other
+    // side if the trace crosses over to it
+    fn cross_bsp_node(&self, child: u16, trace: &mut SightTrace) -> bool {
+        if child & NF_SUBSECTOR != 0 {
+            return self.cross_subsector((child & !NF_SUBSECTOR) as usize, trace);
+        }
+        let Some(node) = self.nodes.get(child as usize) else {
+            return true;
+        };
+
+        let side = node_side(node, trace.from.0, trace.from.1);
+        if !self.cross_bsp_node(node.children[side], trace) {
+            return false;
+        }
+        let (end_x, end_y) = trace.end();
+        if side == node_side(node, end_x, end_y) {
+            return true;
+        }
+        self.cross_bsp_node(node.children[side ^ 1], trace)
+    }
+
+    // False if a line in the subsector blocks the trace
+    fn cross_subsector(&self, index: usize, trace: &mut SightTrace) -> bool {
+        let Some(subsector) = self.subsectors.get(index) else {
+            return true;
+        };
+        let first = subsector.first_seg;
+        let segs = self.segs.get(first..first + subsector.seg_count);
+
+        for seg in segs.unwrap_or_default() {
+            let Some(line) = self.linedefs.get(seg.linedef) else {
+                continue;
+            };
+            let Some((start, end)) = self.line_points(line) else {
+                continue;
+            };
+
+            // Only lines the trace actually crosses matter
+            if point_side(start, trace.from, trace.delta)
+                == point_side(end, trace.from, trace.delta)
+            {
+                continue;
+            }
+            let line_delta = (end.0 - start.0, end.1 - start.1);
+            if point_side(trace.from, start, line_delta)
+                == point_side(trace.end(), start, line_delta)
+            {
+                continue;
+            }
+
+            // Solid walls block sight, so do closed doors
+            let (Some(front), Some(back)) = self.line_sectors(line) else {
+                return false;
+            };
+            if front.floor_height == back.floor_height
+                && front.ceiling_height == back.ceiling_height
+            {
+                continue;
+            }
+            let top = front.ceiling_height.min(back.ceiling_height);
+            let bottom = front.floor_height.max(back.floor_height);
+            if bottom >= top {
+                return false;
+            }
+
+            // How far along the trace the line is, to turn the opening's
+            // heights into slopes
+            let to_line = (start.0 - trace.from.0, start.1 - trace.from.1);
+            let fraction =
+                (cross(to_line, line_delta) / cross(trace.delta, line_delta)).max(f32::EPSILON);
+            if front.floor_height != back.floor_height {
+                let slope = (bottom - trace.eye_z) / fraction;
+                trace.bottom_slope = trace.bottom_slope.max(slope);
+            }
+            if front.ceiling_height != back.ceiling_height {
+                let slope = (top - trace.eye_z) / fraction;
+                trace.top_slope = trace.top_slope.min(slope);
+            }
+            if trace.top_slope <= trace.bottom_slope {
+                return false;
+            }
+        }
+        true
+    }
+}
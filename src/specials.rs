@@ -0,0 +1,610 @@
+// Linedef specials, like the original's p_spec.c: lines that
+// are walked over, used or shot set doors, lifts, floors, crushers and
+// lights moving in the sectors with the same tag
+use crate::ceilings::{Ceiling, CeilingKind};
+use crate::collision::{lines_touched, point_on_line_side};
+use crate::combat::line_fraction;
+use crate::doors::{Door, DoorKind};
+use crate::floors::{FloorKind, FloorMove, StairKind};
+use crate::info::MobjType;
+use crate::level::{segments_cross, Level, SECRET_SECTOR};
+use crate::lights::{FAST_DARK, SLOW_DARK};
+use crate::mobj::{heading, Target};
+use crate::plats::{Plat, PlatKind};
+
+// Linedef flags
+pub const ML_TWOSIDED: i16 = 4;
+// Drawn as a one-sided wall on the automap, and monsters won't open it
+pub const ML_SECRET: i16 = 0x20;
+// Left off the automap, unless cheating
+pub const ML_DONTDRAW: i16 = 0x80;
+
+// How far away the player can use a switch or door
+const USE_RANGE: f32 = 64.0;
+// What the surrounding searches start from when there's nothing around
+const MAX_HEIGHT: f32 = 32767.0;
+const MIN_FLOOR: f32 = -500.0;
+
+// Something moving a sector's floor or ceiling over a number of tics. Each
+// sector has at most one at a time.
+pub enum SectorThinker {
+    Door(Door),
+    Floor(FloorMove),
+    Plat(Plat),
+    Ceiling(Ceiling),
+}
+
+impl SectorThinker {
+    fn sector(&self) -> usize {
+        match self {
+            SectorThinker::Door(door) => door.sector,
+            SectorThinker::Floor(floor) => floor.sector,
+            SectorThinker::Plat(plat) => plat.sector,
+            SectorThinker::Ceiling(ceiling) => ceiling.sector,
+        }
+    }
+}
+
+impl Level {
+    pub fn add_thinker(&mut self, sector: usize, thinker: SectorThinker) {
+        self.thinkers.push(Some(thinker));
+        self.sector_thinkers[sector] = Some(self.thinkers.len() - 1);
+    }
+
+    // Start the sector specials going when a level loads: lights, and
+    // doors on a timer. Secrets and damaging floors wait for the player.
+    pub fn spawn_specials(&mut self) {
+        for sector in 0..self.sectors.len() {
+            match self.sectors[sector].special {
+                1 => self.spawn_light_flash(sector),
+                2 => self.spawn_strobe_flash(sector, FAST_DARK, false),
+                3 => self.spawn_strobe_flash(sector, SLOW_DARK, false),
+                // Strobing and hurting, which keeps its special for the damage
+                4 => {
+                    self.spawn_strobe_flash(sector, FAST_DARK, false);
+                    self.sectors[sector].special = 4;
+                }
+                8 => self.spawn_glowing_light(sector),
+                10 => self.spawn_door_close_in_30(sector),
+                12 => self.spawn_strobe_flash(sector, SLOW_DARK, true),
+                13 => self.spawn_strobe_flash(sector, FAST_DARK, true),
+                14 => self.spawn_door_raise_in_5_mins(sector),
+                17 => self.spawn_fire_flicker(sector),
+                _ => {}
+            }
+        }
+    }
+
+    // The player standing on the floor of a special sector: hurt by slime
+    // every second unless wearing a radiation suit, or finding a secret
+    pub fn player_in_special_sector(&mut self, iron_feet: bool) {
+        let Some(player) = self.player_body else {
+            return;
+        };
+        let Some(sector) = self.sector_index_at(player.x, player.y) else {
+            return;
+        };
+        if player.z != self.sectors[sector].floor_height {
+            return;
+        }
+        let hurt = self.stats.time & 0x1f == 0;
+        match self.sectors[sector].special {
+            5 if !iron_feet && hurt => self.damage_mobj(Target::Player, None, None, 10),
+            7 if !iron_feet && hurt => self.damage_mobj(Target::Player, None, None, 5),
+            // The suit doesn't always keep out the worst of it
+            4 | 16 if (!iron_feet || self.rng.next() < 5) && hurt => {
+                self.damage_mobj(Target::Player, None, None, 20)
+            }
+            SECRET_SECTOR => {
+                self.stats.secrets += 1;
+                self.sectors[sector].special = 0;
+            }
+            // The end of E1M8: no god mode, hurt until nearly dead, then out
+            11 => {
+                self.player_god = false;
+                if hurt {
+                    self.damage_mobj(Target::Player, None, None, 20);
+                }
+                if self.player_body.is_some_and(|body| body.health <= 10) {
+                    self.exit_requested = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Run one tic of everything moving sectors about. Finished ones free up
+    // their sector for the next special.
+    pub fn tick_specials(&mut self) {
+        self.tick_lights();
+        for index in 0..self.thinkers.len() {
+            let Some(mut thinker) = self.thinkers[index].take() else {
+                continue;
+            };
+            let running = match &mut thinker {
+                SectorThinker::Door(door) => self.tick_door(door),
+                SectorThinker::Floor(floor) => self.tick_floor(floor),
+                SectorThinker::Plat(plat) => self.tick_plat(plat),
+                SectorThinker::Ceiling(ceiling) => self.tick_ceiling(ceiling),
+            };
+            if running {
+                self.thinkers[index] = Some(thinker);
+            } else {
+                self.sector_thinkers[thinker.sector()] = None;
+            }
+        }
+        self.update_specials();
+    }
+
+    // The next sector after `start` with this tag
+    pub fn find_sector_from_tag(&self, tag: i16, start: Option<usize>) -> Option<usize> {
+        let first = start.map_or(0, |start| start + 1);
+        (first..self.sectors.len()).find(|&index| self.sectors[index].tag == tag)
+    }
+
+    // The sector on the other side of a two-sided line from `sector`
+    pub fn next_sector(&self, line: usize, sector: usize) -> Option<usize> {
+        let line = &self.linedefs[line];
+        if line.flags & ML_TWOSIDED == 0 {
+            return None;
+        }
+        let front = self.side_sector_index(line.front_sidedef);
+        if front == Some(sector) {
+            self.side_sector_index(line.back_sidedef)
+        } else {
+            front
+        }
+    }
+
+    // The sectors across each of a sector's two-sided lines
+    pub fn neighbours(&self, sector: usize) -> impl Iterator<Item = usize> + '_ {
+        self.sector_lines[sector]
+            .iter()
+            .filter_map(move |&line| self.next_sector(line, sector))
+    }
+
+    pub fn lowest_floor_surrounding(&self, sector: usize) -> f32 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].floor_height)
+            .fold(self.sectors[sector].floor_height, f32::min)
+    }
+
+    pub fn highest_floor_surrounding(&self, sector: usize) -> f32 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].floor_height)
+            .fold(MIN_FLOOR, f32::max)
+    }
+
+    // The lowest neighbouring floor above `height`, or `height` if there's
+    // none
+    pub fn next_highest_floor(&self, sector: usize, height: f32) -> f32 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].floor_height)
+            .filter(|&floor| floor > height)
+            .reduce(f32::min)
+            .unwrap_or(height)
+    }
+
+    pub fn lowest_ceiling_surrounding(&self, sector: usize) -> f32 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].ceiling_height)
+            .fold(MAX_HEIGHT, f32::min)
+    }
+
+    pub fn highest_ceiling_surrounding(&self, sector: usize) -> f32 {
+        self.neighbours(sector)
+            .map(|other| self.sectors[other].ceiling_height)
+            .fold(0.0, f32::max)
+    }
+
+    // The height of the shortest lower texture on either side of the
+    // sector's two-sided lines
+    pub fn shortest_lower_texture(&self, sector: usize) -> f32 {
+        let mut shortest = MAX_HEIGHT;
+        for &line in &self.sector_lines[sector] {
+            let line = &self.linedefs[line];
+            if line.flags & ML_TWOSIDED == 0 {
+                continue;
+            }
+            for side in [line.front_sidedef, line.back_sidedef] {
+                let Some(side) = usize::try_from(side)
+                    .ok()
+                    .and_then(|i| self.sidedefs.get(i))
+                else {
+                    continue;
+                };
+                if let Some(&height) = self.texture_heights.get(&side.lower_texture) {
+                    shortest = shortest.min(height);
+                }
+            }
+        }
+        shortest
+    }
+
+    // The box around a sector's lines: left, right, bottom, top
+    pub fn sector_box(&self, sector: usize) -> (f32, f32, f32, f32) {
+        let mut bbox = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+        for &line in &self.sector_lines[sector] {
+            let Some((start, end)) = self.line_points(&self.linedefs[line]) else {
+                continue;
+            };
+            for (x, y) in [start, end] {
+                bbox = (bbox.0.min(x), bbox.1.max(x), bbox.2.min(y), bbox.3.max(y));
+            }
+        }
+        bbox
+    }
+
+    // The sector in front of a line
+    fn front_sector(&self, line: usize) -> Option<usize> {
+        self.side_sector_index(self.linedefs[line].front_sidedef)
+    }
+
+    // Something moved from `from` to `to`: set off the walk-over specials of
+    // any lines it crossed
+    pub fn cross_special_lines(&mut self, from: (f32, f32), to: (f32, f32), who: Target) {
+        if from == to {
+            return;
+        }
+        for index in 0..self.linedefs.len() {
+            let line = &self.linedefs[index];
+            if line.linedef_type == 0 {
+                continue;
+            }
+            let Some((start, end)) = self.line_points(line) else {
+                continue;
+            };
+            if segments_cross(from, to, start, end) {
+                let side = point_on_line_side(from.0, from.1, start, end);
+                self.cross_special_line(index, side, who);
+            }
+        }
+    }
+
+    // Two-sided lines with specials that a box of `radius` at (x, y) touches,
+    // for monsters to try opening when they're blocked
+    pub fn special_lines_touched(&self, x: f32, y: f32, radius: f32) -> Vec<usize> {
+        lines_touched(self, x, y, radius)
+            .into_iter()
+            .filter(|&index| {
+                let line = &self.linedefs[index];
+                line.linedef_type != 0 && line.back_sidedef != -1
+            })
+            .collect()
+    }
+
+    // W1 lines go off once, WR lines every time. Monsters can only set off
+    // a few kinds of lines.
+    fn cross_special_line(&mut self, index: usize, side: bool, who: Target) {
+        let line = &self.linedefs[index];
+        let (special, tag) = (line.linedef_type, line.tag);
+        if let Target::Mobj(mobj) = who {
+            // Most missiles don't set off lines, though anything else that
+            // moves can
+            let kind = self.mobjs[mobj].as_ref().map(|mobj| mobj.kind);
+            let missile = matches!(
+                kind,
+                Some(
+                    MobjType::Rocket
+                        | MobjType::Plasma
+                        | MobjType::Bfg
+                        | MobjType::TroopShot
+                        | MobjType::HeadShot
+                        | MobjType::BruiserShot
+                )
+            );
+            if missile || !matches!(special, 4 | 10 | 39 | 88 | 97 | 125 | 126) {
+                return;
+            }
+        }
+        let front = self.front_sector(index);
+
+        match special {
+            2 | 86 => self.do_door(tag, DoorKind::Open),
+            3 | 75 => self.do_door(tag, DoorKind::Close),
+            4 | 90 => self.do_door(tag, DoorKind::Normal),
+            5 | 91 => self.do_floor(tag, FloorKind::RaiseFloor, front),
+            6 | 77 => self.do_ceiling(tag, CeilingKind::FastCrushAndRaise),
+            8 => self.build_stairs(tag, StairKind::Build8),
+            10 | 88 => self.do_plat(tag, PlatKind::DownWaitUpStay, 0.0, front),
+            12 | 80 => self.light_turn_on(tag, 0),
+            13 | 81 => self.light_turn_on(tag, 255),
+            16 | 76 => self.do_door(tag, DoorKind::Close30ThenOpen),
+            17 => self.start_light_strobing(tag),
+            19 | 83 => self.do_floor(tag, FloorKind::LowerFloor, front),
+            22 | 95 => self.do_plat(tag, PlatKind::RaiseToNearestAndChange, 0.0, front),
+            25 | 73 => self.do_ceiling(tag, CeilingKind::CrushAndRaise),
+            30 | 96 => self.do_floor(tag, FloorKind::RaiseToTexture, front),
+            35 | 79 => self.light_turn_on(tag, 35),
+            36 | 98 => self.do_floor(tag, FloorKind::TurboLower, front),
+            37 | 84 => self.do_floor(tag, FloorKind::LowerAndChange, front),
+            38 | 82 => self.do_floor(tag, FloorKind::LowerFloorToLowest, front),
+            40 => {
+                self.do_ceiling(tag, CeilingKind::RaiseToHighest);
+                self.do_floor(tag, FloorKind::LowerFloorToLowest, front)
+            }
+            44 | 72 => self.do_ceiling(tag, CeilingKind::LowerAndCrush),
+            52 => {
+                self.exit_requested = true;
+                true
+            }
+            53 | 87 => self.do_plat(tag, PlatKind::PerpetualRaise, 0.0, front),
+            124 => {
+                self.exit_requested = true;
+                self.secret_exit = true;
+                true
+            }
+            39 | 97 => self.teleport(index, side, who),
+            // Teleports for monsters only, the W1 one used up by the first
+            // monster to cross it
+            125 if who != Target::Player => {
+                let teleported = self.teleport(index, side, who);
+                self.linedefs[index].linedef_type = 0;
+                teleported
+            }
+            126 if who != Target::Player => self.teleport(index, side, who),
+            54 | 89 => self.stop_plat(tag),
+            56 | 94 => self.do_floor(tag, FloorKind::RaiseFloorCrush, front),
+            57 | 74 => self.ceiling_crush_stop(tag),
+            58 | 92 => self.do_floor(tag, FloorKind::RaiseFloor24, front),
+            59 | 93 => self.do_floor(tag, FloorKind::RaiseFloor24AndChange, front),
+            100 => self.build_stairs(tag, StairKind::Turbo16),
+            104 => self.turn_tag_lights_off(tag),
+            108 | 105 => self.do_door(tag, DoorKind::BlazeRaise),
+            109 | 106 => self.do_door(tag, DoorKind::BlazeOpen),
+            110 | 107 => self.do_door(tag, DoorKind::BlazeClose),
+            119 | 128 => self.do_floor(tag, FloorKind::RaiseFloorToNearest, front),
+            121 | 120 => self.do_plat(tag, PlatKind::BlazeDownWaitUpStay, 0.0, front),
+            130 | 129 => self.do_floor(tag, FloorKind::RaiseFloorTurbo, front),
+            141 => self.do_ceiling(tag, CeilingKind::SilentCrushAndRaise),
+            _ => false,
+        };
+
+        // Whether it did anything or not, a W1 line is used up
+        let once = matches!(
+            special,
+            2..=6 | 8 | 10 | 12 | 13 | 16 | 17 | 19 | 22 | 25 | 30 | 35..=40 | 44 | 52..=54
+                | 56..=59 | 100 | 104 | 108..=110 | 119 | 121 | 124 | 130 | 141
+        );
+        if once {
+            self.linedefs[index].linedef_type = 0;
+        }
+    }
+
+    // Press use: the first line with a special within reach straight ahead
+    // is set off, unless a wall is in the way
+    pub fn use_lines(&mut self, who: Target) {
+        let Some(body) = self.body(who) else {
+            return;
+        };
+        let from = (body.x, body.y);
+        let (dx, dy) = heading(body.angle);
+        let delta = (dx * USE_RANGE, dy * USE_RANGE);
+
+        let mut crossed: Vec<(f32, usize)> = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let (start, end) = self.line_points(line)?;
+                Some((line_fraction(from, delta, start, end)?, index))
+            })
+            .collect();
+        crossed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (_, index) in crossed {
+            let line = &self.linedefs[index];
+            if line.linedef_type == 0 {
+                // Walls and closed doors stop the search
+                let open = match self.line_sectors(line) {
+                    (Some(front), Some(back)) => {
+                        front.ceiling_height.min(back.ceiling_height)
+                            > front.floor_height.max(back.floor_height)
+                    }
+                    _ => false,
+                };
+                if open {
+                    continue;
+                }
+                return;
+            }
+            let Some((start, end)) = self.line_points(line) else {
+                return;
+            };
+            let side = point_on_line_side(from.0, from.1, start, end);
+            self.use_special_line(index, side, who);
+            return;
+        }
+    }
+
+    // Use a line from `side`: doors, S1 switches that work once and SR
+    // buttons that work every time. Returns true if it was a line that could
+    // be used.
+    pub fn use_special_line(&mut self, index: usize, side: bool, who: Target) -> bool {
+        let line = &self.linedefs[index];
+        let (special, tag, flags) = (line.linedef_type, line.tag, line.flags);
+        // Only the front of a line can be used
+        if side {
+            return false;
+        }
+        // Monsters only open plain doors, and never secret ones
+        if who != Target::Player && (flags & ML_SECRET != 0 || !matches!(special, 1 | 32..=34)) {
+            return false;
+        }
+        let front = self.front_sector(index);
+
+        match special {
+            // Doors opened by hand, the locked ones need keys
+            1 | 26..=28 | 31..=34 | 117 | 118 => self.vertical_door(index, who),
+
+            // Switches that work once
+            7 => self.switch_if(index, false, |level| {
+                level.build_stairs(tag, StairKind::Build8)
+            }),
+            9 => self.switch_if(index, false, |level| level.do_donut(tag)),
+            11 => {
+                self.change_switch_texture(index, false);
+                self.exit_requested = true;
+            }
+            51 => {
+                self.change_switch_texture(index, false);
+                self.exit_requested = true;
+                self.secret_exit = true;
+            }
+            14 => self.switch_if(index, false, |level| {
+                level.do_plat(tag, PlatKind::RaiseAndChange, 32.0, front)
+            }),
+            15 => self.switch_if(index, false, |level| {
+                level.do_plat(tag, PlatKind::RaiseAndChange, 24.0, front)
+            }),
+            18 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorToNearest, front)
+            }),
+            20 => self.switch_if(index, false, |level| {
+                level.do_plat(tag, PlatKind::RaiseToNearestAndChange, 0.0, front)
+            }),
+            21 => self.switch_if(index, false, |level| {
+                level.do_plat(tag, PlatKind::DownWaitUpStay, 0.0, front)
+            }),
+            23 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::LowerFloorToLowest, front)
+            }),
+            29 => self.switch_if(index, false, |level| level.do_door(tag, DoorKind::Normal)),
+            41 => self.switch_if(index, false, |level| {
+                level.do_ceiling(tag, CeilingKind::LowerToFloor)
+            }),
+            71 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::TurboLower, front)
+            }),
+            49 => self.switch_if(index, false, |level| {
+                level.do_ceiling(tag, CeilingKind::CrushAndRaise)
+            }),
+            50 => self.switch_if(index, false, |level| level.do_door(tag, DoorKind::Close)),
+            55 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorCrush, front)
+            }),
+            101 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloor, front)
+            }),
+            102 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::LowerFloor, front)
+            }),
+            103 => self.switch_if(index, false, |level| level.do_door(tag, DoorKind::Open)),
+            111 => self.switch_if(index, false, |level| {
+                level.do_door(tag, DoorKind::BlazeRaise)
+            }),
+            112 => self.switch_if(index, false, |level| {
+                level.do_door(tag, DoorKind::BlazeOpen)
+            }),
+            113 => self.switch_if(index, false, |level| {
+                level.do_door(tag, DoorKind::BlazeClose)
+            }),
+            122 => self.switch_if(index, false, |level| {
+                level.do_plat(tag, PlatKind::BlazeDownWaitUpStay, 0.0, front)
+            }),
+            127 => self.switch_if(index, false, |level| {
+                level.build_stairs(tag, StairKind::Turbo16)
+            }),
+            131 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorTurbo, front)
+            }),
+            133 | 135 | 137 => self.switch_if(index, false, |level| {
+                level.do_locked_door(index, DoorKind::BlazeOpen, who)
+            }),
+            140 => self.switch_if(index, false, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloor512, front)
+            }),
+
+            // Buttons that work every time
+            42 => self.switch_if(index, true, |level| level.do_door(tag, DoorKind::Close)),
+            43 => self.switch_if(index, true, |level| {
+                level.do_ceiling(tag, CeilingKind::LowerToFloor)
+            }),
+            45 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::LowerFloor, front)
+            }),
+            60 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::LowerFloorToLowest, front)
+            }),
+            61 => self.switch_if(index, true, |level| level.do_door(tag, DoorKind::Open)),
+            62 => self.switch_if(index, true, |level| {
+                level.do_plat(tag, PlatKind::DownWaitUpStay, 1.0, front)
+            }),
+            63 => self.switch_if(index, true, |level| level.do_door(tag, DoorKind::Normal)),
+            64 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloor, front)
+            }),
+            66 => self.switch_if(index, true, |level| {
+                level.do_plat(tag, PlatKind::RaiseAndChange, 24.0, front)
+            }),
+            67 => self.switch_if(index, true, |level| {
+                level.do_plat(tag, PlatKind::RaiseAndChange, 32.0, front)
+            }),
+            65 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorCrush, front)
+            }),
+            68 => self.switch_if(index, true, |level| {
+                level.do_plat(tag, PlatKind::RaiseToNearestAndChange, 0.0, front)
+            }),
+            69 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorToNearest, front)
+            }),
+            70 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::TurboLower, front)
+            }),
+            114 => self.switch_if(index, true, |level| {
+                level.do_door(tag, DoorKind::BlazeRaise)
+            }),
+            115 => self.switch_if(index, true, |level| level.do_door(tag, DoorKind::BlazeOpen)),
+            116 => self.switch_if(index, true, |level| {
+                level.do_door(tag, DoorKind::BlazeClose)
+            }),
+            123 => self.switch_if(index, true, |level| {
+                level.do_plat(tag, PlatKind::BlazeDownWaitUpStay, 0.0, front)
+            }),
+            132 => self.switch_if(index, true, |level| {
+                level.do_floor(tag, FloorKind::RaiseFloorTurbo, front)
+            }),
+            99 | 134 | 136 => self.switch_if(index, true, |level| {
+                level.do_locked_door(index, DoorKind::BlazeOpen, who)
+            }),
+            138 => self.switch_if(index, true, |level| level.light_turn_on(tag, 255)),
+            139 => self.switch_if(index, true, |level| level.light_turn_on(tag, 35)),
+            _ => {}
+        }
+        true
+    }
+
+    // Flip a switch if what it does got going
+    fn switch_if(&mut self, index: usize, use_again: bool, action: impl Fn(&mut Level) -> bool) {
+        if action(self) {
+            self.change_switch_texture(index, use_again);
+        }
+    }
+
+    // A bullet hit or passed a line: G1 and GR lines go off, monsters can
+    // only open doors this way
+    pub fn shoot_special_line(&mut self, index: usize, who: Target) {
+        let line = &self.linedefs[index];
+        let (special, tag) = (line.linedef_type, line.tag);
+        if who != Target::Player && special != 46 {
+            return;
+        }
+        let front = self.front_sector(index);
+        match special {
+            24 => {
+                self.do_floor(tag, FloorKind::RaiseFloor, front);
+                self.change_switch_texture(index, false);
+            }
+            46 => {
+                self.do_door(tag, DoorKind::Open);
+                self.change_switch_texture(index, true);
+            }
+            47 => {
+                self.do_plat(tag, PlatKind::RaiseToNearestAndChange, 0.0, front);
+                self.change_switch_texture(index, false);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// Built-in text shown by the game, kept together like the original's
+// string table
+
+// Episode endings for Doom and The Ultimate Doom
+pub const E1TEXT: &str = "Once you beat the big badasses and
+clean out the moon base you're supposed
+to win, aren't you? Aren't you? Where's
+your fat reward and ticket home? What
+the hell is this? It's not supposed to
+end this way!
+
+It stinks like rotten meat, but looks
+like the lost Deimos base.  Looks like
+you're stuck on The Shores of Hell.
+The only way out is through.
+
+To continue the DOOM experience, play
+The Shores of Hell and its amazing
+sequel, Inferno!
+";
+
+pub const E2TEXT: &str = "You've done it! The hideous cyber-
+demon lord that ruled the lost Deimos
+moon base has been slain and you
+are triumphant! But ... where are
+you? You clamber to the edge of the
+moon and look down to see the awful
+truth.
+
+Deimos floats above Hell itself!
+You've never heard of anyone escaping
+from Hell, but you'll make the bastards
+sorry they ever heard of you! Quickly,
+you rappel down to  the surface of
+Hell.
+
+Now, it's on to the final chapter of
+DOOM! -- Inferno.";
+
+pub const E3TEXT: &str = "The loathsome spiderdemon that
+masterminded the invasion of the moon
+bases and caused so much death has had
+its ass kicked for all time.
+
+A hidden doorway opens and you enter.
+You've proven too tough for Hell to
+contain, and now Hell at last plays
+fair -- for you emerge from the door
+to see the green fields of Earth!
+Home at last.
+
+You wonder what's been happening on
+Earth while you were battling evil
+unleashed. It's good that no Hell-
+spawn could have come through that
+door with you ...";
+
+pub const E4TEXT: &str = "the spider mastermind must have sent forth
+its legions of hellspawn before your
+final confrontation with that terrible
+beast from hell.  but you stepped forward
+and brought forth eternal damnation and
+suffering upon the horde as a true hero
+would in the face of something so evil.
+
+besides, someone was gonna pay for what
+happened to daisy, your pet rabbit.
+
+but now, you see spread before you more
+potential pain and gibbitude as a nation
+of demons run amok among our cities.
+
+next stop, hell on earth!";
+
+// Doom II story breaks
+pub const C1TEXT: &str = "YOU HAVE ENTERED DEEPLY INTO THE INFESTED
+STARPORT. BUT SOMETHING IS WRONG. THE
+MONSTERS HAVE BROUGHT THEIR OWN REALITY
+WITH THEM, AND THE STARPORT'S TECHNOLOGY
+IS BEING SUBVERTED BY THEIR PRESENCE.
+
+AHEAD, YOU SEE AN OUTPOST OF HELL, A
+FORTIFIED ZONE. IF YOU CAN GET PAST IT,
+YOU CAN PENETRATE INTO THE HAUNTED HEART
+OF THE STARBASE AND FIND THE CONTROLLING
+SWITCH WHICH HOLDS EARTH'S POPULATION
+HOSTAGE.";
+
+pub const C2TEXT: &str = "YOU HAVE WON! YOUR VICTORY HAS ENABLED
+HUMANKIND TO EVACUATE EARTH AND ESCAPE
+THE NIGHTMARE.  NOW YOU ARE THE ONLY
+HUMAN LEFT ON THE FACE OF THE PLANET.
+CANNIBAL MUTATIONS, CARNIVOROUS ALIENS,
+AND EVIL SPIRITS ARE YOUR ONLY NEIGHBORS.
+YOU SIT BACK AND WAIT FOR DEATH, CONTENT
+THAT YOU HAVE SAVED YOUR SPECIES.
+
+BUT THEN, EARTH CONTROL BEAMS DOWN A
+MESSAGE FROM SPACE: \"SENSORS HAVE LOCATED
+THE SOURCE OF THE ALIEN INVASION. IF YOU
+GO THERE, YOU MAY BE ABLE TO BLOCK THEIR
+ENTRY.  THE ALIEN BASE IS IN THE HEART OF
+YOUR OWN HOME CITY, NOT FAR FROM THE
+STARPORT.\" SLOWLY AND PAINFULLY YOU GET
+UP AND RETURN TO THE FRAY.";
+
+pub const C3TEXT: &str = "YOU ARE AT THE CORRUPT HEART OF THE CITY,
+SURROUNDED BY THE CORPSES OF YOUR ENEMIES.
+YOU SEE NO WAY TO DESTROY THE CREATURES'
+ENTRYWAY ON THIS SIDE, SO YOU CLENCH YOUR
+TEETH AND PLUNGE THROUGH IT.
+
+THERE MUST BE A WAY TO CLOSE IT ON THE
+OTHER SIDE. WHAT DO YOU CARE IF YOU'VE
+GOT TO GO THROUGH HELL TO GET TO IT?";
+
+pub const C4TEXT: &str = "THE HORRENDOUS VISAGE OF THE BIGGEST
+DEMON YOU'VE EVER SEEN CRUMBLES BEFORE
+YOU, AFTER YOU PUMP YOUR ROCKETS INTO
+HIS EXPOSED BRAIN. THE MONSTER SHRIVELS
+UP AND DIES, ITS THRASHING LIMBS
+DEVASTATING UNTOLD MILES OF HELL'S
+SURFACE.
+
+YOU'VE DONE IT. THE INVASION IS OVER.
+EARTH IS SAVED. HELL IS A WRECK. YOU
+WONDER WHERE BAD FOLKS WILL GO WHEN THEY
+DIE, NOW. WIPING THE SWEAT FROM YOUR
+FOREHEAD YOU BEGIN THE LONG TREK BACK
+HOME. REBUILDING EARTH OUGHT TO BE A
+LOT MORE FUN THAN RUINING IT WAS.
+";
+
+pub const C5TEXT: &str = "CONGRATULATIONS, YOU'VE FOUND THE SECRET
+LEVEL! LOOKS LIKE IT'S BEEN BUILT BY
+HUMANS, RATHER THAN DEMONS. YOU WONDER
+WHO THE INMATES OF THIS CORNER OF HELL
+WILL BE.";
+
+pub const C6TEXT: &str = "CONGRATULATIONS, YOU'VE FOUND THE
+SUPER SECRET LEVEL!  YOU'D BETTER
+BLAZE THROUGH THIS ONE!
+";
+
+// Names for the Doom II cast call
+pub const CC_ZOMBIE: &str = "ZOMBIEMAN";
+pub const CC_SHOTGUN: &str = "SHOTGUN GUY";
+pub const CC_HEAVY: &str = "HEAVY WEAPON DUDE";
+pub const CC_IMP: &str = "IMP";
+pub const CC_DEMON: &str = "DEMON";
+pub const CC_LOST: &str = "LOST SOUL";
+pub const CC_CACO: &str = "CACODEMON";
+pub const CC_HELL: &str = "HELL KNIGHT";
+pub const CC_BARON: &str = "BARON OF HELL";
+pub const CC_ARACH: &str = "ARACHNOTRON";
+pub const CC_PAIN: &str = "PAIN ELEMENTAL";
+pub const CC_REVEN: &str = "REVENANT";
+pub const CC_MANCU: &str = "MANCUBUS";
+pub const CC_ARCH: &str = "ARCH-VILE";
+pub const CC_SPIDER: &str = "THE SPIDER MASTERMIND";
+pub const CC_CYBER: &str = "THE CYBERDEMON";
+pub const CC_HERO: &str = "OUR HERO";
+
+// Pickup messages
+pub const GOTARMOR: &str = "Picked up the armor.";
+pub const GOTMEGA: &str = "Picked up the MegaArmor!";
+pub const GOTHTHBONUS: &str = "Picked up a health bonus.";
+pub const GOTARMBONUS: &str = "Picked up an armor bonus.";
+pub const GOTSTIM: &str = "Picked up a stimpack.";
+pub const GOTMEDINEED: &str = "Picked up a medikit that you REALLY need!";
+pub const GOTMEDIKIT: &str = "Picked up a medikit.";
+pub const GOTSUPER: &str = "Supercharge!";
+
+pub const GOTBLUECARD: &str = "Picked up a blue keycard.";
+pub const GOTYELWCARD: &str = "Picked up a yellow keycard.";
+pub const GOTREDCARD: &str = "Picked up a red keycard.";
+pub const GOTBLUESKUL: &str = "Picked up a blue skull key.";
+pub const GOTYELWSKUL: &str = "Picked up a yellow skull key.";
+pub const GOTREDSKULL: &str = "Picked up a red skull key.";
+
+pub const GOTINVUL: &str = "Invulnerability!";
+pub const GOTBERSERK: &str = "Berserk!";
+pub const GOTINVIS: &str = "Partial Invisibility";
+pub const GOTSUIT: &str = "Radiation Shielding Suit";
+pub const GOTMAP: &str = "Computer Area Map";
+pub const GOTVISOR: &str = "Light Amplification Visor";
+pub const GOTMSPHERE: &str = "MegaSphere!";
+
+pub const GOTCLIP: &str = "Picked up a clip.";
+pub const GOTCLIPBOX: &str = "Picked up a box of bullets.";
+pub const GOTROCKET: &str = "Picked up a rocket.";
+pub const GOTROCKBOX: &str = "Picked up a box of rockets.";
+pub const GOTCELL: &str = "Picked up an energy cell.";
+pub const GOTCELLBOX: &str = "Picked up an energy cell pack.";
+pub const GOTSHELLS: &str = "Picked up 4 shotgun shells.";
+pub const GOTSHELLBOX: &str = "Picked up a box of shotgun shells.";
+pub const GOTBACKPACK: &str = "Picked up a backpack full of ammo!";
+
+pub const GOTBFG9000: &str = "You got the BFG9000!  Oh, yes.";
+pub const GOTCHAINGUN: &str = "You got the chaingun!";
+pub const GOTCHAINSAW: &str = "A chainsaw!  Find some meat!";
+pub const GOTLAUNCHER: &str = "You got the rocket launcher!";
+pub const GOTPLASMA: &str = "You got the plasma gun!";
+pub const GOTSHOTGUN: &str = "You got the shotgun!";
+pub const GOTSHOTGUN2: &str = "You got the super shotgun!";
+
+// Locked doors and switches
+pub const PD_BLUEO: &str = "You need a blue key to activate this object";
+pub const PD_REDO: &str = "You need a red key to activate this object";
+pub const PD_YELLOWO: &str = "You need a yellow key to activate this object";
+pub const PD_BLUEK: &str = "You need a blue key to open this door";
+pub const PD_REDK: &str = "You need a red key to open this door";
+pub const PD_YELLOWK: &str = "You need a yellow key to open this door";
+
+// Savegames
+pub const GGSAVED: &str = "game saved.";
+
+// Cheats
+pub const STSTR_DQDON: &str = "Degreelessness Mode On";
+pub const STSTR_DQDOFF: &str = "Degreelessness Mode Off";
+pub const STSTR_KFAADDED: &str = "Very Happy Ammo Added";
+pub const STSTR_NCON: &str = "No Clipping Mode ON";
+pub const STSTR_NCOFF: &str = "No Clipping Mode OFF";
+pub const STSTR_BEHOLD: &str = "inVuln, Str, Inviso, Rad, Allmap, or Lite-amp";
+pub const STSTR_BEHOLDX: &str = "Power-up Toggled";
+pub const STSTR_CLEV: &str = "Changing Level...";
@@ -0,0 +1,168 @@
+// Switches, like the original's p_switch.c: using or shooting a switch swaps
+// its SW1 texture for the SW2 one, and buttons that can be used again swap
+// back a second later
+use crate::level::Level;
+use crate::wad_reader::WadData;
+
+// Tics a button stays pressed
+const BUTTON_TIME: i32 = 35;
+
+// The original's switch textures, off and on, and the game they first
+// appear in: 1 shareware, 2 registered and 3 Doom II
+const SWITCH_LIST: [(&str, &str, i16); 40] = [
+    ("SW1BRCOM", "SW2BRCOM", 1),
+    ("SW1BRN1", "SW2BRN1", 1),
+    ("SW1BRN2", "SW2BRN2", 1),
+    ("SW1BRNGN", "SW2BRNGN", 1),
+    ("SW1BROWN", "SW2BROWN", 1),
+    ("SW1COMM", "SW2COMM", 1),
+    ("SW1COMP", "SW2COMP", 1),
+    ("SW1DIRT", "SW2DIRT", 1),
+    ("SW1EXIT", "SW2EXIT", 1),
+    ("SW1GRAY", "SW2GRAY", 1),
+    ("SW1GRAY1", "SW2GRAY1", 1),
+    ("SW1METAL", "SW2METAL", 1),
+    ("SW1PIPE", "SW2PIPE", 1),
+    ("SW1SLAD", "SW2SLAD", 1),
+    ("SW1STARG", "SW2STARG", 1),
+    ("SW1STON1", "SW2STON1", 1),
+    ("SW1STON2", "SW2STON2", 1),
+    ("SW1STONE", "SW2STONE", 1),
+    ("SW1STRTN", "SW2STRTN", 1),
+    ("SW1BLUE", "SW2BLUE", 2),
+    ("SW1CMT", "SW2CMT", 2),
+    ("SW1GARG", "SW2GARG", 2),
+    ("SW1GSTON", "SW2GSTON", 2),
+    ("SW1HOT", "SW2HOT", 2),
+    ("SW1LION", "SW2LION", 2),
+    ("SW1SATYR", "SW2SATYR", 2),
+    ("SW1SKIN", "SW2SKIN", 2),
+    ("SW1VINE", "SW2VINE", 2),
+    ("SW1WOOD", "SW2WOOD", 2),
+    ("SW1PANEL", "SW2PANEL", 3),
+    ("SW1ROCK", "SW2ROCK", 3),
+    ("SW1MET2", "SW2MET2", 3),
+    ("SW1WDMET", "SW2WDMET", 3),
+    ("SW1BRIK", "SW2BRIK", 3),
+    ("SW1MOD1", "SW2MOD1", 3),
+    ("SW1ZIM", "SW2ZIM", 3),
+    ("SW1STON6", "SW2STON6", 3),
+    ("SW1TEK", "SW2TEK", 3),
+    ("SW1MARB", "SW2MARB", 3),
+    ("SW1SKULL", "SW2SKULL", 3),
+];
+
+// Which of a sidedef's textures a switch is on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwitchPlace {
+    Top,
+    Middle,
+    Bottom,
+}
+
+// A pressed button waiting to pop back out
+pub struct Button {
+    pub line: usize,
+    pub place: SwitchPlace,
+    pub texture: String,
+    pub timer: i32,
+}
+
+// The off and on textures of the switches this game has, from the WAD's
+// SWITCHES lump if there is one
+pub fn load_switches(wad: &WadData) -> Vec<(String, String)> {
+    let game = if wad.has_lump("MAP01") {
+        3
+    } else if wad.has_lump("E2M1") {
+        2
+    } else {
+        1
+    };
+    let list = wad.read_switches().unwrap_or_else(|| {
+        SWITCH_LIST
+            .iter()
+            .map(|&(off, on, game)| (off.to_string(), on.to_string(), game))
+            .collect()
+    });
+    list.into_iter()
+        .filter(|&(_, _, needs)| needs <= game)
+        .map(|(off, on, _)| (off, on))
+        .collect()
+}
+
+impl Level {
+    // A switch was flipped: show the other texture of its pair. One that
+    // only works once loses its special, one that works again pops back
+    // out after a second.
+    pub fn change_switch_texture(&mut self, index: usize, use_again: bool) {
+        if !use_again {
+            self.linedefs[index].linedef_type = 0;
+        }
+        let Ok(side) = usize::try_from(self.linedefs[index].front_sidedef) else {
+            return;
+        };
+        let Some(sidedef) = self.sidedefs.get(side) else {
+            return;
+        };
+        let places = [
+            (SwitchPlace::Top, &sidedef.upper_texture),
+            (SwitchPlace::Middle, &sidedef.middle_texture),
+            (SwitchPlace::Bottom, &sidedef.lower_texture),
+        ];
+        let found = places.into_iter().find_map(|(place, texture)| {
+            let other = self.switches.iter().find_map(|(off, on)| {
+                if off == texture {
+                    Some(on)
+                } else if on == texture {
+                    Some(off)
+                } else {
+                    None
+                }
+            })?;
+            Some((place, texture.clone(), other.clone()))
+        });
+        let Some((place, texture, other)) = found else {
+            return;
+        };
+
+        // A button already waiting to pop back doesn't press again
+        if use_again && self.buttons.iter().any(|button| button.line == index) {
+            return;
+        }
+        self.set_side_texture(side, place, other);
+        if use_again {
+            self.buttons.push(Button {
+                line: index,
+                place,
+                texture,
+                timer: BUTTON_TIME,
+            });
+        }
+    }
+
+    fn set_side_texture(&mut self, side: usize, place: SwitchPlace, texture: String) {
+        let sidedef = &mut self.sidedefs[side];
+        match place {
+            SwitchPlace::Top => sidedef.upper_texture = texture,
+            SwitchPlace::Middle => sidedef.middle_texture = texture,
+            SwitchPlace::Bottom => sidedef.lower_texture = texture,
+        }
+    }
+
+    // Pop pressed buttons back out when their time's up
+    pub fn tick_buttons(&mut self) {
+        for button in &mut self.buttons {
+            button.timer -= 1;
+        }
+        let (done, waiting) = std::mem::take(&mut self.buttons)
+            .into_iter()
+            .partition(|button| button.timer <= 0);
+        self.buttons = waiting;
+        for button in done {
+            let Ok(side) = usize::try_from(self.linedefs[button.line].front_sidedef) else {
+                continue;
+            };
+            self.set_side_texture(side, button.place, button.texture);
+        }
+    }
+}
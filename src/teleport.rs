@@ -0,0 +1,99 @@
+// Teleporters, like the original's p_telept.c: crossing a teleport line
+// from the front moves you onto the teleport destination in the tagged
+// sector, killing whatever was standing there
+use crate::info::{MobjType, MF_MISSILE, MF_SHOOTABLE};
+use crate::level::{parse_map_name, Level, MapStart};
+use crate::mobj::{heading, Target};
+
+// Tics the player can't move or turn after teleporting
+pub const TELEPORT_FREEZE: i32 = 18;
+
+impl Level {
+    // Send `who` to the destination in the sector tagged like the line.
+    // Returns false if there isn't one or something's in the way.
+    pub fn teleport(&mut self, index: usize, side: bool, who: Target) -> bool {
+        let Some(body) = self.body(who) else {
+            return false;
+        };
+        // Missiles and things crossing from behind stay put
+        if body.flags & MF_MISSILE != 0 || side {
+            return false;
+        }
+        let tag = self.linedefs[index].tag;
+        let destination = (0..self.sectors.len())
+            .filter(|&sector| self.sectors[sector].tag == tag)
+            .find_map(|sector| {
+                self.mobjs.iter().flatten().find(|mobj| {
+                    mobj.kind == MobjType::TeleportMan
+                        && self.sector_index_at(mobj.x, mobj.y) == Some(sector)
+                })
+            })
+            .map(|mobj| (mobj.x, mobj.y, mobj.angle));
+        let Some((x, y, angle)) = destination else {
+            return false;
+        };
+        if !self.telefrag(who, x, y) {
+            return false;
+        }
+
+        let (floor_z, ceiling_z) = self.sector_at(x, y).map_or((0.0, 0.0), |sector| {
+            (sector.floor_height, sector.ceiling_height)
+        });
+        match who {
+            // The game moves the player, with everything else it tracks
+            Target::Player => {
+                self.player_teleport = Some(MapStart { x, y, angle });
+                if let Some(player) = self.player_body.as_mut() {
+                    (player.x, player.y, player.z, player.angle) = (x, y, floor_z, angle);
+                }
+            }
+            Target::Mobj(mobj) => {
+                if let Some(mobj) = self.mobjs[mobj].as_mut() {
+                    (mobj.x, mobj.y, mobj.z, mobj.angle) = (x, y, floor_z, angle);
+                    mobj.floor_z = floor_z;
+                    mobj.ceiling_z = ceiling_z;
+                    (mobj.mom_x, mobj.mom_y, mobj.mom_z) = (0.0, 0.0, 0.0);
+                }
+            }
+        }
+
+        // Fog where it left and just in front of where it arrived
+        let fog = self.spawn_mobj(MobjType::TeleportFog, body.x, body.y, 0.0);
+        if let Some(fog) = self.mobjs[fog].as_mut() {
+            fog.z = body.z;
+        }
+        let (dx, dy) = heading(angle);
+        let fog = self.spawn_mobj(MobjType::TeleportFog, x + 20.0 * dx, y + 20.0 * dy, 0.0);
+        if let Some(fog) = self.mobjs[fog].as_mut() {
+            fog.z = floor_z;
+        }
+        true
+    }
+
+    // Kill everything shootable where `who` is about to land. Monsters only
+    // get to do that on the last level of Doom II, anywhere else they don't
+    // teleport.
+    pub fn telefrag(&mut self, who: Target, x: f32, y: f32) -> bool {
+        let Some(body) = self.body(who) else {
+            return false;
+        };
+        let stomped: Vec<Target> = self
+            .bodies()
+            .filter(|&(target, other)| {
+                target != who
+                    && other.flags & MF_SHOOTABLE != 0
+                    && (other.x - x).abs() < other.radius + body.radius
+                    && (other.y - y).abs() < other.radius + body.radius
+            })
+            .map(|(target, _)| target)
+            .collect();
+        let boss_level = parse_map_name(&self.map).is_some_and(|(_, map)| map == 30);
+        if who != Target::Player && !boss_level && !stomped.is_empty() {
+            return false;
+        }
+        for target in stomped {
+            self.damage_mobj(target, Some(body), Some(who), 10000);
+        }
+        true
+    }
+}
@@ -0,0 +1,143 @@
+// A small WAD put together for tests: E1M1 is one 512x512 room, lit at 160
+// with a randomly blinking light, holding player 1's start in the middle
+// facing north, an imp in two of the corners and a clip in a third
+use crate::wad_reader::{DoomEngine, WadData};
+use std::env;
+use std::fs;
+
+const ROOM_SIZE: i16 = 512;
+const BLOCK_SIZE: i16 = 128;
+
+fn words(values: &[i16]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn name(name: &str) -> [u8; 8] {
+    let mut bytes = [0; 8];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    bytes
+}
+
+// The lumps of E1M1, marker first
+pub fn room_map() -> Vec<(&'static str, Vec<u8>)> {
+    let corners = [
+        (0, 0),
+        (0, ROOM_SIZE),
+        (ROOM_SIZE, ROOM_SIZE),
+        (ROOM_SIZE, 0),
+    ];
+    let lines: Vec<(usize, usize)> = (0..4).map(|i| (i, (i + 1) % 4)).collect();
+
+    // x, y, angle, type, flags (all skills)
+    let things = words(&[
+        256, 256, 90, 1, 7, //
+        100, 100, 0, 3001, 7, //
+        400, 400, 0, 3001, 7, //
+        100, 400, 0, 2007, 7,
+    ]);
+    let vertexes = words(
+        &corners
+            .iter()
+            .flat_map(|&(x, y)| [x, y])
+            .collect::<Vec<_>>(),
+    );
+    // One sided walls, facing into the room
+    let linedefs = words(
+        &lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &(v1, v2))| [v1 as i16, v2 as i16, 1, 0, 0, i as i16, -1])
+            .collect::<Vec<_>>(),
+    );
+    let mut sidedefs = Vec::new();
+    for _ in &lines {
+        sidedefs.extend(words(&[0, 0]));
+        sidedefs.extend(name("-"));
+        sidedefs.extend(name("-"));
+        sidedefs.extend(name("STARTAN3"));
+        sidedefs.extend(words(&[0]));
+    }
+    let segs = words(
+        &lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &(v1, v2))| [v1 as i16, v2 as i16, 0, i as i16, 0, 0])
+            .collect::<Vec<_>>(),
+    );
+    let subsectors = words(&[lines.len() as i16, 0]);
+    let mut sectors = words(&[0, 128]);
+    sectors.extend(name("FLOOR4_8"));
+    sectors.extend(name("CEIL3_5"));
+    sectors.extend(words(&[160, 1, 0]));
+
+    // Every block lists the walls whose bounding box touches it
+    let blocks = ROOM_SIZE / BLOCK_SIZE + 1;
+    let mut lists: Vec<Vec<i16>> = Vec::new();
+    for row in 0..blocks {
+        for column in 0..blocks {
+            let (left, bottom) = (column * BLOCK_SIZE, row * BLOCK_SIZE);
+            let mut list = vec![0];
+            for (i, &(v1, v2)) in lines.iter().enumerate() {
+                let (x1, y1) = corners[v1];
+                let (x2, y2) = corners[v2];
+                if x1.min(x2) <= left + BLOCK_SIZE
+                    && x1.max(x2) >= left
+                    && y1.min(y2) <= bottom + BLOCK_SIZE
+                    && y1.max(y2) >= bottom
+                {
+                    list.push(i as i16);
+                }
+            }
+            list.push(-1);
+            lists.push(list);
+        }
+    }
+    let mut blockmap = vec![0, 0, blocks, blocks];
+    let mut offset = 4 + lists.len() as i16;
+    for list in &lists {
+        blockmap.push(offset);
+        offset += list.len() as i16;
+    }
+    blockmap.extend(lists.concat());
+
+    vec![
+        ("E1M1", Vec::new()),
+        ("THINGS", things),
+        ("LINEDEFS", linedefs),
+        ("SIDEDEFS", sidedefs),
+        ("VERTEXES", vertexes),
+        ("SEGS", segs),
+        ("SSECTORS", subsectors),
+        ("NODES", Vec::new()),
+        ("SECTORS", sectors),
+        ("REJECT", vec![0]),
+        ("BLOCKMAP", words(&blockmap)),
+    ]
+}
+
+// Write the lumps out as a PWAD in the temp directory, named after the test
+// using it so tests running side by side don't share a file, and load it
+pub fn load(test: &str, lumps: &[(&str, Vec<u8>)]) -> WadData {
+    let mut data = b"PWAD".to_vec();
+    data.extend((lumps.len() as i32).to_le_bytes());
+    let directory_at = 12 + lumps.iter().map(|(_, lump)| lump.len()).sum::<usize>();
+    data.extend((directory_at as i32).to_le_bytes());
+
+    let mut directory = Vec::new();
+    for (lump_name, lump) in lumps {
+        directory.extend((data.len() as i32).to_le_bytes());
+        directory.extend((lump.len() as i32).to_le_bytes());
+        directory.extend(name(lump_name));
+        data.extend(lump);
+    }
+    data.extend(directory);
+
+    let path = env::temp_dir().join(format!("rusticdoom-{}-{}.wad", test, std::process::id()));
+    fs::write(&path, data).unwrap();
+    let mut engine = DoomEngine::new(path.to_str().unwrap());
+    engine.load_wad().unwrap();
+    WadData::new(engine)
+}
@@ -0,0 +1,19 @@
+// One tic's worth of player input, the same fields vanilla Doom stores in
+// demos and sends over the network
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TicCmd {
+    // Positive is forward / right
+    pub forward_move: i8,
+    pub side_move: i8,
+    // Turning in 1/65536ths of a full circle, positive turns left
+    pub angle_turn: i16,
+    pub buttons: u8,
+}
+
+// Button bits
+pub const BT_ATTACK: u8 = 1;
+pub const BT_USE: u8 = 2;
+// A weapon change, with the weapon number in the bits above it
+pub const BT_CHANGE: u8 = 4;
+pub const BT_WEAPONMASK: u8 = 8 + 16 + 32;
+pub const BT_WEAPONSHIFT: u8 = 3;
@@ -118,11 +118,38 @@ impl Directory {
         Ok(())
     }
 
+    // Later lumps win over earlier ones with the same name, so replacements
+    // added after the originals take effect
     pub fn get_entry(&self, name: &str) -> Option<&DirectoryEntry> {
-        self.entries.iter().find(|e| e.name == name)
+        self.entries.iter().rev().find(|e| e.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    // Map lumps (THINGS, LINEDEFS, ...) repeat for every map, so look them up
+    // among the entries following the map's marker lump (e.g. "E1M1")
+    pub fn get_map_entry(&self, map: &str, name: &str) -> Option<&DirectoryEntry> {
+        let marker = self.entries.iter().position(|e| e.name == map)?;
+        self.entries[marker + 1..]
+            .iter()
+            .take(MAP_LUMP_COUNT)
+            .find(|e| e.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entry_map.contains_key(name)
     }
 }
 
+// THINGS, LINEDEFS, SIDEDEFS, VERTEXES, SEGS, SSECTORS, NODES, SECTORS, REJECT, BLOCKMAP
+const MAP_LUMP_COUNT: usize = 10;
+
+// Flats are square floor and ceiling textures
+pub const FLAT_SIZE: usize = 64;
+
+#[derive(Clone)]
 pub struct DoomEngine {
     pub wad_path: String,
     pub directory: Directory, // Changed to lowercase for Rust conventions
@@ -171,6 +198,7 @@ impl DoomEngine {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub fn read_vertex(&self, offset: i32) -> io::Result<(f32, f32)> {
         let mut file = File::open(&self.wad_path)?;
         let mut x_bytes = [0u8; 2];
@@ -192,42 +220,346 @@ impl DoomEngine {
     }
 }
 
+#[derive(Clone)]
 pub struct WadData {
     wad: DoomEngine,
 }
 
+#[allow(dead_code)]
 pub struct Vertex {
     x: f32,
     y: f32,
 }
 
+#[derive(Debug, Clone)]
 pub struct LineDef {
     pub start_vertex: Vec<i16>,
     pub end_vertex: Vec<i16>,
     pub flags: i16,
     pub linedef_type: i16,
     pub tag: i16,
-    // front_sidedef: i16,
-    // back_sidedef: i16
+    pub front_sidedef: i16,
+    // -1 for one-sided lines
+    pub back_sidedef: i16,
+}
+
+// Monsters, items, decorations and player starts placed in the map
+#[derive(Debug, Clone)]
+pub struct Thing {
+    pub x: i16,
+    pub y: i16,
+    // Degrees, 0 is east and 90 north
+    pub angle: i16,
+    pub thing_type: i16,
+    pub flags: i16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sector {
+    pub floor_height: f32,
+    pub ceiling_height: f32,
+    pub floor_texture: String,
+    pub light_level: i16,
+    pub special: i16,
+    pub tag: i16,
+}
+
+// The side of a linedef facing into a sector, "-" for no texture
+#[derive(Debug, Clone)]
+pub struct SideDef {
+    pub upper_texture: String,
+    pub lower_texture: String,
+    pub middle_texture: String,
+    pub sector: usize,
+}
+
+// An 8 character name as stored in map lumps, padded with zeros
+fn lump_name(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| b.to_ascii_uppercase() as char)
+        .collect()
+}
+
+// Part of a linedef bordering a subsector
+#[derive(Debug, Clone)]
+pub struct Seg {
+    pub linedef: usize,
+    // 0 if the seg runs along the linedef's front side, 1 for the back
+    pub direction: i16,
+}
+
+// A convex piece of a sector, the leaves of the BSP tree
+#[derive(Debug, Clone)]
+pub struct SubSector {
+    pub seg_count: usize,
+    pub first_seg: usize,
+}
+
+pub const NF_SUBSECTOR: u16 = 0x8000;
+
+// A BSP node: a partition line and the two halves of the map it splits
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub x: f32,
+    pub y: f32,
+    pub dx: f32,
+    pub dy: f32,
+    // Right (front) and left (back) children, subsectors have NF_SUBSECTOR set
+    pub children: [u16; 2],
+}
+
+// BLOCKMAP splits the map into 128x128 unit blocks, each listing the
+// linedefs that pass through it, so collision checks only look nearby
+#[derive(Debug, Clone)]
+pub struct Blockmap {
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub columns: usize,
+    pub rows: usize,
+    blocks: Vec<Vec<usize>>,
+}
+
+pub const BLOCK_SIZE: f32 = 128.0;
+
+impl Blockmap {
+    pub fn from_bytes(data: &[u8]) -> io::Result<Blockmap> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed BLOCKMAP");
+        // Offsets and line numbers are unsigned so large maps still work
+        let word = |i: usize| -> io::Result<u16> {
+            let bytes = data.get(i * 2..i * 2 + 2).ok_or_else(invalid)?;
+            Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+        };
+
+        let origin_x = word(0)? as i16 as f32;
+        let origin_y = word(1)? as i16 as f32;
+        let columns = word(2)? as usize;
+        let rows = word(3)? as usize;
+
+        let mut blocks = Vec::with_capacity(columns * rows);
+        for block in 0..columns * rows {
+            // Each list starts with a 0 and ends with 0xFFFF
+            let mut i = word(4 + block)? as usize + 1;
+            let mut lines = Vec::new();
+            loop {
+                let line = word(i)?;
+                if line == 0xFFFF {
+                    break;
+                }
+                lines.push(line as usize);
+                i += 1;
+            }
+            blocks.push(lines);
+        }
+
+        Ok(Blockmap {
+            origin_x,
+            origin_y,
+            columns,
+            rows,
+            blocks,
+        })
+    }
+
+    // The linedefs in the block at (column, row), empty outside the map
+    pub fn lines(&self, column: i32, row: i32) -> &[usize] {
+        if column < 0 || row < 0 || column as usize >= self.columns || row as usize >= self.rows {
+            return &[];
+        }
+        &self.blocks[row as usize * self.columns + column as usize]
+    }
+
+    // Which block column and row a map position falls in
+    pub fn block_at(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            ((x - self.origin_x) / BLOCK_SIZE).floor() as i32,
+            ((y - self.origin_y) / BLOCK_SIZE).floor() as i32,
+        )
+    }
+}
+
+// Doom picture format: a column-major image made of "posts" of opaque pixels.
+// Decoded here into a row-major grid of palette indices, None = transparent.
+#[derive(Debug, Clone)]
+pub struct Patch {
+    pub width: usize,
+    pub height: usize,
+    pub left_offset: i16,
+    pub top_offset: i16,
+    pub pixels: Vec<Option<u8>>,
 }
 
+impl Patch {
+    // The same picture flipped left to right, keeping its offsets
+    pub fn mirrored(&self) -> Patch {
+        let pixels = self
+            .pixels
+            .chunks(self.width.max(1))
+            .flat_map(|row| row.iter().rev().copied())
+            .collect();
+        Patch { pixels, ..*self }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> io::Result<Patch> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed patch");
+        if data.len() < 8 {
+            return Err(invalid());
+        }
+
+        let width = u16::from_le_bytes([data[0], data[1]]) as usize;
+        let height = u16::from_le_bytes([data[2], data[3]]) as usize;
+        let left_offset = i16::from_le_bytes([data[4], data[5]]);
+        let top_offset = i16::from_le_bytes([data[6], data[7]]);
+        let mut pixels = vec![None; width * height];
+
+        for x in 0..width {
+            // Each column has a 4 byte offset into the lump
+            let ofs = 8 + x * 4;
+            let column = data.get(ofs..ofs + 4).ok_or_else(invalid)?;
+            let mut pos = u32::from_le_bytes([column[0], column[1], column[2], column[3]]) as usize;
+
+            // Walk the posts until the 0xFF terminator
+            loop {
+                let top_delta = *data.get(pos).ok_or_else(invalid)?;
+                if top_delta == 0xFF {
+                    break;
+                }
+                let length = *data.get(pos + 1).ok_or_else(invalid)? as usize;
+                // skip the unused padding byte before and after the post
+                let post = data.get(pos + 3..pos + 3 + length).ok_or_else(invalid)?;
+                for (i, &index) in post.iter().enumerate() {
+                    let y = top_delta as usize + i;
+                    if y < height {
+                        pixels[y * width + x] = Some(index);
+                    }
+                }
+                pos += length + 4;
+            }
+        }
+
+        Ok(Patch {
+            width,
+            height,
+            left_offset,
+            top_offset,
+            pixels,
+        })
+    }
+}
 
 impl WadData {
     pub fn new(wad: DoomEngine) -> WadData {
         WadData { wad }
     }
 
-    pub fn read_vertexes(&self) -> io::Result<Vec<(f32, f32)>> {
-        // Look up the VERTEXES lump in the directory
-        let vertexes_entry = self
+    pub fn has_lump(&self, name: &str) -> bool {
+        self.wad.directory.contains(name)
+    }
+
+    // Read the raw bytes of any lump by name
+    pub fn read_lump(&self, name: &str) -> io::Result<Vec<u8>> {
+        let entry = self.wad.directory.get_entry(name).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} lump not found", name),
+        ))?;
+
+        let mut file = File::open(&self.wad.wad_path)?;
+        file.seek(SeekFrom::Start(entry.filepos as u64))?;
+
+        let mut data = vec![0u8; entry.size as usize];
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    // Read the raw bytes of one of a map's lumps
+    pub fn read_map_lump(&self, map: &str, name: &str) -> io::Result<Vec<u8>> {
+        let entry = self
             .wad
             .directory
-            .get_entry("VERTEXES")
+            .get_map_entry(map, name)
             .ok_or(io::Error::new(
                 io::ErrorKind::NotFound,
-                "VERTEXES lump not found",
+                format!("{} lump not found for {}", name, map),
             ))?;
 
+        let mut file = File::open(&self.wad.wad_path)?;
+        file.seek(SeekFrom::Start(entry.filepos as u64))?;
+
+        let mut data = vec![0u8; entry.size as usize];
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn read_patch(&self, name: &str) -> io::Result<Patch> {
+        Patch::from_bytes(&self.read_lump(name)?)
+    }
+
+    // Flats are raw 64x64 blocks of palette indices
+    pub fn read_flat(&self, name: &str) -> io::Result<Vec<u8>> {
+        let data = self.read_lump(name)?;
+        if data.len() < FLAT_SIZE * FLAT_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is too short for a flat", name),
+            ));
+        }
+        Ok(data[..FLAT_SIZE * FLAT_SIZE].to_vec())
+    }
+
+    // Sprite lumps are named like POSSA1: sprite, frame, rotation (0 for
+    // all angles). A second frame and rotation pair, as in SPIDA1D1, reuses
+    // the picture mirrored. Returns the picture for one frame and rotation.
+    pub fn read_sprite_frame(&self, sprite: &str, frame: char, rotation: char) -> Option<Patch> {
+        let wanted = format!("{}{}", frame, rotation);
+        let wanted_all = format!("{}0", frame);
+        let prefix_len = sprite.len();
+
+        let names: Vec<&str> = self
+            .wad
+            .directory
+            .names()
+            .filter(|name| name.starts_with(sprite) && name.len() >= prefix_len + 2)
+            .collect();
+        for name in names.iter().rev() {
+            let first = &name[prefix_len..prefix_len + 2];
+            let second = name.get(prefix_len + 2..prefix_len + 4);
+            if first == wanted || first == wanted_all {
+                return self.read_patch(name).ok();
+            }
+            if second == Some(wanted.as_str()) || second == Some(wanted_all.as_str()) {
+                return self.read_patch(name).ok().map(|patch| patch.mirrored());
+            }
+        }
+        None
+    }
+
+    // PLAYPAL holds 14 palettes of 256 RGB triplets, we convert one to 0xRRGGBB
+    pub fn read_palette(&self, index: usize) -> io::Result<Vec<u32>> {
+        let playpal = self.read_lump("PLAYPAL")?;
+        let start = index * 256 * 3;
+        let palette = playpal.get(start..start + 256 * 3).ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "PLAYPAL lump too short",
+        ))?;
+
+        Ok(palette
+            .chunks(3)
+            .map(|rgb| (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32)
+            .collect())
+    }
+
+    pub fn read_vertexes(&self, map: &str) -> io::Result<Vec<(f32, f32)>> {
+        // Look up the map's VERTEXES lump in the directory
+        let vertexes_entry =
+            self.wad
+                .directory
+                .get_map_entry(map, "VERTEXES")
+                .ok_or(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "VERTEXES lump not found",
+                ))?;
+
         println!("VERTEXES entry: {:?}", vertexes_entry);
 
         let mut file = File::open(&self.wad.wad_path)?;
@@ -257,42 +589,37 @@ impl WadData {
             vertices.push((x, y));
         }
 
-        // print vertices
-        for (i, vertex) in vertices.iter().enumerate() {
-            println!("Vertex {}: x: {}, y: {}", i, vertex.0, vertex.1);
-        }
         Ok(vertices)
     }
 
     //function for linedefs - makes up map shape
     //every linedef is bw 2 vertices and has 1-2 sidedefs containing wall textures
-    
-    pub fn read_linedefs(&self) -> io::Result<Vec<LineDef>> {
 
-        // Look up the LINEDEFS lump in the directory
-        let linedefs_entry = self
-            .wad
-            .directory
-            .get_entry("LINEDEFS")  
-            .ok_or(io::Error::new(
-                io::ErrorKind::NotFound,    //if LINEDEFS lump not found
-                "LINEDEFS lump not found",
-            ))?;
+    pub fn read_linedefs(&self, map: &str) -> io::Result<Vec<LineDef>> {
+        // Look up the map's LINEDEFS lump in the directory
+        let linedefs_entry =
+            self.wad
+                .directory
+                .get_map_entry(map, "LINEDEFS")
+                .ok_or(io::Error::new(
+                    io::ErrorKind::NotFound, //if LINEDEFS lump not found
+                    "LINEDEFS lump not found",
+                ))?;
 
         println!("LINEDEFS entry: {:?}", linedefs_entry);
 
-        let mut file = File::open(&self.wad.wad_path)?; //just opens 
+        let mut file = File::open(&self.wad.wad_path)?; //just opens
 
         // Seek to the start of the LINEDEFS lump
         file.seek(SeekFrom::Start(linedefs_entry.filepos as u64))?;
 
         // Calculate number of linedefs - each is 14 B
         let num_linedefs = linedefs_entry.size / 14;
-        let mut linedefs = Vec::with_capacity(num_linedefs as usize);   //vector to store linedefs
+        let mut linedefs = Vec::with_capacity(num_linedefs as usize); //vector to store linedefs
 
         // Read all linedefs
         for _ in 0..num_linedefs {
-            let mut buffer = [0u8; 14]; //buffer to store temp linedef data 
+            let mut buffer = [0u8; 14]; //buffer to store temp linedef data
             file.read_exact(&mut buffer)?;
 
             let start_vertex = i16::from_le_bytes([buffer[0], buffer[1]]); // an integer value from its representation as a byte array in little endian
@@ -300,8 +627,8 @@ impl WadData {
             let flags = i16::from_le_bytes([buffer[4], buffer[5]]);
             let linedef_type = i16::from_le_bytes([buffer[6], buffer[7]]);
             let tag = i16::from_le_bytes([buffer[8], buffer[9]]);
-            // let front_sidedef = i16::from_le_bytes([buffer[10], buffer[11]]);
-            // let back_sidedef = i16::from_le_bytes([buffer[12], buffer[13]]);
+            let front_sidedef = i16::from_le_bytes([buffer[10], buffer[11]]);
+            let back_sidedef = i16::from_le_bytes([buffer[12], buffer[13]]);
 
             linedefs.push(LineDef {
                 start_vertex: vec![start_vertex],
@@ -309,26 +636,185 @@ impl WadData {
                 flags,
                 linedef_type,
                 tag,
-                // front_sidedef,
-                // back_sidedef,
+                front_sidedef,
+                back_sidedef,
             });
         }
 
-        // debugs
-        for (i, linedef) in linedefs.iter().enumerate() {
-            println!(
-                "Linedef {}: start_vertex: {}, end_vertex: {}, flags: {}, type: {}, tag: {}",
-                i,
-                linedef.start_vertex[0],
-                linedef.end_vertex[0],
-                linedef.flags,
-                linedef.linedef_type,
-                linedef.tag
-            );
+        Ok(linedefs) //useful - this returns a vector of linedefs
+    }
+
+    // Each thing is 10 bytes: x, y, angle, type and flags
+    pub fn read_things(&self, map: &str) -> io::Result<Vec<Thing>> {
+        let data = self.read_map_lump(map, "THINGS")?;
+        let field = |chunk: &[u8], i: usize| i16::from_le_bytes([chunk[i], chunk[i + 1]]);
+
+        Ok(data
+            .chunks_exact(10)
+            .map(|chunk| Thing {
+                x: field(chunk, 0),
+                y: field(chunk, 2),
+                angle: field(chunk, 4),
+                thing_type: field(chunk, 6),
+                flags: field(chunk, 8),
+            })
+            .collect())
+    }
+
+    pub fn read_blockmap(&self, map: &str) -> io::Result<Blockmap> {
+        Blockmap::from_bytes(&self.read_map_lump(map, "BLOCKMAP")?)
+    }
+
+    // Each sector is 26 bytes: heights, flat names, light level, special and tag
+    pub fn read_sectors(&self, map: &str) -> io::Result<Vec<Sector>> {
+        let data = self.read_map_lump(map, "SECTORS")?;
+        let field = |chunk: &[u8], i: usize| i16::from_le_bytes([chunk[i], chunk[i + 1]]);
+
+        Ok(data
+            .chunks_exact(26)
+            .map(|chunk| Sector {
+                floor_height: field(chunk, 0) as f32,
+                ceiling_height: field(chunk, 2) as f32,
+                floor_texture: lump_name(&chunk[4..12]),
+                light_level: field(chunk, 20),
+                special: field(chunk, 22),
+                tag: field(chunk, 24),
+            })
+            .collect())
+    }
+
+    // Each sidedef is 30 bytes: offsets, three texture names and the sector
+    pub fn read_sidedefs(&self, map: &str) -> io::Result<Vec<SideDef>> {
+        let data = self.read_map_lump(map, "SIDEDEFS")?;
+
+        Ok(data
+            .chunks_exact(30)
+            .map(|chunk| SideDef {
+                upper_texture: lump_name(&chunk[4..12]),
+                lower_texture: lump_name(&chunk[12..20]),
+                middle_texture: lump_name(&chunk[20..28]),
+                sector: u16::from_le_bytes([chunk[28], chunk[29]]) as usize,
+            })
+            .collect())
+    }
+
+    // Every wall texture in TEXTURE1 and TEXTURE2 in order, with its
+    // height. Both start with a count and offsets to each texture's name,
+    // flags, width and height.
+    pub fn read_textures(&self) -> Vec<(String, f32)> {
+        let mut textures = Vec::new();
+        for lump in ["TEXTURE1", "TEXTURE2"] {
+            let Ok(data) = self.read_lump(lump) else {
+                continue;
+            };
+            let int = |i: usize| {
+                data.get(i..i + 4)
+                    .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            };
+            let count = int(0).unwrap_or(0);
+            for n in 0..count {
+                let Some(offset) = int(4 + n * 4) else {
+                    break;
+                };
+                let Some(texture) = data.get(offset..offset + 16) else {
+                    continue;
+                };
+                let height = i16::from_le_bytes([texture[14], texture[15]]);
+                textures.push((lump_name(&texture[..8]), height as f32));
+            }
         }
+        textures
+    }
 
-        Ok(linedefs) //useful - this returns a vector of linedefs
+    // The flats between the F_START and F_END markers, in order
+    pub fn read_flat_names(&self) -> Vec<String> {
+        self.wad
+            .directory
+            .names()
+            .skip_while(|&name| name != "F_START")
+            .take_while(|&name| name != "F_END")
+            .filter(|name| !name.ends_with("_START") && !name.ends_with("_END"))
+            .map(str::to_string)
+            .collect()
     }
 
+    // Boom's ANIMATED lump: 23 byte entries of a type (0 for flats, 1 for
+    // textures), the last and first names and the tics per frame, ending
+    // with type 255. Each comes back as (is texture, last, first, speed).
+    pub fn read_animated(&self) -> Option<Vec<(bool, String, String, u32)>> {
+        let data = self.read_lump("ANIMATED").ok()?;
+        Some(
+            data.chunks_exact(23)
+                .take_while(|entry| entry[0] != 255)
+                .map(|entry| {
+                    let speed = u32::from_le_bytes([entry[19], entry[20], entry[21], entry[22]]);
+                    let last = lump_name(&entry[1..10]);
+                    let first = lump_name(&entry[10..19]);
+                    (entry[0] & 1 != 0, last, first, speed)
+                })
+                .collect(),
+        )
+    }
 
+    // Boom's SWITCHES lump: 20 byte entries of the off and on texture names
+    // and the game they need (1 shareware, 2 registered, 3 Doom II), ending
+    // with game 0
+    pub fn read_switches(&self) -> Option<Vec<(String, String, i16)>> {
+        let data = self.read_lump("SWITCHES").ok()?;
+        Some(
+            data.chunks_exact(20)
+                .map(|entry| {
+                    let game = i16::from_le_bytes([entry[18], entry[19]]);
+                    (lump_name(&entry[..9]), lump_name(&entry[9..18]), game)
+                })
+                .take_while(|&(_, _, game)| game != 0)
+                .collect(),
+        )
+    }
+
+    // Each seg is 12 bytes: vertexes, angle, linedef, direction and offset
+    pub fn read_segs(&self, map: &str) -> io::Result<Vec<Seg>> {
+        let data = self.read_map_lump(map, "SEGS")?;
+
+        Ok(data
+            .chunks_exact(12)
+            .map(|chunk| Seg {
+                linedef: u16::from_le_bytes([chunk[6], chunk[7]]) as usize,
+                direction: i16::from_le_bytes([chunk[8], chunk[9]]),
+            })
+            .collect())
+    }
+
+    // Each subsector is 4 bytes: seg count and first seg
+    pub fn read_subsectors(&self, map: &str) -> io::Result<Vec<SubSector>> {
+        let data = self.read_map_lump(map, "SSECTORS")?;
+
+        Ok(data
+            .chunks_exact(4)
+            .map(|chunk| SubSector {
+                seg_count: u16::from_le_bytes([chunk[0], chunk[1]]) as usize,
+                first_seg: u16::from_le_bytes([chunk[2], chunk[3]]) as usize,
+            })
+            .collect())
+    }
+
+    // Each node is 28 bytes: partition line, two bounding boxes, two children
+    pub fn read_nodes(&self, map: &str) -> io::Result<Vec<Node>> {
+        let data = self.read_map_lump(map, "NODES")?;
+        let field = |chunk: &[u8], i: usize| i16::from_le_bytes([chunk[i], chunk[i + 1]]);
+
+        Ok(data
+            .chunks_exact(28)
+            .map(|chunk| Node {
+                x: field(chunk, 0) as f32,
+                y: field(chunk, 2) as f32,
+                dx: field(chunk, 4) as f32,
+                dy: field(chunk, 6) as f32,
+                children: [
+                    u16::from_le_bytes([chunk[24], chunk[25]]),
+                    u16::from_le_bytes([chunk[26], chunk[27]]),
+                ],
+            })
+            .collect())
+    }
 }
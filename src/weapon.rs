@@ -0,0 +1,487 @@
+// The player's weapons, like the original's p_pspr.c: the weapon and its
+// muzzle flash are "player sprites" drawn over the view, stepping through
+// states of their own that raise, lower and fire the weapon.
+use crate::combat::MISSILE_RANGE;
+use crate::enemy::{point_to_angle, MELEE_RANGE};
+use crate::game::Player;
+use crate::info::{info, Action, AmmoType, MobjType, State, StateId, WeaponType, S_NULL};
+use crate::level::Level;
+use crate::mobj::Target;
+use crate::pickup::Power;
+use crate::render::Graphics;
+use crate::ticcmd::{BT_ATTACK, BT_CHANGE, BT_WEAPONMASK, BT_WEAPONSHIFT};
+use crate::wad_reader::WadData;
+
+// Which psprite is which
+pub const PS_WEAPON: usize = 0;
+pub const PS_FLASH: usize = 1;
+
+// Weapon heights on the 320x200 view: raised, and lowered out of sight
+const WEAPON_TOP: f32 = 32.0;
+const WEAPON_BOTTOM: f32 = 128.0;
+const LOWER_SPEED: f32 = 6.0;
+const RAISE_SPEED: f32 = 6.0;
+// The held weapon sways back and forth once every this many tics
+const WEAPON_BOB_PERIOD: u32 = 64;
+// How far autoaim looks up and down for a target, and how far to either side
+const AUTOAIM_RANGE: f32 = 16.0 * 64.0;
+const AUTOAIM_SPREAD: f32 = 5.625;
+// The chainsaw turns towards its victim by this much a tic
+const SAW_TURN: f32 = 4.5;
+
+// Which weapons the game being played has: Doom II adds the super shotgun,
+// the shareware episode has no plasma rifle or BFG
+#[derive(Clone, Copy)]
+pub struct WeaponSet {
+    pub commercial: bool,
+    pub shareware: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Psprite {
+    // S_NULL when there's nothing to draw
+    pub state: StateId,
+    // Tics left in the current state, -1 for ever
+    pub tics: i32,
+    // Position on the 320x200 view
+    pub sx: f32,
+    pub sy: f32,
+}
+
+// Psprite pictures are the sprite's frame lumps for all angles, like PISGA0
+pub fn psprite_name(state: &State) -> String {
+    format!("{}{}", state.sprite, state.frame)
+}
+
+// Every picture the weapon states show, loaded up front like other graphics
+pub fn psprite_graphics(wad: &WadData) -> Graphics {
+    let mut graphics = Graphics::load::<&str>(wad, &[]);
+    let states = &info().states[info().weapon_states.clone()];
+    for state in states {
+        let name = psprite_name(state);
+        if graphics.get(&name).is_some() {
+            continue;
+        }
+        if let Some(patch) = wad.read_sprite_frame(state.sprite, state.frame, '0') {
+            graphics.insert(&name, patch);
+        }
+    }
+    graphics
+}
+
+impl Player {
+    // Start a level with the ready weapon coming up
+    pub fn setup_psprites(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        self.psprites = [Psprite::default(); 2];
+        self.pending_weapon = Some(self.ready_weapon);
+        self.bring_up_weapon(level, set);
+    }
+
+    // A number key picks a weapon, which is raised once the current one is
+    // put away
+    pub fn change_weapon(&mut self, set: WeaponSet) {
+        if self.cmd.buttons & BT_CHANGE == 0 {
+            return;
+        }
+        let number = (self.cmd.buttons & BT_WEAPONMASK) >> BT_WEAPONSHIFT;
+        let mut weapon = WeaponType::ALL[number as usize];
+
+        // The fist and shotgun keys also pick the chainsaw and super shotgun,
+        // though a berserk player holding the chainsaw can go back to the fist
+        let berserk_saw =
+            self.ready_weapon == WeaponType::Chainsaw && self.has_power(Power::Strength);
+        if weapon == WeaponType::Fist && self.owns(WeaponType::Chainsaw) && !berserk_saw {
+            weapon = WeaponType::Chainsaw;
+        }
+        if set.commercial
+            && weapon == WeaponType::Shotgun
+            && self.owns(WeaponType::SuperShotgun)
+            && self.ready_weapon != WeaponType::SuperShotgun
+        {
+            weapon = WeaponType::SuperShotgun;
+        }
+
+        let missing = set.shareware && matches!(weapon, WeaponType::Plasma | WeaponType::Bfg);
+        if self.owns(weapon) && weapon != self.ready_weapon && !missing {
+            self.pending_weapon = Some(weapon);
+        }
+    }
+
+    // Run one tic of the weapon and flash animations
+    pub fn move_psprites(&mut self, mut level: Option<&mut Level>, set: WeaponSet) {
+        for position in [PS_WEAPON, PS_FLASH] {
+            let psprite = &mut self.psprites[position];
+            if psprite.state == S_NULL || psprite.tics == -1 {
+                continue;
+            }
+            psprite.tics -= 1;
+            if psprite.tics == 0 {
+                let next = info().states[psprite.state].next;
+                self.set_psprite(position, next, level.as_deref_mut(), set);
+            }
+        }
+        // The flash is drawn wherever the weapon is
+        self.psprites[PS_FLASH].sx = self.psprites[PS_WEAPON].sx;
+        self.psprites[PS_FLASH].sy = self.psprites[PS_WEAPON].sy;
+    }
+
+    // Move a psprite to a new state, running the state's action. States
+    // that last no time at all are passed straight through.
+    fn set_psprite(
+        &mut self,
+        position: usize,
+        mut state: StateId,
+        mut level: Option<&mut Level>,
+        set: WeaponSet,
+    ) {
+        loop {
+            if state == S_NULL {
+                self.psprites[position].state = S_NULL;
+                return;
+            }
+            let next = &info().states[state];
+            self.psprites[position].state = state;
+            self.psprites[position].tics = next.tics;
+            self.weapon_action(position, next.action, level.as_deref_mut(), set);
+
+            // The action may have moved the psprite on already
+            let psprite = self.psprites[position];
+            if psprite.state == S_NULL || psprite.tics != 0 {
+                return;
+            }
+            state = info().states[psprite.state].next;
+        }
+    }
+
+    fn weapon_action(
+        &mut self,
+        position: usize,
+        action: Action,
+        mut level: Option<&mut Level>,
+        set: WeaponSet,
+    ) {
+        match action {
+            Action::WeaponReady => self.weapon_ready(level, set),
+            Action::Lower => self.lower_weapon(level, set),
+            Action::Raise => self.raise_weapon(level, set),
+            Action::ReFire | Action::CloseShotgun2 => self.refire(level, set),
+            Action::CheckReload => {
+                self.check_ammo(level, set);
+            }
+            Action::GunFlash => self.flash(0, level, set),
+            Action::Punch => {
+                if let Some(level) = level {
+                    self.punch(level);
+                }
+            }
+            Action::Saw => {
+                if let Some(level) = level {
+                    self.saw(level);
+                }
+            }
+            Action::FirePistol | Action::FireShotgun | Action::FireShotgun2 => {
+                self.use_ammo();
+                self.flash(0, level.as_deref_mut(), set);
+                if let Some(level) = level {
+                    self.shoot(level, action);
+                }
+            }
+            Action::FireCGun => {
+                if !self.has_ammo() {
+                    return;
+                }
+                self.use_ammo();
+                // Each of the two firing frames has its own flash
+                let attack = info().weapon(self.ready_weapon).attack_state;
+                let offset = self.psprites[position].state - attack;
+                self.flash(offset, level.as_deref_mut(), set);
+                if let Some(level) = level {
+                    self.shoot(level, action);
+                }
+            }
+            Action::FireMissile | Action::FireBFG => {
+                self.use_ammo();
+                let kind = match action {
+                    Action::FireMissile => MobjType::Rocket,
+                    _ => MobjType::Bfg,
+                };
+                if let Some(level) = level {
+                    level.spawn_player_missile(kind, self.angle);
+                }
+            }
+            Action::FirePlasma => {
+                self.use_ammo();
+                let offset = level
+                    .as_deref_mut()
+                    .map_or(0, |level| (level.rng.next() & 1) as usize);
+                self.flash(offset, level.as_deref_mut(), set);
+                if let Some(level) = level {
+                    level.spawn_player_missile(MobjType::Plasma, self.angle);
+                }
+            }
+            // Muzzle flashes light up the view for a tic or two
+            Action::Light0 => self.extra_light = 0,
+            Action::Light1 => self.extra_light = 1,
+            Action::Light2 => self.extra_light = 2,
+            // Weapon sounds come with sound
+            Action::OpenShotgun2 | Action::LoadShotgun2 | Action::BFGsound => {}
+            // Monster actions never turn up on weapon states
+            _ => {}
+        }
+    }
+
+    // The slope to fire bullets at: up or down at whatever's roughly in
+    // front of the player, or level if nothing is
+    fn bullet_slope(&self, level: &Level) -> f32 {
+        for delta in [0.0, -AUTOAIM_SPREAD, AUTOAIM_SPREAD] {
+            let angle = (self.angle + delta).rem_euclid(360.0);
+            let (slope, target) = level.aim_line_attack(Target::Player, angle, AUTOAIM_RANGE);
+            if target.is_some() {
+                return slope;
+            }
+        }
+        0.0
+    }
+
+    // One bullet, a little off aim unless it's the first of a burst
+    fn gun_shot(&self, level: &mut Level, slope: f32, accurate: bool) {
+        let damage = 5 * (level.rng.next() % 3 + 1);
+        let mut angle = self.angle;
+        if !accurate {
+            angle += level.angle_spread(18);
+        }
+        level.line_attack(
+            Target::Player,
+            angle.rem_euclid(360.0),
+            MISSILE_RANGE,
+            slope,
+            damage,
+        );
+    }
+
+    // The bullets of a pistol, shotgun, super shotgun or chaingun shot
+    fn shoot(&self, level: &mut Level, action: Action) {
+        let slope = self.bullet_slope(level);
+        match action {
+            Action::FireShotgun => {
+                for _ in 0..7 {
+                    self.gun_shot(level, slope, false);
+                }
+            }
+            // The super shotgun spreads wider, up and down as well
+            Action::FireShotgun2 => {
+                for _ in 0..20 {
+                    let damage = 5 * (level.rng.next() % 3 + 1);
+                    let angle = (self.angle + level.angle_spread(19)).rem_euclid(360.0);
+                    let wobble = (level.rng.next() - level.rng.next()) as f32 / 2048.0;
+                    level.line_attack(Target::Player, angle, MISSILE_RANGE, slope + wobble, damage);
+                }
+            }
+            _ => self.gun_shot(level, slope, self.refire == 0),
+        }
+    }
+
+    // Hit whatever's in reach, and turn to face it
+    fn punch(&mut self, level: &mut Level) {
+        let mut damage = (level.rng.next() % 10 + 1) * 2;
+        if self.has_power(Power::Strength) {
+            damage *= 10;
+        }
+        let angle = (self.angle + level.angle_spread(18)).rem_euclid(360.0);
+        let (slope, target) = level.aim_line_attack(Target::Player, angle, MELEE_RANGE);
+        level.line_attack(Target::Player, angle, MELEE_RANGE, slope, damage);
+        if let Some(victim) = target.and_then(|target| level.body(target)) {
+            let position = (self.x.get_value(), self.y.get_value());
+            self.angle = point_to_angle(position, (victim.x, victim.y));
+        }
+    }
+
+    // Saw into whatever's in reach, which pulls the player round and onwards
+    fn saw(&mut self, level: &mut Level) {
+        let damage = 2 * (level.rng.next() % 10 + 1);
+        let angle = (self.angle + level.angle_spread(18)).rem_euclid(360.0);
+        // Just past melee range, so the puff isn't a punch's
+        let range = MELEE_RANGE + 1.0;
+        let (slope, target) = level.aim_line_attack(Target::Player, angle, range);
+        level.line_attack(Target::Player, angle, range, slope, damage);
+        let Some(victim) = target.and_then(|target| level.body(target)) else {
+            return;
+        };
+
+        let position = (self.x.get_value(), self.y.get_value());
+        let goal = point_to_angle(position, (victim.x, victim.y));
+        let delta = (goal - self.angle + 540.0).rem_euclid(360.0) - 180.0;
+        let angle = if delta < -SAW_TURN {
+            goal + 90.0 / 21.0
+        } else if delta < 0.0 {
+            self.angle - SAW_TURN
+        } else if delta > SAW_TURN {
+            goal - 90.0 / 21.0
+        } else {
+            self.angle + SAW_TURN
+        };
+        self.angle = angle.rem_euclid(360.0);
+        self.just_attacked = true;
+    }
+
+    // The player died: the weapon goes down and stays there
+    pub fn drop_weapon(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        let down = info().weapon(self.ready_weapon).down_state;
+        self.set_psprite(PS_WEAPON, down, level, set);
+    }
+
+    fn owns(&self, weapon: WeaponType) -> bool {
+        self.weapon_owned[weapon as usize]
+    }
+
+    // Whether the ready weapon has ammo for a shot, the fist and chainsaw
+    // always do
+    fn has_ammo(&self) -> bool {
+        let weapon = info().weapon(self.ready_weapon);
+        match weapon.ammo {
+            Some(ammo) => self.ammo[ammo as usize] >= weapon.per_shot,
+            None => true,
+        }
+    }
+
+    fn use_ammo(&mut self) {
+        let weapon = info().weapon(self.ready_weapon);
+        if let Some(ammo) = weapon.ammo {
+            self.ammo[ammo as usize] -= weapon.per_shot;
+        }
+    }
+
+    // True if the ready weapon can fire. If it can't, it's put away for the
+    // best weapon that still has ammo.
+    fn check_ammo(&mut self, level: Option<&mut Level>, set: WeaponSet) -> bool {
+        if self.has_ammo() {
+            return true;
+        }
+        self.pending_weapon = Some(self.best_weapon(set));
+        let down = info().weapon(self.ready_weapon).down_state;
+        self.set_psprite(PS_WEAPON, down, level, set);
+        false
+    }
+
+    // The original's order of preference when a weapon runs dry
+    fn best_weapon(&self, set: WeaponSet) -> WeaponType {
+        let ammo = |kind: AmmoType| self.ammo[kind as usize];
+        if self.owns(WeaponType::Plasma) && ammo(AmmoType::Cell) > 0 && !set.shareware {
+            WeaponType::Plasma
+        } else if self.owns(WeaponType::SuperShotgun) && ammo(AmmoType::Shell) > 2 && set.commercial
+        {
+            WeaponType::SuperShotgun
+        } else if self.owns(WeaponType::Chaingun) && ammo(AmmoType::Clip) > 0 {
+            WeaponType::Chaingun
+        } else if self.owns(WeaponType::Shotgun) && ammo(AmmoType::Shell) > 0 {
+            WeaponType::Shotgun
+        } else if ammo(AmmoType::Clip) > 0 {
+            WeaponType::Pistol
+        } else if self.owns(WeaponType::Chainsaw) {
+            WeaponType::Chainsaw
+        } else if self.owns(WeaponType::Missile) && ammo(AmmoType::Missile) > 0 {
+            WeaponType::Missile
+        } else if self.owns(WeaponType::Bfg) && ammo(AmmoType::Cell) > 40 && !set.shareware {
+            WeaponType::Bfg
+        } else {
+            WeaponType::Fist
+        }
+    }
+
+    // Make the pending weapon the ready one and start raising it from out
+    // of sight
+    fn bring_up_weapon(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        if let Some(weapon) = self.pending_weapon.take() {
+            self.ready_weapon = weapon;
+        }
+        self.psprites[PS_WEAPON].sy = WEAPON_BOTTOM;
+        let up = info().weapon(self.ready_weapon).up_state;
+        self.set_psprite(PS_WEAPON, up, level, set);
+    }
+
+    fn fire_weapon(&mut self, mut level: Option<&mut Level>, set: WeaponSet) {
+        if !self.check_ammo(level.as_deref_mut(), set) {
+            return;
+        }
+        let attack = info().weapon(self.ready_weapon).attack_state;
+        self.set_psprite(PS_WEAPON, attack, level.as_deref_mut(), set);
+
+        // Gunfire wakes up the monsters that hear it
+        if let Some(level) = level {
+            level.noise_alert(Target::Player, self.x.get_value(), self.y.get_value());
+        }
+    }
+
+    // The weapon is up and waiting: fire, put it away, or sway it about
+    fn weapon_ready(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        // Put it away to switch weapons, or because the player died
+        if self.pending_weapon.is_some() || self.health <= 0 {
+            let down = info().weapon(self.ready_weapon).down_state;
+            self.set_psprite(PS_WEAPON, down, level, set);
+            return;
+        }
+
+        // The rocket launcher and BFG need the trigger let go between shots
+        if self.cmd.buttons & BT_ATTACK != 0 {
+            let single_shot = matches!(self.ready_weapon, WeaponType::Missile | WeaponType::Bfg);
+            if !self.attack_down || !single_shot {
+                self.attack_down = true;
+                self.fire_weapon(level, set);
+                return;
+            }
+        } else {
+            self.attack_down = false;
+        }
+
+        // Bob the weapon in an arc as the player moves
+        let time = level.map_or(0, |level| level.stats.time);
+        let phase = (time % WEAPON_BOB_PERIOD) as f32 / WEAPON_BOB_PERIOD as f32;
+        let angle = phase * std::f32::consts::TAU;
+        let psprite = &mut self.psprites[PS_WEAPON];
+        psprite.sx = 1.0 + self.bob * angle.cos();
+        psprite.sy = WEAPON_TOP + self.bob * angle.sin().abs();
+    }
+
+    // Keep firing while the trigger is held, unless switching weapons
+    fn refire(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        let attacking = self.cmd.buttons & BT_ATTACK != 0;
+        if attacking && self.pending_weapon.is_none() && self.health > 0 {
+            self.refire += 1;
+            self.fire_weapon(level, set);
+        } else {
+            self.refire = 0;
+            self.check_ammo(level, set);
+        }
+    }
+
+    fn lower_weapon(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        let psprite = &mut self.psprites[PS_WEAPON];
+        psprite.sy += LOWER_SPEED;
+        if psprite.sy < WEAPON_BOTTOM {
+            return;
+        }
+        // The dead keep their weapon down and out of sight
+        if self.health <= 0 {
+            psprite.sy = WEAPON_BOTTOM;
+            return;
+        }
+        self.bring_up_weapon(level, set);
+    }
+
+    fn raise_weapon(&mut self, level: Option<&mut Level>, set: WeaponSet) {
+        let psprite = &mut self.psprites[PS_WEAPON];
+        psprite.sy -= RAISE_SPEED;
+        if psprite.sy > WEAPON_TOP {
+            return;
+        }
+        psprite.sy = WEAPON_TOP;
+        let ready = info().weapon(self.ready_weapon).ready_state;
+        self.set_psprite(PS_WEAPON, ready, level, set);
+    }
+
+    // Show the ready weapon's muzzle flash, or one of its alternatives
+    fn flash(&mut self, offset: usize, level: Option<&mut Level>, set: WeaponSet) {
+        let flash = info().weapon(self.ready_weapon).flash_state;
+        self.set_psprite(PS_FLASH, flash + offset, level, set);
+    }
+}
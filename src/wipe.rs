@@ -0,0 +1,70 @@
+use crate::random::Random;
+use crate::render::{HEIGHT, SCALE, WIDTH};
+
+// The screen melts in strips 2 pixels wide on the original 320 wide screen
+const COLUMN_WIDTH: usize = 2 * SCALE;
+const COLUMNS: usize = WIDTH / COLUMN_WIDTH;
+// Melt distances are tracked in unscaled screen units
+const MELT_HEIGHT: i32 = (HEIGHT / SCALE) as i32;
+
+// The classic melt: columns of the old screen slide down at slightly
+// different times and speeds, uncovering the new screen behind them.
+pub struct Wipe {
+    start: Vec<u32>,
+    end: Vec<u32>,
+    // How far each column has slid down, negative while it's still waiting
+    offsets: Vec<i32>,
+}
+
+impl Wipe {
+    pub fn new(start: Vec<u32>, end: Vec<u32>, rng: &mut Random) -> Self {
+        // Each column starts a little before or after its neighbour
+        let mut offsets = vec![0; COLUMNS];
+        offsets[0] = -(rng.next() % 16);
+        for i in 1..COLUMNS {
+            let step = rng.next() % 3 - 1;
+            offsets[i] = (offsets[i - 1] + step).clamp(-15, 0);
+        }
+
+        Wipe {
+            start,
+            end,
+            offsets,
+        }
+    }
+
+    // Advance the melt by one tic, returns true once the new screen is fully shown
+    pub fn tick(&mut self) -> bool {
+        let mut done = true;
+
+        for offset in self.offsets.iter_mut() {
+            if *offset < 0 {
+                *offset += 1;
+                done = false;
+            } else if *offset < MELT_HEIGHT {
+                // Columns accelerate over their first few tics
+                let dy = if *offset < 16 { *offset + 1 } else { 8 };
+                *offset = (*offset + dy).min(MELT_HEIGHT);
+                done = false;
+            }
+        }
+
+        done
+    }
+
+    pub fn draw(&self, buffer: &mut [u32]) {
+        for (column, &offset) in self.offsets.iter().enumerate() {
+            let melted = offset.max(0) as usize * SCALE;
+
+            for x in column * COLUMN_WIDTH..(column + 1) * COLUMN_WIDTH {
+                for y in 0..HEIGHT {
+                    buffer[y * WIDTH + x] = if y < melted {
+                        self.end[y * WIDTH + x]
+                    } else {
+                        self.start[(y - melted) * WIDTH + x]
+                    };
+                }
+            }
+        }
+    }
+}